@@ -5,28 +5,48 @@
 
 //! Implementation of the `#[autoimpl]` attribute
 
-use crate::generics::{clause_to_toks, WhereClause};
-use crate::{ForDeref, SimplePath};
+use crate::generics::{ImplGenericsBuilder, TypeParamBound, WhereClause, WherePredicate};
+use crate::{Dispatch, ForDeref, SimplePath};
 use proc_macro2::{Span, TokenStream as Toks};
 use proc_macro_error::emit_error;
-use quote::{quote, TokenStreamExt};
+use quote::{quote, ToTokens, TokenStreamExt};
+use syn::ext::IdentExt;
+use syn::parse::ParseStream;
+use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::token::Comma;
+use syn::visit::{self, Visit};
 use syn::{
-    parse2, Field, Fields, Ident, Index, Item, ItemEnum, ItemStruct, Member, Path, PathArguments,
-    Token,
+    parse2, Attribute, Expr, Field, Fields, Ident, Index, Item, ItemEnum, ItemStruct, ItemUnion,
+    LitStr, Member, Pat, Path, PathArguments, Token, Type, Visibility,
 };
 
+#[cfg(feature = "arbitrary")]
+mod impl_arbitrary;
 mod impl_misc;
+mod impl_ops;
+#[cfg(feature = "serde")]
+mod impl_serde;
 mod impl_using;
+#[cfg(feature = "zeroize")]
+mod impl_zeroize;
 
+#[cfg(feature = "arbitrary")]
+pub use impl_arbitrary::*;
 pub use impl_misc::*;
+pub use impl_ops::*;
+#[cfg(feature = "serde")]
+pub use impl_serde::*;
 pub use impl_using::*;
+#[cfg(feature = "zeroize")]
+pub use impl_zeroize::*;
 
 /// List of all builtin trait implementations
 pub const STD_IMPLS: &[&dyn ImplTrait] = &[
     &ImplClone,
     &ImplCopy,
+    &ImplSend,
+    &ImplSync,
     &ImplDebug,
     &ImplDefault,
     &ImplPartialEq,
@@ -34,12 +54,65 @@ pub const STD_IMPLS: &[&dyn ImplTrait] = &[
     &ImplPartialOrd,
     &ImplOrd,
     &ImplHash,
+    &ImplAdd,
+    &ImplSub,
+    &ImplMul,
+    &ImplDiv,
+    &ImplRem,
+    &ImplBitAnd,
+    &ImplBitOr,
+    &ImplBitXor,
+    &ImplShl,
+    &ImplShr,
+    &ImplAddAssign,
+    &ImplSubAssign,
+    &ImplMulAssign,
+    &ImplDivAssign,
+    &ImplRemAssign,
+    &ImplBitAndAssign,
+    &ImplBitOrAssign,
+    &ImplBitXorAssign,
+    &ImplShlAssign,
+    &ImplShrAssign,
     &ImplBorrow,
     &ImplBorrowMut,
     &ImplAsRef,
     &ImplAsMut,
+    &ImplDisplay,
+    &ImplLowerHex,
+    &ImplUpperHex,
+    &ImplOctal,
+    &ImplBinary,
+    &ImplPointer,
+    &ImplFmtWrite,
+    &ImplError,
+    &ImplBufRead,
+    &ImplFrom,
+    &ImplTryFrom,
+    &ImplInto,
+    &ImplFuture,
+    &ImplHasher,
+    &ImplIterator,
+    &ImplIntoIterator,
+    &ImplFromIterator,
+    &ImplExtend,
     &ImplDeref,
     &ImplDerefMut,
+    &ImplIndex,
+    &ImplIndexMut,
+    &ImplRangeBounds,
+    &ImplInner,
+    &ImplStrNewtype,
+    #[cfg(feature = "serde")]
+    &ImplSerialize,
+    #[cfg(feature = "serde")]
+    &ImplDeserialize,
+    #[cfg(feature = "zeroize")]
+    &ImplZeroize,
+    #[cfg(feature = "zeroize")]
+    &ImplZeroizeOnDrop,
+    #[cfg(feature = "arbitrary")]
+    &ImplArbitrary,
 ];
 
 /// Trait required by extensions
@@ -54,6 +127,26 @@ pub trait ImplTrait {
         false
     }
 
+    /// True if this target is an unsafe trait
+    ///
+    /// Such targets must be written with the `unsafe` keyword, e.g.
+    /// `#[autoimpl(unsafe Send)]`; other targets must not be.
+    ///
+    /// Default implementation: `false`
+    fn require_unsafe(&self) -> bool {
+        false
+    }
+
+    /// True if `Rc<T>` and `Arc<T>` implement this trait for all `T`
+    ///
+    /// If so, `T: trait` bounds are not required for type parameters used
+    /// only within these pointer types (e.g. `Clone`).
+    ///
+    /// Default implementation: `false`
+    fn exempt_shared_pointers(&self) -> bool {
+        false
+    }
+
     /// True if this target supports ignoring fields
     ///
     /// Default implementation: `false`
@@ -68,6 +161,56 @@ pub trait ImplTrait {
         None
     }
 
+    /// True if this target supports redacting fields (`redact self.FIELD`)
+    ///
+    /// Default implementation: `false`
+    fn support_redact(&self) -> bool {
+        false
+    }
+
+    /// True if this target supports field format overrides (`fmt self.FIELD as EXPR`)
+    ///
+    /// Default implementation: `false`
+    fn support_fmt(&self) -> bool {
+        false
+    }
+
+    /// True if this target is implemented field-wise, thus supports inferring
+    /// bounds from field types (`bound(fields)`)
+    ///
+    /// Default implementation: `false`
+    fn support_field_bounds(&self) -> bool {
+        false
+    }
+
+    /// True if this target supports per-variant format strings on enums
+    /// (`fmt Self::VARIANT(..) => "FORMAT"`)
+    ///
+    /// Default implementation: `false`
+    fn support_variant_fmt(&self) -> bool {
+        false
+    }
+
+    /// True if this target supports `#[cfg(..)]` attributes on fields
+    ///
+    /// If so, generated code referring to a field must be subject to the
+    /// field's `#[cfg(..)]` attributes (see [`field_cfgs`]).
+    ///
+    /// Default implementation: `false`
+    fn support_field_cfgs(&self) -> bool {
+        false
+    }
+
+    /// True if this target supports ignoring fields of enum variants
+    ///
+    /// For enums, `ignore self.FIELD` ignores the field of that name (or
+    /// index) within each variant having such a field.
+    ///
+    /// Default implementation: `false`
+    fn support_enum_ignore(&self) -> bool {
+        false
+    }
+
     /// True if this target supports using a field
     ///
     /// Default implementation: `false`
@@ -75,6 +218,50 @@ pub trait ImplTrait {
         false
     }
 
+    /// True if this target cannot be implemented without a field to use
+    ///
+    /// Such targets fall back to the default field (see `#[autoimpl(using)]`)
+    /// when no `using` is given. Targets for which `using` is optional (e.g.
+    /// `PartialEq`, which otherwise compares all fields) must return `false`.
+    ///
+    /// ```
+    /// # use impl_tools_lib::autoimpl::{ImplDeref, ImplPartialEq, ImplTrait};
+    /// assert!(ImplDeref.require_using());
+    /// assert!(!ImplPartialEq.require_using());
+    /// ```
+    ///
+    /// Default implementation: `false`
+    fn require_using(&self) -> bool {
+        false
+    }
+
+    /// True if this target supports a visibility (e.g. `pub(crate) Inner`)
+    ///
+    /// This is intended for targets generating inherent items.
+    ///
+    /// Default implementation: `false`
+    fn support_visibility(&self) -> bool {
+        false
+    }
+
+    /// True if this target supports comparison by keys (`by KEY, ...`)
+    ///
+    /// Default implementation: `false`
+    fn support_by(&self) -> bool {
+        false
+    }
+
+    /// True if this target supports the named option (`with NAME`)
+    ///
+    /// Options are shared between all targets of an attribute; it is an error
+    /// if no target supports a given option.
+    ///
+    /// Default implementation: `false`
+    fn support_option(&self, name: &str) -> bool {
+        let _ = name;
+        false
+    }
+
     /// Generate an impl for an enum item
     ///
     /// The default implementation is a wrapper around [`Self::enum_items`]
@@ -82,19 +269,24 @@ pub trait ImplTrait {
     /// multiple implementation items. It is not recommended to modify the
     /// generics.
     fn enum_impl(&self, item: &ItemEnum, args: &ImplArgs) -> Result<Toks> {
-        let type_ident = &item.ident;
-        let (impl_generics, ty_generics, item_wc) = item.generics.split_for_impl();
-
         let (path, items) = self.enum_items(item, args)?;
 
-        let wc = clause_to_toks(&args.clause, item_wc, &path);
+        Ok(ImplGenericsBuilder::new(&item.generics)
+            .clause(args.clause.as_ref())
+            .impl_block(&item.ident, &path, &items))
+    }
+
+    /// Generate an impl for a union item
+    ///
+    /// The default implementation is a wrapper around [`Self::union_items`]
+    /// and suffices for most cases. It is not recommended to modify the
+    /// generics.
+    fn union_impl(&self, item: &ItemUnion, args: &ImplArgs) -> Result<Toks> {
+        let (path, items) = self.union_items(item, args)?;
 
-        Ok(quote! {
-            #[automatically_derived]
-            impl #impl_generics #path for #type_ident #ty_generics #wc {
-                #items
-            }
-        })
+        Ok(ImplGenericsBuilder::new(&item.generics)
+            .clause(args.clause.as_ref())
+            .impl_block(&item.ident, &path, &items))
     }
 
     /// Generate an impl for a struct item
@@ -104,19 +296,11 @@ pub trait ImplTrait {
     /// multiple implementation items. It is not recommended to modify the
     /// generics.
     fn struct_impl(&self, item: &ItemStruct, args: &ImplArgs) -> Result<Toks> {
-        let type_ident = &item.ident;
-        let (impl_generics, ty_generics, item_wc) = item.generics.split_for_impl();
-
         let (path, items) = self.struct_items(item, args)?;
 
-        let wc = clause_to_toks(&args.clause, item_wc, &path);
-
-        Ok(quote! {
-            #[automatically_derived]
-            impl #impl_generics #path for #type_ident #ty_generics #wc {
-                #items
-            }
-        })
+        Ok(ImplGenericsBuilder::new(&item.generics)
+            .clause(args.clause.as_ref())
+            .impl_block(&item.ident, &path, &items))
     }
 
     /// Generate enum items
@@ -137,6 +321,18 @@ pub trait ImplTrait {
         Err(Error::CallSite("enum expansion not supported"))
     }
 
+    /// Generate union items
+    ///
+    /// On success, this method returns the tuple `(trait_path, items)`, as
+    /// for [`Self::enum_items`]. Since fields of a union may not be read
+    /// safely, this is only useful for marker traits and `Copy`-based impls.
+    ///
+    /// Default implementation: generate error `union expansion not supported`
+    fn union_items(&self, item: &ItemUnion, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let _ = (item, args);
+        Err(Error::CallSite("union expansion not supported"))
+    }
+
     /// Generate struct items
     ///
     /// On success, this method returns the tuple `(trait_path, items)`. These
@@ -155,24 +351,56 @@ pub trait ImplTrait {
 mod kw {
     use syn::custom_keyword;
 
+    custom_keyword!(bound);
+    custom_keyword!(by);
+    custom_keyword!(cfg);
+    custom_keyword!(clone);
+    custom_keyword!(debug);
+    custom_keyword!(default);
+    custom_keyword!(dispatch);
+    custom_keyword!(fields);
+    custom_keyword!(fmt);
+    custom_keyword!(hidden);
     custom_keyword!(ignore);
+    custom_keyword!(last);
+    custom_keyword!(only);
+    custom_keyword!(redact);
+    custom_keyword!(rename);
+    custom_keyword!(test_only);
+    custom_keyword!(used);
     custom_keyword!(using);
+    custom_keyword!(with);
 }
 
 /// The `#[autoimpl]` attribute
 pub enum Attr {
     /// Autoimpl for types supporting `Deref`
     ForDeref(ForDeref),
+    /// Autoimpl for an enum by dispatch to variant payloads
+    Dispatch(Dispatch),
     /// Autoimpl for trait targets
     ImplTraits(ImplTraits),
 }
 
 /// Autoimpl for trait targets
 pub struct ImplTraits {
-    targets: Vec<Path>,
+    targets: Vec<Target>,
     args: ImplArgs,
+    /// Span of `(last)` in `using self.(last)`
+    using_last: Option<Span>,
+    /// Fields listed by `only` (resolved to `args.ignores` on expansion)
+    only: Vec<Member>,
+    /// Wrap generated impls in `const _: () = { .. };` (flag `const`)
+    in_const: bool,
+    /// Predicate given by `cfg(PRED)` and/or `test_only`
+    cfg: Option<Toks>,
+    /// Targets with their own arguments (e.g. `Debug(ignore self.x)`)
+    groups: Vec<ImplTraits>,
 }
 
+/// A target trait with optional visibility and `unsafe` keyword
+type Target = (Option<Visibility>, Option<Token![unsafe]>, Path);
+
 /// Error type
 pub enum Error {
     /// Emit an error clarifying that `using self.FIELD` is required
@@ -214,23 +442,85 @@ mod parsing {
             if lookahead.peek(Token![for]) {
                 return input.call(ForDeref::parse).map(Attr::ForDeref);
             }
+            if lookahead.peek(kw::dispatch) {
+                return input.call(Dispatch::parse).map(Attr::Dispatch);
+            }
 
             let mut targets = Vec::new();
-            let mut using = None;
-            let mut ignores = Vec::new();
-            let mut clause = None;
+            let mut groups = Vec::new();
+            let mut krate = None;
+            let mut in_const = false;
+            let mut cfg = None;
+            let mut test_only = None;
 
             while !input.is_empty() {
                 if lookahead.peek(Token![where])
+                    || lookahead.peek(kw::bound)
                     || lookahead.peek(kw::using)
                     || lookahead.peek(kw::ignore)
+                    || lookahead.peek(kw::only)
+                    || lookahead.peek(kw::with)
+                    || lookahead.peek(kw::by)
+                    || lookahead.peek(kw::redact)
+                    || lookahead.peek(kw::fmt)
                 {
                     break;
                 }
 
                 if empty_or_trailing {
-                    if lookahead.peek(Ident) {
-                        targets.push(input.parse()?);
+                    if krate.is_none() && lookahead.peek(Token![crate]) && input.peek2(Token![=]) {
+                        let _ = input.parse::<Token![crate]>()?;
+                        let _ = input.parse::<Token![=]>()?;
+                        krate = Some(input.call(Path::parse_mod_style)?);
+                        empty_or_trailing = false;
+                        lookahead = input.lookahead1();
+                        continue;
+                    } else if !in_const && lookahead.peek(Token![const]) {
+                        let _ = input.parse::<Token![const]>()?;
+                        in_const = true;
+                        empty_or_trailing = false;
+                        lookahead = input.lookahead1();
+                        continue;
+                    } else if cfg.is_none()
+                        && lookahead.peek(kw::cfg)
+                        && input.peek2(syn::token::Paren)
+                    {
+                        let _: kw::cfg = input.parse()?;
+                        let content;
+                        let _ = syn::parenthesized!(content in input);
+                        cfg = Some(content.parse()?);
+                        empty_or_trailing = false;
+                        lookahead = input.lookahead1();
+                        continue;
+                    } else if test_only.is_none() && lookahead.peek(kw::test_only) {
+                        let _: kw::test_only = input.parse()?;
+                        test_only = Some(if input.peek(Token![=]) {
+                            let _ = input.parse::<Token![=]>()?;
+                            let feature: LitStr = input.parse()?;
+                            quote! { any(test, feature = #feature) }
+                        } else {
+                            quote! { test }
+                        });
+                        empty_or_trailing = false;
+                        lookahead = input.lookahead1();
+                        continue;
+                    } else if lookahead.peek(Token![pub]) {
+                        let vis = input.parse()?;
+                        let target = (Some(vis), None, input.parse()?);
+                        push_target(input, target, &mut targets, &mut groups)?;
+                        empty_or_trailing = false;
+                        lookahead = input.lookahead1();
+                        continue;
+                    } else if lookahead.peek(Token![unsafe]) {
+                        let unsafety = input.parse()?;
+                        let target = (None, Some(unsafety), input.parse()?);
+                        push_target(input, target, &mut targets, &mut groups)?;
+                        empty_or_trailing = false;
+                        lookahead = input.lookahead1();
+                        continue;
+                    } else if lookahead.peek(Ident) {
+                        let target = (None, None, input.parse()?);
+                        push_target(input, target, &mut targets, &mut groups)?;
                         empty_or_trailing = false;
                         lookahead = input.lookahead1();
                         continue;
@@ -244,44 +534,269 @@ mod parsing {
                 return Err(lookahead.error());
             }
 
-            while !input.is_empty() {
-                lookahead = input.lookahead1();
-                if clause.is_none() && using.is_none() && lookahead.peek(kw::using) {
-                    let _: kw::using = input.parse()?;
+            let args_span = (!input.is_empty()).then(|| input.span());
+            let (mut args, using_last, only) = parse_args(input)?;
+            args.root = krate;
+            if let (Some(span), true) = (args_span, targets.is_empty() && !groups.is_empty()) {
+                return Err(syn::Error::new(
+                    span,
+                    "arguments apply only to targets without parenthesized arguments",
+                ));
+            }
+
+            let cfg = match (cfg, test_only) {
+                (Some(pred), Some(test)) => Some(quote! { all(#pred, #test) }),
+                (cfg, test) => cfg.or(test),
+            };
+
+            Ok(Attr::ImplTraits(ImplTraits {
+                targets,
+                args,
+                using_last,
+                only,
+                in_const,
+                cfg,
+                groups,
+            }))
+        }
+    }
+
+    /// Push `target` to `targets`, or with its own (parenthesized) arguments
+    /// to `groups`
+    fn push_target(
+        input: ParseStream,
+        target: Target,
+        targets: &mut Vec<Target>,
+        groups: &mut Vec<ImplTraits>,
+    ) -> Result<()> {
+        if input.peek(syn::token::Paren) {
+            let content;
+            let _ = syn::parenthesized!(content in input);
+            let (args, using_last, only) = parse_args(&content)?;
+            groups.push(ImplTraits {
+                targets: vec![target],
+                args,
+                using_last,
+                only,
+                in_const: false,
+                cfg: None,
+                groups: vec![],
+            });
+        } else {
+            targets.push(target);
+        }
+        Ok(())
+    }
+
+    /// Parse arguments following targets: `using`, `ignore`, `where`, etc.
+    ///
+    /// Returns the arguments, the span of `using self.(last)` and fields listed by `only`.
+    fn parse_args(input: ParseStream) -> Result<(ImplArgs, Option<Span>, Vec<Member>)> {
+        let mut using = None;
+        let mut using_last = None;
+        let mut ignores = Vec::new();
+        let mut ignore_values = Vec::new();
+        let mut only = Vec::new();
+        let mut options = Vec::new();
+        let mut by = Vec::new();
+        let mut redacts = Vec::new();
+        let mut fmts = Vec::new();
+        let mut variant_fmts = Vec::new();
+        let mut clause = None;
+        let mut bound_override = false;
+        let mut bound_fields = false;
+        let mut bound_used = false;
+
+        while !input.is_empty() {
+            let lookahead = input.lookahead1();
+            if clause.is_none()
+                && using.is_none()
+                && using_last.is_none()
+                && lookahead.peek(kw::using)
+            {
+                let _: kw::using = input.parse()?;
+                let _ = input.parse::<Token![self]>()?;
+                let _ = input.parse::<Token![.]>()?;
+                if input.peek(syn::token::Paren) {
+                    let content;
+                    let paren = syn::parenthesized!(content in input);
+                    let _: kw::last = content.parse()?;
+                    using_last = Some(paren.span.join());
+                } else {
+                    using = Some(input.parse()?);
+                }
+            } else if clause.is_none() && ignores.is_empty() && lookahead.peek(kw::ignore) {
+                let _: kw::ignore = input.parse()?;
+                loop {
                     let _ = input.parse::<Token![self]>()?;
                     let _ = input.parse::<Token![.]>()?;
-                    using = Some(input.parse()?);
-                } else if clause.is_none() && ignores.is_empty() && lookahead.peek(kw::ignore) {
-                    let _: kw::ignore = input.parse()?;
+                    let member: Member = input.parse()?;
+                    if input.peek(Token![=]) {
+                        let _ = input.parse::<Token![=]>()?;
+                        ignore_values.push((member.clone(), input.parse()?));
+                    }
+                    ignores.push(member);
+
+                    if input.peek(Comma) {
+                        let _ = input.parse::<Comma>()?;
+                        if input.peek(Token![self]) {
+                            continue;
+                        }
+                    }
+                    break;
+                }
+            } else if clause.is_none() && only.is_empty() && lookahead.peek(kw::only) {
+                let _: kw::only = input.parse()?;
+                loop {
+                    let _ = input.parse::<Token![self]>()?;
+                    let _ = input.parse::<Token![.]>()?;
+                    only.push(input.parse()?);
+
+                    if input.peek(Comma) {
+                        let _ = input.parse::<Comma>()?;
+                        if input.peek(Token![self]) {
+                            continue;
+                        }
+                    }
+                    break;
+                }
+            } else if clause.is_none() && redacts.is_empty() && lookahead.peek(kw::redact) {
+                let _: kw::redact = input.parse()?;
+                loop {
                     let _ = input.parse::<Token![self]>()?;
                     let _ = input.parse::<Token![.]>()?;
-                    ignores.push(input.parse()?);
-                    while input.peek(Comma) {
+                    redacts.push(input.parse()?);
+
+                    if input.peek(Comma) {
                         let _ = input.parse::<Comma>()?;
                         if input.peek(Token![self]) {
-                            let _ = input.parse::<Token![self]>()?;
-                            let _ = input.parse::<Token![.]>()?;
-                            ignores.push(input.parse()?);
                             continue;
                         }
+                    }
+                    break;
+                }
+            } else if clause.is_none()
+                && fmts.is_empty()
+                && variant_fmts.is_empty()
+                && lookahead.peek(kw::fmt)
+            {
+                let _: kw::fmt = input.parse()?;
+                if input.peek(Token![Self]) {
+                    loop {
+                        let pat = Pat::parse_multi(input)?;
+                        let _ = input.parse::<Token![=>]>()?;
+                        variant_fmts.push((pat, input.parse()?));
+
+                        if input.peek(Comma) {
+                            let _ = input.parse::<Comma>()?;
+                            if input.peek(Token![Self]) {
+                                continue;
+                            }
+                        }
                         break;
                     }
-                } else if lookahead.peek(Token![where]) {
-                    // Note: assigning to clause disables other match branches since clause must come last!
-                    clause = Some(input.parse()?);
                 } else {
-                    return Err(lookahead.error());
+                    loop {
+                        let _ = input.parse::<Token![self]>()?;
+                        let _ = input.parse::<Token![.]>()?;
+                        let member: Member = input.parse()?;
+                        let _ = input.parse::<Token![as]>()?;
+                        fmts.push((member, input.parse()?));
+
+                        if input.peek(Comma) {
+                            let _ = input.parse::<Comma>()?;
+                            if input.peek(Token![self]) {
+                                continue;
+                            }
+                        }
+                        break;
+                    }
+                }
+            } else if clause.is_none() && options.is_empty() && lookahead.peek(kw::with) {
+                let _: kw::with = input.parse()?;
+                options.push(input.parse()?);
+                while input.peek(Comma) && input.peek2(Ident) {
+                    let _ = input.parse::<Comma>()?;
+                    options.push(input.parse()?);
+                }
+            } else if clause.is_none() && by.is_empty() && lookahead.peek(kw::by) {
+                let _: kw::by = input.parse()?;
+                by.push(input.parse()?);
+                while input.peek(Comma) {
+                    let _ = input.parse::<Comma>()?;
+                    if input.is_empty()
+                        || input.peek(Token![where])
+                        || input.peek(kw::bound)
+                        || input.peek(kw::using)
+                        || input.peek(kw::ignore)
+                        || input.peek(kw::only)
+                        || input.peek(kw::with)
+                        || input.peek(kw::redact)
+                        || input.peek(kw::fmt)
+                    {
+                        break;
+                    }
+                    by.push(input.parse()?);
+                }
+            } else if lookahead.peek(Token![where]) {
+                // Note: assigning to clause disables other match branches since clause must come last!
+                clause = Some(input.parse()?);
+            } else if clause.is_none() && lookahead.peek(kw::bound) {
+                let _: kw::bound = input.parse()?;
+                if input.peek(syn::token::Paren) {
+                    let content;
+                    let _ = syn::parenthesized!(content in input);
+                    let lookahead = content.lookahead1();
+                    if !bound_fields && lookahead.peek(kw::fields) {
+                        let _: kw::fields = content.parse()?;
+                        bound_fields = true;
+                    } else if !bound_used && lookahead.peek(kw::used) {
+                        let _: kw::used = content.parse()?;
+                        bound_used = true;
+                    } else {
+                        return Err(lookahead.error());
+                    }
+                    continue;
                 }
-            }
 
-            let args = ImplArgs {
-                path_arguments: PathArguments::None,
-                ignores,
-                using,
-                clause,
-            };
-            Ok(Attr::ImplTraits(ImplTraits { targets, args }))
+                // As for `where`, this must come last
+                let eq = input.parse::<Token![=]>()?;
+                if bound_fields {
+                    return Err(syn::Error::new(
+                        eq.span,
+                        "`bound = ...` cannot be combined with `bound(fields)`",
+                    ));
+                }
+                let predicates: Toks = if input.peek(LitStr) {
+                    input.parse::<LitStr>()?.parse()?
+                } else {
+                    input.parse()?
+                };
+                clause = Some(parse2(quote! { where #predicates })?);
+                bound_override = true;
+            } else {
+                return Err(lookahead.error());
+            }
         }
+
+        let args = ImplArgs {
+            path_arguments: PathArguments::None,
+            vis: None,
+            ignores,
+            ignore_values,
+            using,
+            options,
+            by,
+            redacts,
+            fmts,
+            variant_fmts,
+            clause,
+            bound_override,
+            bound_fields,
+            bound_used,
+            root: None,
+        };
+        Ok((args, using_last, only))
     }
 }
 
@@ -297,182 +812,1446 @@ impl ImplTraits {
         item: Toks,
         find_impl: impl Fn(&Path) -> Option<&'static dyn ImplTrait>,
     ) -> Toks {
-        match parse2::<Item>(item) {
-            Ok(Item::Enum(item)) => self.expand_enum(item, find_impl),
-            Ok(Item::Struct(item)) => self.expand_struct(item, find_impl),
-            Ok(item) => {
-                emit_error!(item, "expected struct");
-                Toks::new()
-            }
-            Err(err) => err.into_compile_error(),
-        }
+        self.expand_with(item, find_impl, |_, _, toks| toks)
     }
 
-    fn expand_enum(
-        self,
-        item: ItemEnum,
+    /// Expand over the given `item`, post-processing each generated impl
+    ///
+    /// This is as [`Self::expand`], except that the output of each target is
+    /// passed to `visit` along with the target's path (as given by
+    /// [`ImplTrait::path`]) and the item's identifier. The result of `visit`
+    /// is emitted in place of the generated impl, allowing e.g. addition of
+    /// attributes such as `#[cfg(..)]`. (With flag `const`, `cfg(..)` or
+    /// `test_only`, the results are then wrapped in a `const _: () = { .. };`
+    /// block.)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use impl_tools_lib::autoimpl::{Attr, ImplDebug, ImplTrait, STD_IMPLS};
+    /// # use quote::quote;
+    /// let attr = match syn::parse_quote! { Clone, Debug } {
+    ///     Attr::ImplTraits(attr) => attr,
+    ///     Attr::ForDeref(_) | Attr::Dispatch(_) => unreachable!(),
+    /// };
+    /// let item = quote! { struct Foo(u32); };
+    /// let find_impl = |path: &syn::Path| {
+    ///     STD_IMPLS
+    ///         .iter()
+    ///         .cloned()
+    ///         .find(|impl_| impl_.path().matches_ident_or_path(path))
+    /// };
+    ///
+    /// let toks = attr.expand_with(item, find_impl, |path, _ident, toks| {
+    ///     if *path == ImplDebug.path() {
+    ///         quote! { #[cfg(debug_assertions)] #toks }
+    ///     } else {
+    ///         toks
+    ///     }
+    /// });
+    /// assert!(toks.to_string().contains("# [cfg (debug_assertions)]"));
+    /// ```
+    pub fn expand_with(
+        mut self,
+        item: Toks,
         find_impl: impl Fn(&Path) -> Option<&'static dyn ImplTrait>,
+        mut visit: impl FnMut(&SimplePath, &Ident, Toks) -> Toks,
     ) -> Toks {
-        let ImplTraits {
-            mut targets,
-            mut args,
-        } = self;
-
-        if !args.ignores.is_empty() {
-            let ignores = args.ignores.iter();
-            let list = quote! { #(#ignores)* };
-            emit_error!(list, "enum expansion does not currently support `ignore`",);
-            return Toks::new();
-        }
-        if let Some(mem) = args.using {
-            emit_error!(mem, "enum expansion does not currently support `using`",);
-            return Toks::new();
-        }
-
-        let mut impl_targets: Vec<(Span, _, _)> = Vec::with_capacity(targets.len());
-        for mut target in targets.drain(..) {
-            let target_span = target.span();
-            let path_args = target
-                .segments
-                .last_mut()
-                .map(|seg| std::mem::take(&mut seg.arguments))
-                .unwrap_or(PathArguments::None);
-            let target_impl = match find_impl(&target) {
-                Some(impl_) => impl_,
-                None => {
-                    emit_error!(target, "unsupported trait");
-                    return Toks::new();
-                }
+        // With `crate = PATH`, generated paths are resolved through PATH
+        // Within a const block, PATH is imported locally
+        let cfg = self.cfg.take();
+        let in_const = self.in_const || cfg.is_some();
+        let krate = self.args.root.take();
+        let import = krate.as_ref().filter(|_| in_const).map(|path| {
+            let ident = Ident::new("__impl_root", Span::call_site());
+            let import = quote! {
+                #[allow(unused_imports)]
+                use #path as #ident;
             };
-
-            if !(path_args.is_empty() || target_impl.support_path_arguments()) {
-                emit_error!(
-                    target_span,
-                    "target {} does not support path arguments",
-                    target_impl.path()
-                );
+            (import, Path::from(ident))
+        });
+        let root = match import {
+            Some((_, ref local)) => Some(local),
+            None => krate.as_ref(),
+        };
+        let item = match parse2::<Item>(item) {
+            Ok(item @ (Item::Enum(_) | Item::Struct(_) | Item::Union(_))) => item,
+            Ok(item) => {
+                emit_error!(item, "expected struct, enum or union");
+                return Toks::new();
             }
-
-            impl_targets.push((target.span(), target_impl, path_args));
+            Err(err) => return err.into_compile_error(),
+        };
+        let attrs = match &item {
+            Item::Enum(item) => &item.attrs,
+            Item::Struct(item) => &item.attrs,
+            Item::Union(item) => &item.attrs,
+            _ => unreachable!(),
+        };
+        self.remove_conflicts(attrs, cfg.as_ref(), &find_impl);
+        if let Item::Struct(item) = &item {
+            check_hash_eq(item, &self);
         }
 
+        // Targets with their own arguments are expanded independently
+        let groups = std::mem::take(&mut self.groups);
         let mut toks = Toks::new();
-
-        for (span, target, path_args) in impl_targets.drain(..) {
-            let path_args_span = path_args.span();
-            args.path_arguments = path_args;
-            match target.enum_impl(&item, &args) {
-                Ok(items) => toks.append_all(items),
-                Err(error) => error.emit(span, path_args_span),
+        for mut traits in std::iter::once(self).chain(groups) {
+            traits.args.root = root.cloned();
+            if !traits.targets.is_empty() {
+                toks.append_all(traits.expand_item(item.clone(), &find_impl, &mut visit));
             }
         }
+
+        if in_const && !toks.is_empty() {
+            let import = import.map(|(import, _)| import);
+            let cfg = cfg.map(|pred| quote! { #[cfg(#pred)] });
+            toks = quote! {
+                #cfg
+                const _: () = {
+                    #import
+                    #toks
+                };
+            };
+        }
         toks
     }
 
-    fn expand_struct(
+    fn expand_item(
         self,
-        item: ItemStruct,
+        item: Item,
         find_impl: impl Fn(&Path) -> Option<&'static dyn ImplTrait>,
+        visit: impl FnMut(&SimplePath, &Ident, Toks) -> Toks,
     ) -> Toks {
-        let ImplTraits {
-            mut targets,
-            mut args,
-        } = self;
-
-        let mut not_supporting_ignore = vec![];
-        let mut not_supporting_using = vec![];
-
-        let mut impl_targets: Vec<(Span, _, _)> = Vec::with_capacity(targets.len());
-        for mut target in targets.drain(..) {
-            let target_span = target.span();
-            let path_args = target
-                .segments
-                .last_mut()
-                .map(|seg| std::mem::take(&mut seg.arguments))
-                .unwrap_or(PathArguments::None);
-            let target_impl = match find_impl(&target) {
-                Some(impl_) => impl_,
-                None => {
-                    emit_error!(target, "unsupported trait");
-                    return Toks::new();
-                }
-            };
+        match item {
+            Item::Enum(item) => self.expand_enum(item, find_impl, visit),
+            Item::Struct(item) => self.expand_struct(item, find_impl, visit),
+            Item::Union(item) => self.expand_union(item, find_impl, visit),
+            _ => unreachable!(),
+        }
+    }
 
-            if !target_impl.support_ignore() {
-                let except_with = target_impl.allow_ignore_with();
-                not_supporting_ignore.push((target.clone(), except_with));
-            }
-            if !target_impl.support_using() {
-                not_supporting_using.push(target.clone());
-            }
-            if !(path_args.is_empty() || target_impl.support_path_arguments()) {
-                emit_error!(
-                    target_span,
-                    "target {} does not support path arguments",
-                    target_impl.path()
-                );
-            }
+    /// Iterate over targets, including those of groups
+    fn all_targets_mut(&mut self) -> impl Iterator<Item = &mut Vec<Target>> {
+        let groups = self.groups.iter_mut().map(|group| &mut group.targets);
+        std::iter::once(&mut self.targets).chain(groups)
+    }
 
-            impl_targets.push((target.span(), target_impl, path_args));
-        }
+    /// Remove targets which are listed twice or also implemented by a later
+    /// `#[autoimpl]` or a `#[derive]` on the item, reporting an error for each
+    ///
+    /// Preceding `#[autoimpl]` attributes have already been expanded (and are
+    /// not among `attrs`), thus each conflict is reported once. Two
+    /// `#[autoimpl]` attributes gated by different `cfg` predicates are assumed
+    /// not to coexist, thus do not conflict (`cfg` is the predicate of `self`).
+    fn remove_conflicts(
+        &mut self,
+        attrs: &[Attribute],
+        cfg: Option<&Toks>,
+        find_impl: &impl Fn(&Path) -> Option<&'static dyn ImplTrait>,
+    ) {
+        // A target is identified by its implementation and path arguments
+        let key = |path: &Path| -> Option<(String, String)> {
+            let mut path = path.clone();
+            let args = std::mem::take(&mut path.segments.last_mut()?.arguments);
+            let impl_path = find_impl(&path)?.path().to_string();
+            Some((impl_path, args.to_token_stream().to_string()))
+        };
 
-        if !args.ignores.is_empty() {
-            for (target, except_with) in not_supporting_ignore.into_iter() {
-                if let Some(path) = except_with {
-                    if impl_targets
-                        .iter()
-                        .any(|(_, target_impl, _)| path == target_impl.path())
-                    {
+        let cfg = cfg.map(|pred| pred.to_string());
+        let mut others = vec![];
+        for attr in attrs {
+            if attr.path().is_ident("derive") {
+                let parser = Punctuated::<Path, Comma>::parse_terminated;
+                if let Ok(paths) = attr.parse_args_with(parser) {
+                    others.extend(paths.into_iter().map(|path| (path, "#[derive]")));
+                }
+            } else if is_autoimpl(attr) {
+                if let Ok(Attr::ImplTraits(mut other)) = attr.parse_args::<Attr>() {
+                    let other_cfg = other.cfg.as_ref().map(|pred| pred.to_string());
+                    if cfg.is_some() && other_cfg.is_some() && cfg != other_cfg {
                         continue;
                     }
+                    let targets = other.all_targets_mut().flat_map(std::mem::take);
+                    let paths = targets.map(|(_, _, path)| path);
+                    others.extend(paths.map(|path| (path, "another #[autoimpl]")));
                 }
-                emit_error!(target, "target does not support `ignore`",);
-            }
-        }
-        if args.using.is_some() {
-            for target in not_supporting_using.into_iter() {
-                emit_error!(target, "target does not support `using`",);
             }
         }
 
-        fn check_is_field(mem: &Member, fields: &Fields) {
-            match (fields, mem) {
-                (Fields::Named(fields), Member::Named(ref ident)) => {
-                    if fields
-                        .named
-                        .iter()
-                        .any(|field| field.ident.as_ref() == Some(ident))
-                    {
-                        return;
-                    }
-                }
-                (Fields::Unnamed(fields), Member::Unnamed(index)) => {
-                    if (index.index as usize) < fields.unnamed.len() {
-                        return;
-                    }
-                }
-                _ => (),
+        let mut seen = vec![];
+        let mut retain = |(_, _, target): &Target| {
+            // Unsupported targets are reported on expansion
+            let target_key = match key(target) {
+                Some(key) => key,
+                None => return true,
+            };
+            if seen.contains(&target_key) {
+                emit_error!(target, "duplicate target");
+                return false;
             }
-            emit_error!(mem, "not a struct field");
+            let other = others
+                .iter()
+                .find(|(path, _)| key(path).as_ref() == Some(&target_key));
+            seen.push(target_key);
+            if let Some((path, by)) = other {
+                emit_error!(
+                    target, "target is also implemented by {}", by;
+                    note = path.span() => "conflicting implementation requested here";
+                );
+                return false;
+            }
+            true
+        };
+        for targets in self.all_targets_mut() {
+            targets.retain(&mut retain);
         }
+    }
 
-        let mut toks = Toks::new();
+    fn expand_union(
+        self,
+        item: ItemUnion,
+        find_impl: impl Fn(&Path) -> Option<&'static dyn ImplTrait>,
+        mut visit: impl FnMut(&SimplePath, &Ident, Toks) -> Toks,
+    ) -> Toks {
+        let ImplTraits {
+            targets,
+            mut args,
+            using_last,
+            only,
+            in_const: _,
+            cfg: _,
+            groups: _,
+        } = self;
+
+        let unsupported = if let Some(mem) = args.ignores.first() {
+            Some((mem.span(), "ignore"))
+        } else if let Some(mem) = only.first() {
+            Some((mem.span(), "only"))
+        } else if let Some(mem) = args.using.as_ref() {
+            Some((mem.span(), "using"))
+        } else if let Some(span) = using_last {
+            Some((span, "using"))
+        } else if let Some(key) = args.by.first() {
+            Some((key.span(), "by"))
+        } else if let Some(mem) = args.redacts.first() {
+            Some((mem.span(), "redact"))
+        } else if let Some((pat, _)) = args.variant_fmts.first() {
+            Some((pat.span(), "fmt"))
+        } else if args.bound_fields {
+            Some((Span::call_site(), "bound(fields)"))
+        } else if args.bound_used {
+            Some((Span::call_site(), "bound(used)"))
+        } else {
+            args.fmts.first().map(|(mem, _)| (mem.span(), "fmt"))
+        };
+        if let Some((span, name)) = unsupported {
+            emit_error!(span, "union expansion does not support `{}`", name);
+            return Toks::new();
+        }
+
+        let impl_targets = match resolve_targets(targets, &find_impl) {
+            Some(impl_targets) => impl_targets,
+            None => return Toks::new(),
+        };
+
+        check_options(&args, &impl_targets);
+
+        // Union targets do not access fields, thus none are listed
+        let fields = ItemFields {
+            ident: &item.ident,
+            generics: &item.generics,
+            fields: vec![],
+            default_using: None,
+        };
+        let expand = |target: &dyn ImplTrait, args: &ImplArgs| target.union_impl(&item, args);
+        expand_targets(
+            &mut args,
+            impl_targets,
+            fields,
+            |target| target.support_ignore(),
+            expand,
+            &mut visit,
+        )
+    }
+
+    fn expand_enum(
+        self,
+        item: ItemEnum,
+        find_impl: impl Fn(&Path) -> Option<&'static dyn ImplTrait>,
+        mut visit: impl FnMut(&SimplePath, &Ident, Toks) -> Toks,
+    ) -> Toks {
+        let ImplTraits {
+            targets,
+            mut args,
+            using_last,
+            mut only,
+            in_const: _,
+            cfg: _,
+            groups: _,
+        } = self;
+        let fields = item.variants.iter().map(|v| &v.fields);
+        respell_members(&mut args, &mut only, fields);
+
+        if let Some((_, expr)) = args.ignore_values.first() {
+            emit_error!(
+                expr,
+                "enum expansion does not support values of ignored fields"
+            );
+            return Toks::new();
+        }
+        if let Some(mem) = args.using {
+            emit_error!(mem, "enum expansion does not currently support `using`",);
+            return Toks::new();
+        }
+        if let Some(span) = using_last {
+            emit_error!(span, "enum expansion does not currently support `using`",);
+            return Toks::new();
+        }
+        if let Some(key) = args.by.first() {
+            emit_error!(key, "enum expansion does not currently support `by`",);
+            return Toks::new();
+        }
+        if let Some(mem) = args.redacts.first() {
+            emit_error!(mem, "enum expansion does not currently support `redact`",);
+            return Toks::new();
+        }
+        if let Some((mem, _)) = args.fmts.first() {
+            emit_error!(mem, "enum expansion does not currently support `fmt`",);
+            return Toks::new();
+        }
+        if !only.is_empty() {
+            let fields = item.variants.iter().map(|v| &v.fields);
+            if !resolve_only(&only, fields, &mut args) {
+                return Toks::new();
+            }
+        }
+        if !check_tuple_cfgs(item.variants.iter().map(|v| &v.fields)) {
+            return Toks::new();
+        }
+
+        let impl_targets = match resolve_targets(targets, &find_impl) {
+            Some(impl_targets) => impl_targets,
+            None => return Toks::new(),
+        };
+        for (span, target, _, _) in &impl_targets {
+            if !args.ignores.is_empty() && !target.support_enum_ignore() {
+                let name = if only.is_empty() { "ignore" } else { "only" };
+                emit_error!(span, "target does not support `{}` on enums", name);
+            }
+            if !args.variant_fmts.is_empty() && !target.support_variant_fmt() {
+                emit_error!(span, "target does not support `fmt` on enums");
+            }
+        }
+
+        // Ignored fields apply to each variant with a matching field
         for mem in &args.ignores {
+            let found = item.variants.iter().any(|v| match (&v.fields, mem) {
+                (Fields::Named(fields), Member::Named(ident)) => fields
+                    .named
+                    .iter()
+                    .any(|field| field.ident.as_ref() == Some(ident)),
+                (Fields::Unnamed(fields), Member::Unnamed(index)) => {
+                    (index.index as usize) < fields.unnamed.len()
+                }
+                _ => false,
+            });
+            if !found {
+                emit_error!(mem, "not a field of any variant");
+            }
+        }
+
+        // Each format pattern must name a variant; each variant must be covered
+        if !args.variant_fmts.is_empty() {
+            let mut covered = Vec::new();
+            for (pat, _) in &args.variant_fmts {
+                for path in variant_fmt_paths(pat) {
+                    let variant = (path.segments.len() == 2 && path.segments[0].ident == "Self")
+                        .then(|| &path.segments[1].ident)
+                        .filter(|ident| item.variants.iter().any(|v| v.ident == **ident));
+                    match variant {
+                        Some(ident) => covered.push(ident),
+                        None => emit_error!(path, "expected `Self::VARIANT`"),
+                    }
+                }
+            }
+            for v in item.variants.iter() {
+                if !covered.contains(&&v.ident) {
+                    emit_error!(v.ident, "missing `fmt` for this variant");
+                }
+            }
+        }
+
+        check_options(&args, &impl_targets);
+
+        let fields = ItemFields {
+            ident: &item.ident,
+            generics: &item.generics,
+            fields: item
+                .variants
+                .iter()
+                .flat_map(|v| field_members(&v.fields))
+                .collect(),
+            default_using: None,
+        };
+        let expand = |target: &dyn ImplTrait, args: &ImplArgs| target.enum_impl(&item, args);
+        expand_targets(
+            &mut args,
+            impl_targets,
+            fields,
+            |target| target.support_enum_ignore(),
+            expand,
+            &mut visit,
+        )
+    }
+
+    fn expand_struct(
+        self,
+        item: ItemStruct,
+        find_impl: impl Fn(&Path) -> Option<&'static dyn ImplTrait>,
+        mut visit: impl FnMut(&SimplePath, &Ident, Toks) -> Toks,
+    ) -> Toks {
+        let ImplTraits {
+            targets,
+            mut args,
+            using_last,
+            mut only,
+            in_const: _,
+            cfg: _,
+            groups: _,
+        } = self;
+        respell_members(&mut args, &mut only, std::iter::once(&item.fields));
+
+        if let Some(span) = using_last {
+            // Resolve `using self.(last)` to the last field
+            let len = item.fields.len();
+            args.using = match item.fields.iter().last() {
+                Some(field) => Some(match field.ident.clone() {
+                    Some(ident) => Member::Named(ident),
+                    None => Member::Unnamed(Index {
+                        index: (len - 1) as u32,
+                        span,
+                    }),
+                }),
+                None => {
+                    emit_error!(span, "struct has no fields");
+                    return Toks::new();
+                }
+            };
+        }
+        if !only.is_empty() && !resolve_only(&only, std::iter::once(&item.fields), &mut args) {
+            return Toks::new();
+        }
+        if !check_tuple_cfgs(std::iter::once(&item.fields)) {
+            return Toks::new();
+        }
+        let ignore_name = if only.is_empty() { "ignore" } else { "only" };
+
+        let impl_targets = match resolve_targets(targets, &find_impl) {
+            Some(impl_targets) => impl_targets,
+            None => return Toks::new(),
+        };
+
+        if !args.ignores.is_empty() {
+            for (span, target, _, _) in &impl_targets {
+                if target.support_ignore() {
+                    continue;
+                }
+                if let Some(path) = target.allow_ignore_with() {
+                    if impl_targets
+                        .iter()
+                        .any(|(_, other, _, _)| path == other.path())
+                    {
+                        continue;
+                    }
+                }
+                if target.path() == ImplCopy.path() {
+                    emit_error!(
+                        span, "target does not support `{}`", ignore_name;
+                        note = "`Copy` always copies all fields, thus `Clone` may not ignore fields; other targets may use per-target arguments, e.g. `Debug(ignore self.x)`";
+                    );
+                } else {
+                    emit_error!(span, "target does not support `{}`", ignore_name);
+                }
+            }
+        }
+        if let Some((_, expr)) = args.ignore_values.first() {
+            if !impl_targets
+                .iter()
+                .any(|(_, target, _, _)| target.path() == ImplClone.path())
+            {
+                emit_error!(expr, "values of ignored fields are only used by `Clone`");
+            }
+        }
+        if let Some((pat, _)) = args.variant_fmts.first() {
+            emit_error!(pat, "per-variant `fmt` is only supported on enums");
+            return Toks::new();
+        }
+        let unsupported = |used: bool, supported: fn(&dyn ImplTrait) -> bool, name: &str| {
+            if used {
+                for (span, target, _, _) in &impl_targets {
+                    if !supported(*target) {
+                        emit_error!(span, "target does not support `{}`", name);
+                    }
+                }
+            }
+        };
+        unsupported(args.using.is_some(), |t| t.support_using(), "using");
+        unsupported(!args.by.is_empty(), |t| t.support_by(), "by");
+        unsupported(!args.redacts.is_empty(), |t| t.support_redact(), "redact");
+        unsupported(!args.fmts.is_empty(), |t| t.support_fmt(), "fmt");
+
+        fn check_is_field(mem: &Member, fields: &Fields) -> bool {
+            match (fields, mem) {
+                (Fields::Named(fields), Member::Named(ref ident)) => {
+                    if fields
+                        .named
+                        .iter()
+                        .any(|field| field.ident.as_ref() == Some(ident))
+                    {
+                        return true;
+                    }
+                }
+                (Fields::Unnamed(fields), Member::Unnamed(index)) => {
+                    let len = fields.unnamed.len();
+                    if (index.index as usize) < len {
+                        return true;
+                    }
+                    emit_error!(
+                        mem,
+                        "field index out of bounds: struct has {} field{}",
+                        len,
+                        if len == 1 { "" } else { "s" },
+                    );
+                    return false;
+                }
+                _ => (),
+            }
+            emit_error!(mem, "not a struct field");
+            false
+        }
+
+        check_options(&args, &impl_targets);
+
+        let fmt_members = args.fmts.iter().map(|(mem, _)| mem);
+        for mem in args
+            .ignores
+            .iter()
+            .chain(args.redacts.iter())
+            .chain(fmt_members)
+        {
             check_is_field(mem, &item.fields);
         }
         if let Some(mem) = args.using_member() {
-            check_is_field(mem, &item.fields);
+            if !check_is_field(mem, &item.fields) {
+                return Toks::new();
+            }
         }
 
-        for (span, target, path_args) in impl_targets.drain(..) {
-            let path_args_span = path_args.span();
-            args.path_arguments = path_args;
-            match target.struct_impl(&item, &args) {
-                Ok(items) => toks.append_all(items),
-                Err(error) => error.emit(span, path_args_span),
+        // Targets requiring `using` may use the field marked `#[autoimpl(using)]`
+        // or else the field of a transparent struct
+        let default_using = using_field_attr(&item).or_else(|| transparent_field(&item));
+
+        #[cfg(feature = "warnings")]
+        warnings::check_struct(&impl_targets, &args, default_using.as_ref());
+
+        let fields = ItemFields {
+            ident: &item.ident,
+            generics: &item.generics,
+            fields: field_members(&item.fields).collect(),
+            default_using,
+        };
+        let expand = |target: &dyn ImplTrait, args: &ImplArgs| target.struct_impl(&item, args);
+        expand_targets(
+            &mut args,
+            impl_targets,
+            fields,
+            |target| target.support_ignore(),
+            expand,
+            &mut visit,
+        )
+    }
+}
+
+/// A target resolved to its implementation
+///
+/// This is the target's span, implementation, path arguments and visibility.
+type ImplTarget = (
+    Span,
+    &'static dyn ImplTrait,
+    PathArguments,
+    Option<Visibility>,
+);
+
+/// Resolve `targets` to implementations, checking properties common to all
+/// item kinds (path arguments, `unsafe` and visibility)
+///
+/// Returns `None` (after reporting an error) on an unsupported trait.
+fn resolve_targets(
+    targets: Vec<Target>,
+    find_impl: &impl Fn(&Path) -> Option<&'static dyn ImplTrait>,
+) -> Option<Vec<ImplTarget>> {
+    let mut impl_targets = Vec::with_capacity(targets.len());
+    for (vis, unsafety, mut target) in targets {
+        let target_span = target.span();
+        let path_args = target
+            .segments
+            .last_mut()
+            .map(|seg| std::mem::take(&mut seg.arguments))
+            .unwrap_or(PathArguments::None);
+        let target_impl = match find_impl(&target) {
+            Some(impl_) => impl_,
+            None => {
+                emit_error!(target, "unsupported trait");
+                return None;
             }
+        };
+
+        if !(path_args.is_empty() || target_impl.support_path_arguments()) {
+            emit_error!(
+                target_span,
+                "target {} does not support path arguments",
+                target_impl.path()
+            );
+        }
+        check_unsafety(unsafety.as_ref(), &target, target_impl);
+        if let Some(ref vis) = vis {
+            if !target_impl.support_visibility() {
+                emit_error!(
+                    vis,
+                    "target {} does not support a visibility",
+                    target_impl.path()
+                );
+            }
+        }
+
+        impl_targets.push((target.span(), target_impl, path_args, vis));
+    }
+    Some(impl_targets)
+}
+
+/// Properties of the item relevant to [`expand_targets`]
+struct ItemFields<'a> {
+    ident: &'a Ident,
+    generics: &'a syn::Generics,
+    /// Fields which targets may access (of all variants of an enum)
+    fields: Vec<(Member, &'a Field)>,
+    /// Field to use where a target requires `using` but none is given
+    default_using: Option<Member>,
+}
+
+/// Expand each of `impl_targets` via `expand`, passing the results to `visit`
+///
+/// This handles per-target `#[cfg]` checks and bounds (`bound(used)` and
+/// `bound(fields)`). Where `support_ignore` returns false for a target, that
+/// target uses all fields.
+fn expand_targets(
+    args: &mut ImplArgs,
+    impl_targets: Vec<ImplTarget>,
+    item: ItemFields,
+    support_ignore: impl Fn(&dyn ImplTrait) -> bool,
+    mut expand: impl FnMut(&dyn ImplTrait, &ImplArgs) -> Result<Toks>,
+    visit: &mut impl FnMut(&SimplePath, &Ident, Toks) -> Toks,
+) -> Toks {
+    let all_types: Vec<&Type> = item.fields.iter().map(|(_, field)| &field.ty).collect();
+    let used_types: Vec<&Type> = item
+        .fields
+        .iter()
+        .filter(|(member, _)| !args.ignore(member))
+        .map(|(_, field)| &field.ty)
+        .collect();
+
+    let mut toks = Toks::new();
+    for (span, target, path_args, vis) in impl_targets {
+        let path_args_span = path_args.span();
+        args.path_arguments = path_args;
+        args.vis = vis;
+        if args.bound_fields && !target.support_field_bounds() {
+            emit_error!(span, "target does not support `bound(fields)`");
+            continue;
+        }
+        let using = args.using.as_ref().or(item.default_using.as_ref());
+        if !check_target_cfgs(target, span, item.fields.iter().cloned(), using) {
+            continue;
+        }
+        let ignore = support_ignore(target);
+        // With `bound(used)`, `T: trait` bounds are not required for
+        // parameters only used by ignored fields, `PhantomData` (or shared
+        // pointers, for some targets)
+        let trimmed = if !args.bound_used || target.require_unsafe() {
+            None
+        } else {
+            let types = if ignore { &used_types } else { &all_types };
+            args.trimmed_clause(item.generics, types, target.exempt_shared_pointers())
+        };
+        let mut saved_clause = trimmed.map(|clause| args.clause.replace(clause));
+        if args.bound_fields {
+            // With `using`, only the used field is relevant
+            let types = item
+                .fields
+                .iter()
+                .filter(|(member, _)| match args.using.as_ref() {
+                    Some(using) => member == using,
+                    None => !(ignore && args.ignore(member)),
+                })
+                .map(|(_, field)| &field.ty);
+            let clause =
+                field_bounds_clause(args.clause.as_ref(), item.ident, item.generics, types);
+            let clause = args.clause.replace(clause);
+            saved_clause.get_or_insert(clause);
+        }
+        let mut result = expand(target, args);
+        if let (Err(Error::RequireUsing), Some(member)) = (&result, &item.default_using) {
+            args.using = Some(member.clone());
+            result = expand(target, args);
+            args.using = None;
+        }
+        match result {
+            Ok(items) => toks.append_all(visit(&target.path(), item.ident, items)),
+            Err(error) => error.emit(span, path_args_span),
+        }
+        if let Some(clause) = saved_clause {
+            args.clause = clause;
+        }
+    }
+    toks
+}
+
+/// Get the member of `field`, the field at `index` of its item or variant
+pub(crate) fn field_member(index: usize, field: &Field) -> Member {
+    match field.ident.clone() {
+        Some(ident) => Member::Named(ident),
+        None => Member::Unnamed(Index::from(index)),
+    }
+}
+
+/// Iterate over `fields` with their members
+pub(crate) fn field_members(fields: &Fields) -> impl Iterator<Item = (Member, &Field)> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| (field_member(i, field), field))
+}
+
+/// Extend `clause` with a bound `Ty: trait` for each of `types` using a type
+/// parameter of `generics`
+///
+/// Duplicate types are bounded once. Types referring to the item itself
+/// (`ident` or `Self`, e.g. `Option<Box<Self>>`) are skipped since such a
+/// bound would be recursive and could never be satisfied.
+fn field_bounds_clause<'a>(
+    clause: Option<&WhereClause>,
+    ident: &Ident,
+    generics: &syn::Generics,
+    types: impl Iterator<Item = &'a Type>,
+) -> WhereClause {
+    let mut clause = clause
+        .cloned()
+        .unwrap_or_else(|| syn::parse_quote! { where });
+    let params: Vec<&Ident> = generics.type_params().map(|param| &param.ident).collect();
+    let mut bounded: Vec<String> = vec![];
+    for ty in types.filter(|ty| !is_phantom_data(ty)) {
+        let toks = ty.to_token_stream();
+        if !params
+            .iter()
+            .any(|param| tokens_use_ident(toks.clone(), param))
+            || tokens_use_ident(toks.clone(), ident)
+            || tokens_use_ident(toks.clone(), &Ident::new("Self", Span::call_site()))
+        {
+            continue;
+        }
+        let key = toks.to_string();
+        if bounded.contains(&key) {
+            continue;
+        }
+        bounded.push(key);
+        if !clause.predicates.empty_or_trailing() {
+            clause.predicates.push_punct(Default::default());
+        }
+        clause
+            .predicates
+            .push_value(syn::parse_quote! { #ty: trait });
+    }
+    clause
+}
+
+/// Resolve an `only` clause to the complementary list of ignored fields
+///
+/// Each listed member must be a field of some `fields`. Returns false if an
+/// error was emitted.
+fn resolve_only<'a>(
+    only: &[Member],
+    fields: impl Iterator<Item = &'a Fields>,
+    args: &mut ImplArgs,
+) -> bool {
+    if let Some(mem) = args.ignores.first() {
+        emit_error!(mem, "`ignore` cannot be combined with `only`");
+        return false;
+    }
+
+    let members = members(fields);
+    let mut ok = true;
+    for mem in only {
+        if !members.contains(mem) {
+            emit_error!(mem, "not a field");
+            ok = false;
+        }
+    }
+    args.ignores = members
+        .into_iter()
+        .filter(|mem| !only.contains(mem))
+        .collect();
+    ok
+}
+
+/// Respell named members of `args` and `only` as the matching field identifier
+///
+/// A field is matched regardless of whether either identifier is raw (e.g.
+/// `self.r#foo` and `self.foo` both name field `foo`), thus generated code
+/// refers to the field as declared.
+fn respell_members<'a>(
+    args: &mut ImplArgs,
+    only: &mut [Member],
+    fields: impl Iterator<Item = &'a Fields>,
+) {
+    let idents: Vec<&Ident> = fields
+        .flat_map(|fields| fields.iter())
+        .filter_map(|field| field.ident.as_ref())
+        .collect();
+    let members = args
+        .ignores
+        .iter_mut()
+        .chain(args.ignore_values.iter_mut().map(|(mem, _)| mem))
+        .chain(args.redacts.iter_mut())
+        .chain(args.fmts.iter_mut().map(|(mem, _)| mem))
+        .chain(args.using.iter_mut())
+        .chain(only.iter_mut());
+    for member in members {
+        if let Member::Named(ident) = member {
+            let name = ident.unraw();
+            if let Some(field) = idents.iter().find(|field| field.unraw() == name) {
+                let span = ident.span();
+                *ident = (*field).clone();
+                ident.set_span(span);
+            }
+        }
+    }
+}
+
+/// List the members of `fields`, without duplicates
+fn members<'a>(fields: impl Iterator<Item = &'a Fields>) -> Vec<Member> {
+    let mut members: Vec<Member> = vec![];
+    for fields in fields {
+        for (member, _) in field_members(fields) {
+            if !members.contains(&member) {
+                members.push(member);
+            }
+        }
+    }
+    members
+}
+
+/// List the members of `fields` not in `only`
+fn complement<'a>(only: &[Member], fields: impl Iterator<Item = &'a Fields>) -> Vec<Member> {
+    members(fields)
+        .into_iter()
+        .filter(|mem| !only.contains(mem))
+        .collect()
+}
+
+/// Get the variant paths matched by a per-variant format pattern
+fn variant_fmt_paths(pat: &Pat) -> Vec<&Path> {
+    match pat {
+        Pat::Path(pat) => vec![&pat.path],
+        Pat::Struct(pat) => vec![&pat.path],
+        Pat::TupleStruct(pat) => vec![&pat.path],
+        Pat::Or(pat) => pat.cases.iter().flat_map(variant_fmt_paths).collect(),
+        _ => vec![],
+    }
+}
+
+/// Check that `unsafe` is given if and only if the target requires it
+fn check_unsafety(unsafety: Option<&Token![unsafe]>, target: &Path, target_impl: &dyn ImplTrait) {
+    match (unsafety, target_impl.require_unsafe()) {
+        (None, true) => emit_error!(
+            target, "target {} is an unsafe trait", target_impl.path();
+            help = "write `unsafe {}` to assert that the implementation is sound", target.to_token_stream();
+        ),
+        (Some(unsafety), false) => {
+            emit_error!(
+                unsafety,
+                "target {} is not an unsafe trait",
+                target_impl.path()
+            )
+        }
+        _ => (),
+    }
+}
+
+/// Checks for soft issues, reported as warnings
+///
+/// Warnings are only emitted by nightly compilers; on stable these are silent.
+#[cfg(feature = "warnings")]
+mod warnings {
+    use super::*;
+    use proc_macro_error::emit_warning;
+
+    pub(super) fn check_struct(
+        targets: &[ImplTarget],
+        args: &ImplArgs,
+        default_using: Option<&Member>,
+    ) {
+        for (i, mem) in args.ignores.iter().enumerate() {
+            if args.ignores[..i].contains(mem) {
+                emit_warning!(mem, "unused `ignore` entry: field is already ignored");
+            }
+        }
+
+        if let Some(using) = args.using.as_ref() {
+            // Redundant if all targets would otherwise use the default field
+            let redundant = Some(using) == default_using
+                && targets
+                    .iter()
+                    .all(|(_, target, _, _)| target.require_using());
+            if redundant {
+                emit_warning!(
+                    using,
+                    "redundant `using`: this is the default field (by `#[autoimpl(using)]` or `#[repr(transparent)]`)"
+                );
+            }
+        }
+    }
+}
+
+/// Report an error where `Hash` and `PartialEq` are implemented with
+/// separate arguments in an inconsistent manner
+///
+/// Each target set of `traits` (targets sharing arguments, including those
+/// with their own arguments) is compared with each following set of `traits`
+/// and of following `#[autoimpl]` attributes. Preceding attributes have
+/// already been expanded (and are not among `item.attrs`), thus each pair of
+/// sets is checked once.
+fn check_hash_eq(item: &ItemStruct, traits: &ImplTraits) {
+    // Each set is (tokens to report on, targets, args, ignored fields)
+    type Set<'a> = (Toks, &'a [Target], &'a ImplArgs, Vec<Member>);
+    fn set<'a>(toks: Toks, traits: &'a ImplTraits, fields: &Fields) -> Set<'a> {
+        let ignores = if traits.only.is_empty() {
+            traits.args.ignores.clone()
+        } else {
+            complement(&traits.only, std::iter::once(fields))
+        };
+        (toks, &traits.targets, &traits.args, ignores)
+    }
+    fn group_sets<'a>(
+        toks: Toks,
+        traits: &'a ImplTraits,
+        fields: &'a Fields,
+    ) -> impl Iterator<Item = Set<'a>> {
+        let groups = traits.groups.iter().map(move |group| {
+            let toks = group.targets.iter().map(|(_, _, path)| path);
+            set(quote! { #(#toks)* }, group, fields)
+        });
+        std::iter::once(set(toks, traits, fields)).chain(groups)
+    }
+
+    let others: Vec<(&Attribute, ImplTraits)> = item
+        .attrs
+        .iter()
+        .filter(|attr| is_autoimpl(attr))
+        .filter_map(|attr| match attr.parse_args::<Attr>() {
+            Ok(Attr::ImplTraits(other)) => Some((attr, other)),
+            _ => None,
+        })
+        .collect();
+    let paths = traits.targets.iter().map(|(_, _, path)| path);
+    let sets: Vec<Set> = group_sets(quote! { #(#paths)* }, traits, &item.fields)
+        .chain(
+            others
+                .iter()
+                .flat_map(|(attr, other)| group_sets(attr.to_token_stream(), other, &item.fields)),
+        )
+        .collect();
+    let own_sets = 1 + traits.groups.len();
+
+    // `PartialEq<T>` compares with another type, thus is not relevant here
+    let has = |targets: &[Target], path: SimplePath| {
+        targets
+            .iter()
+            .any(|(_, _, target)| path.matches_ident_or_path(target))
+    };
+    for (i, (_, targets, args, ignores)) in sets.iter().take(own_sets).enumerate() {
+        let (has_hash, has_eq) = (
+            has(targets, ImplHash.path()),
+            has(targets, ImplPartialEq.path()),
+        );
+        for (toks, other_targets, other_args, other_ignores) in &sets[i + 1..] {
+            let (hash, eq) = if has_hash && !has_eq && has(other_targets, ImplPartialEq.path()) {
+                ((args, ignores), (other_args, other_ignores))
+            } else if has_eq && !has_hash && has(other_targets, ImplHash.path()) {
+                ((other_args, other_ignores), (args, ignores))
+            } else {
+                continue;
+            };
+            let ((hash_args, hash_ignores), (eq_args, eq_ignores)) = (hash, eq);
+
+            // Hash may use fewer fields than PartialEq, but not more. Where this
+            // cannot be determined (keys used by Hash only) we do not report.
+            let consistent = if !eq_args.by.is_empty() {
+                !hash_args.by.is_empty() && hash_args.by.iter().all(|key| eq_args.by.contains(key))
+            } else if !hash_args.by.is_empty() {
+                true
+            } else if let Some(member) = eq_args.using.as_ref() {
+                hash_args.using.as_ref() == Some(member)
+            } else if let Some(member) = hash_args.using.as_ref() {
+                !eq_ignores.contains(member)
+            } else {
+                eq_ignores.iter().all(|mem| hash_ignores.contains(mem))
+            };
+            if !consistent {
+                emit_error!(
+                    toks,
+                    "`Hash` uses fields or keys not compared by `PartialEq`";
+                    note = "values comparing equal must have equal hashes";
+                );
+            }
+        }
+    }
+}
+
+fn check_options(args: &ImplArgs, targets: &[ImplTarget]) {
+    for option in &args.options {
+        let name = option.to_string();
+        if !targets
+            .iter()
+            .any(|(_, target, _, _)| target.support_option(&name))
+        {
+            emit_error!(option, "no target supports option `{}`", name);
         }
-        toks
+    }
+}
+
+/// Get the type `T` from path arguments `<T>`, if any
+fn type_argument(args: &ImplArgs) -> Result<Option<Type>> {
+    match args.path_arguments {
+        PathArguments::None => Ok(None),
+        PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments { ref args, .. }) => {
+            let mut iter = args.iter();
+            match (iter.next(), iter.next()) {
+                (Some(syn::GenericArgument::Type(ty)), None) => Ok(Some(ty.clone())),
+                _ => Err(Error::PathArguments("expected `<T>`")),
+            }
+        }
+        PathArguments::Parenthesized(_) => Err(Error::PathArguments("unexpected")),
+    }
+}
+
+/// Find the field with attribute `#[autoimpl(using)]`, if any
+///
+/// An error is emitted if multiple fields have this attribute.
+fn using_field_attr(item: &ItemStruct) -> Option<Member> {
+    let mut found = None;
+    for (member, field) in field_members(&item.fields) {
+        if !FieldAttrArgs::parse(&field.attrs)
+            .map(|args| args.using)
+            .unwrap_or(false)
+        {
+            continue;
+        }
+        if found.is_some() {
+            emit_error!(field, "multiple fields have attribute `#[autoimpl(using)]`");
+        } else {
+            found = Some(member);
+        }
+    }
+    found
+}
+
+/// True if `ty` is (assumed to be) `PhantomData<..>`
+///
+/// Matches `PhantomData`, `core::marker::PhantomData` and
+/// `std::marker::PhantomData` (with or without a leading `::`).
+fn is_phantom_data(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => {
+            let segments = &path.path.segments;
+            match segments.len() {
+                1 => path.path.leading_colon.is_none() && segments[0].ident == "PhantomData",
+                3 => {
+                    (segments[0].ident == "core" || segments[0].ident == "std")
+                        && segments[1].ident == "marker"
+                        && segments[2].ident == "PhantomData"
+                }
+                _ => false,
+            }
+        }
+        Type::Paren(paren) => is_phantom_data(&paren.elem),
+        Type::Group(group) => is_phantom_data(&group.elem),
+        _ => false,
+    }
+}
+
+/// Find the single non-zero-sized field of a `#[repr(transparent)]` struct
+///
+/// Sizes are not known to macros; fields of type `PhantomData<..>`, `()` or
+/// `[T; 0]` are assumed to be zero-sized.
+fn transparent_field(item: &ItemStruct) -> Option<Member> {
+    let mut transparent = false;
+    for attr in &item.attrs {
+        if attr.path().is_ident("repr") {
+            let _ = attr.parse_nested_meta(|meta| {
+                transparent |= meta.path.is_ident("transparent");
+                if meta.input.peek(syn::token::Paren) {
+                    let _content;
+                    syn::parenthesized!(_content in meta.input);
+                }
+                Ok(())
+            });
+        }
+    }
+    if !transparent {
+        return None;
+    }
+
+    fn is_zero_sized(ty: &Type) -> bool {
+        match ty {
+            Type::Path(_) => is_phantom_data(ty),
+            Type::Tuple(tuple) => tuple.elems.is_empty(),
+            Type::Array(array) => matches!(
+                &array.len,
+                syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(lit), .. }) if lit.base10_digits() == "0"
+            ),
+            _ => false,
+        }
+    }
+
+    let mut found = None;
+    for (member, field) in field_members(&item.fields) {
+        if item.fields.len() > 1 && is_zero_sized(&field.ty) {
+            continue;
+        }
+        if found.is_some() {
+            return None;
+        }
+        found = Some(member);
+    }
+    found
+}
+
+/// True if `ident` is used by `ty`
+///
+/// If `shared`, uses within `Rc<..>`, `Arc<..>` and `Weak<..>` are not
+/// counted (see [`is_shared_pointer`]).
+fn type_uses_ident(ty: &Type, ident: &Ident, shared: bool) -> bool {
+    struct Finder<'a> {
+        ident: &'a Ident,
+        shared: bool,
+        found: bool,
+    }
+    impl<'a, 'ast> Visit<'ast> for Finder<'a> {
+        fn visit_ident(&mut self, ident: &'ast Ident) {
+            self.found |= ident == self.ident;
+        }
+        fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+            self.found |= tokens_use_ident(mac.tokens.clone(), self.ident);
+        }
+        fn visit_type_path(&mut self, path: &'ast syn::TypePath) {
+            if !(self.shared && is_shared_pointer(path)) {
+                visit::visit_type_path(self, path);
+            }
+        }
+    }
+
+    let mut finder = Finder {
+        ident,
+        shared,
+        found: false,
+    };
+    finder.visit_type(ty);
+    finder.found
+}
+
+/// True if `path` is (assumed to be) `Rc<..>`, `Arc<..>` or `Weak<..>`
+///
+/// Matches these names alone or as `std::rc::Rc`, `std::rc::Weak`,
+/// `std::sync::Arc`, `std::sync::Weak` or the same under `alloc` (with or
+/// without a leading `::`).
+fn is_shared_pointer(path: &syn::TypePath) -> bool {
+    if path.qself.is_some() {
+        return false;
+    }
+    let segments = &path.path.segments;
+    match segments.len() {
+        1 => {
+            let ident = &segments[0].ident;
+            path.path.leading_colon.is_none()
+                && (ident == "Rc" || ident == "Arc" || ident == "Weak")
+        }
+        3 => {
+            let (krate, module, ident) =
+                (&segments[0].ident, &segments[1].ident, &segments[2].ident);
+            (krate == "std" || krate == "alloc")
+                && ((module == "rc" && (ident == "Rc" || ident == "Weak"))
+                    || (module == "sync" && (ident == "Arc" || ident == "Weak")))
+        }
+        _ => false,
+    }
+}
+
+/// True if `ident` appears anywhere within `tokens`
+fn tokens_use_ident(tokens: Toks, ident: &Ident) -> bool {
+    tokens.into_iter().any(|tt| match tt {
+        proc_macro2::TokenTree::Ident(id) => id == *ident,
+        proc_macro2::TokenTree::Group(group) => tokens_use_ident(group.stream(), ident),
+        _ => false,
+    })
+}
+
+/// Arguments of field attributes `#[autoimpl(..)]`
+#[derive(Default)]
+pub(crate) struct FieldAttrArgs {
+    pub(crate) rename: Option<LitStr>,
+    pub(crate) hidden: bool,
+    using: bool,
+    clone: Option<Expr>,
+    pub(crate) debug: Option<Expr>,
+    default: Option<Expr>,
+}
+
+impl FieldAttrArgs {
+    /// Parse from field attributes
+    ///
+    /// Parse errors are reported by [`remove_field_attrs`].
+    pub(crate) fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut args = FieldAttrArgs::default();
+        for attr in attrs {
+            if is_autoimpl(attr) {
+                attr.parse_args_with(|input: ParseStream| loop {
+                    let lookahead = input.lookahead1();
+                    if lookahead.peek(kw::rename) {
+                        let _: kw::rename = input.parse()?;
+                        let _: Token![=] = input.parse()?;
+                        args.rename = Some(input.parse()?);
+                    } else if lookahead.peek(kw::hidden) {
+                        let _: kw::hidden = input.parse()?;
+                        args.hidden = true;
+                    } else if lookahead.peek(kw::using) {
+                        let _: kw::using = input.parse()?;
+                        args.using = true;
+                    } else if lookahead.peek(kw::clone) {
+                        let _: kw::clone = input.parse()?;
+                        let _: Token![=] = input.parse()?;
+                        args.clone = Some(input.parse()?);
+                    } else if lookahead.peek(kw::debug) {
+                        let _: kw::debug = input.parse()?;
+                        let _: Token![=] = input.parse()?;
+                        args.debug = Some(input.parse()?);
+                    } else if lookahead.peek(kw::default) {
+                        let _: kw::default = input.parse()?;
+                        let _: Token![=] = input.parse()?;
+                        args.default = Some(input.parse()?);
+                    } else {
+                        return Err(lookahead.error());
+                    }
+
+                    if input.is_empty() {
+                        return Ok(());
+                    }
+                    let _: Token![,] = input.parse()?;
+                })?;
+            }
+        }
+        Ok(args)
+    }
+}
+
+/// Get the name of a field as used by generated code
+///
+/// This is the field's identifier, or the name given by a field attribute
+/// `#[autoimpl(rename = "name")]` where present. Unnamed fields without such
+/// an attribute have no name.
+///
+/// This is used where a field's name appears in the output of generated
+/// code, i.e. by `Debug` and (with feature `serde`) `Serialize` and
+/// `Deserialize`; it is not used for identifiers within generated code.
+pub fn field_name(field: &Field) -> Option<String> {
+    match FieldAttrArgs::parse(&field.attrs) {
+        Ok(FieldAttrArgs {
+            rename: Some(lit), ..
+        }) => Some(lit.value()),
+        _ => field.ident.as_ref().map(|ident| ident.unraw().to_string()),
+    }
+}
+
+/// True if the field has attribute `#[autoimpl(hidden)]`
+///
+/// Hidden fields are internal: they are omitted from `Debug` output and
+/// documentation (the attribute is replaced with `#[doc(hidden)]`).
+pub fn field_is_hidden(field: &Field) -> bool {
+    FieldAttrArgs::parse(&field.attrs)
+        .map(|args| args.hidden)
+        .unwrap_or(false)
+}
+
+/// Kind of a field override (see [`field_override`])
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldOverride {
+    /// `#[autoimpl(clone = EXPR)]`: the expression replaces `self.FIELD.clone()`
+    Clone,
+    /// `#[autoimpl(debug = EXPR)]`: the expression is printed instead of the
+    /// field's value
+    Debug,
+    /// `#[autoimpl(default = EXPR)]`: the expression replaces
+    /// `Default::default()`
+    Default,
+}
+
+/// Get the override expression of the given `kind` from a field attribute
+/// `#[autoimpl(NAME = EXPR)]`
+pub fn field_override(field: &Field, kind: FieldOverride) -> Option<Expr> {
+    let args = FieldAttrArgs::parse(&field.attrs).ok()?;
+    match kind {
+        FieldOverride::Clone => args.clone,
+        FieldOverride::Debug => args.debug,
+        FieldOverride::Default => args.default,
+    }
+}
+
+/// Get the `#[cfg(..)]` attributes of a field
+///
+/// Generated code referring to the field (initializers, statements and
+/// pattern bindings) should be subject to the same attributes, thus the field
+/// may be configured out.
+pub fn field_cfgs(field: &Field) -> Vec<&Attribute> {
+    crate::fields::cfg_attrs(&field.attrs)
+}
+
+/// Report an error on tuple fields with a `#[cfg(..)]` attribute
+///
+/// See [`crate::fields::check_cfgs`]. Returns false on error.
+fn check_tuple_cfgs<'a>(fields: impl IntoIterator<Item = &'a Fields>) -> bool {
+    let mut ok = true;
+    for (member, field) in fields.into_iter().flat_map(field_members) {
+        if let Err(err) = crate::fields::check_cfgs(&member, &field_cfgs(field)) {
+            emit_error!(err.span(), "{}", err);
+            ok = false;
+        }
+    }
+    ok
+}
+
+/// Report an error if `target` refers to a field with `#[cfg(..)]` but does
+/// not support this
+///
+/// Targets implemented over the `using` field only refer to that field.
+/// Returns false on error.
+fn check_target_cfgs<'a>(
+    target: &dyn ImplTrait,
+    span: Span,
+    fields: impl Iterator<Item = (Member, &'a Field)>,
+    using: Option<&Member>,
+) -> bool {
+    if target.support_field_cfgs() {
+        return true;
+    }
+    let using = using.filter(|_| target.support_using());
+    for (member, field) in fields {
+        if using.map(|using| *using != member).unwrap_or(false) {
+            continue;
+        }
+        if let Some(attr) = field_cfgs(field).first() {
+            emit_error!(
+                span, "target does not support `#[cfg]` on fields";
+                note = attr.span() => "this field is configurable";
+            );
+            return false;
+        }
+    }
+    true
+}
+
+/// True if `attr` is an `#[autoimpl]` attribute
+///
+/// Only the last path segment is matched, thus `#[impl_tools::autoimpl]`
+/// is also recognised. This rule is used for both item and field attributes.
+pub(crate) fn is_autoimpl(attr: &Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .map(|seg| seg.ident == "autoimpl")
+        .unwrap_or(false)
+}
+
+/// Remove `#[autoimpl(..)]` attributes from a field's `attrs`
+///
+/// Malformed attributes are reported. A `hidden` field is marked
+/// `#[doc(hidden)]`.
+pub(crate) fn remove_autoimpl_attrs(attrs: &mut Vec<Attribute>) {
+    let hidden = match FieldAttrArgs::parse(attrs) {
+        Ok(args) => args.hidden,
+        Err(err) => {
+            emit_error!(err.span(), "{}", err);
+            false
+        }
+    };
+    attrs.retain(|attr| !is_autoimpl(attr));
+    if hidden {
+        attrs.push(syn::parse_quote! { #[doc(hidden)] });
+    }
+}
+
+/// Remove `#[autoimpl(..)]` field attributes from `item`
+///
+/// Field attributes (see [`field_name`], [`field_is_hidden`], [`field_override`]) are only
+/// removed when `item` has no remaining `#[autoimpl]` attribute, since further
+/// expansions may use them. Malformed field attributes are reported at this
+/// time. At the same time, `#[default]` variant attributes (see [`ImplDefault`])
+/// are removed from enums unless `Default` is derived. Items other than
+/// structs and enums are returned unchanged.
+pub fn remove_field_attrs(item: Toks) -> Toks {
+    fn derives_default(attr: &Attribute) -> bool {
+        let mut found = false;
+        if attr.path().is_ident("derive") {
+            let _ = attr.parse_nested_meta(|meta| {
+                found |= meta.path.segments.last().map(|seg| seg.ident == "Default") == Some(true);
+                Ok(())
+            });
+        }
+        found
+    }
+    fn clean(fields: &mut Fields) {
+        for field in fields.iter_mut() {
+            remove_autoimpl_attrs(&mut field.attrs);
+        }
+    }
+
+    // Avoid parsing the item when there is nothing to remove
+    let names = ["autoimpl", "default"];
+    if !names
+        .iter()
+        .any(|name| tokens_use_ident(item.clone(), &Ident::new(name, Span::call_site())))
+    {
+        return item;
+    }
+
+    match parse2::<Item>(item.clone()) {
+        Ok(Item::Struct(mut item)) if !item.attrs.iter().any(is_autoimpl) => {
+            clean(&mut item.fields);
+            item.into_token_stream()
+        }
+        Ok(Item::Enum(mut item)) if !item.attrs.iter().any(is_autoimpl) => {
+            // `#[default]` is used by `#[autoimpl(Default)]` unless derived
+            let keep_default = item.attrs.iter().any(derives_default);
+            for variant in item.variants.iter_mut() {
+                clean(&mut variant.fields);
+                if !keep_default {
+                    variant
+                        .attrs
+                        .retain(|attr| !attr.path().is_ident("default"));
+                }
+            }
+            item.into_token_stream()
+        }
+        _ => item,
     }
 }
 
@@ -483,18 +2262,68 @@ pub struct ImplArgs {
     /// Example: if the target is `Deref<Target = T>`, this is `<Target = T>`.
     /// This is always empty unless [`ImplTrait::support_path_arguments`] returns true.
     pub path_arguments: PathArguments,
+    /// Visibility given before the target, if any
+    ///
+    /// Example: if the target is `pub(crate) Inner`, this is `pub(crate)`.
+    /// This is always `None` unless [`ImplTrait::support_visibility`] returns true.
+    pub vis: Option<Visibility>,
     /// Fields ignored in attribute
     pub ignores: Vec<Member>,
+    /// Values given for ignored fields (`ignore self.FIELD = EXPR`)
+    pub ignore_values: Vec<(Member, Expr)>,
     /// Field specified to 'use' in attribute
     pub using: Option<Member>,
+    /// Options enabled in attribute (`with NAME, ...`)
+    pub options: Vec<Ident>,
+    /// Keys to compare by (`by KEY, ...`)
+    ///
+    /// Each key is an expression over `self`.
+    pub by: Vec<Expr>,
+    /// Fields redacted in attribute (`redact self.FIELD, ...`)
+    pub redacts: Vec<Member>,
+    /// Field format overrides (`fmt self.FIELD as EXPR, ...`)
+    pub fmts: Vec<(Member, Expr)>,
+    /// Per-variant format strings (`fmt Self::VARIANT(..) => "FORMAT", ...`)
+    pub variant_fmts: Vec<(Pat, LitStr)>,
     /// Where clause added to attribute
     pub clause: Option<WhereClause>,
+    /// True if `clause` was given by `bound = ...`
+    ///
+    /// In this case `clause` replaces any inferred bounds.
+    pub bound_override: bool,
+    /// True if bounds are inferred from field types (`bound(fields)`)
+    ///
+    /// Each target is then expanded with `clause` extended by a predicate
+    /// `FieldTy: trait` for each relevant field type using a type parameter.
+    pub bound_fields: bool,
+    /// True if `T: trait` bounds are dropped for type parameters used only by
+    /// ignored or `PhantomData` fields (`bound(used)`)
+    pub bound_used: bool,
+    /// Root of generated paths (`crate = PATH`)
+    ///
+    /// Implementations should emit paths like `#root::core::clone::Clone`
+    /// (with `root` bound to this field), thus paths are absolute when this
+    /// is `None`. Paths written by the user are not affected.
+    pub root: Option<Path>,
 }
 
 impl ImplArgs {
     /// If true, this field is ignored
     pub fn ignore(&self, member: &Member) -> bool {
-        self.ignores.iter().any(|ig| *ig == *member)
+        self.ignores.contains(member)
+    }
+
+    /// If true, this field is redacted
+    pub fn redact(&self, member: &Member) -> bool {
+        self.redacts.contains(member)
+    }
+
+    /// Get the format override for a field, if any
+    pub fn fmt(&self, member: &Member) -> Option<&Expr> {
+        self.fmts
+            .iter()
+            .find(|(m, _)| m == member)
+            .map(|(_, expr)| expr)
     }
 
     /// If true, this named field is ignored
@@ -513,6 +2342,19 @@ impl ImplArgs {
         })
     }
 
+    /// Get the value given for an ignored field, if any
+    pub fn ignore_value(&self, member: &Member) -> Option<&Expr> {
+        self.ignore_values
+            .iter()
+            .find(|(m, _)| m == member)
+            .map(|(_, expr)| expr)
+    }
+
+    /// If true, the named option is enabled
+    pub fn option(&self, name: &str) -> bool {
+        self.options.iter().any(|option| option == name)
+    }
+
     /// Field to "use", if any
     pub fn using_member(&self) -> Option<&Member> {
         self.using.as_ref()
@@ -541,6 +2383,70 @@ impl ImplArgs {
         }
     }
 
+    /// Get the where clause, less `trait` bounds on unused type parameters
+    ///
+    /// A `T: trait` bound is removed when `T` is a type parameter in
+    /// `generics` not used by any of `types` (excepting `PhantomData` and, if
+    /// `shared`, within `Rc`, `Arc` or `Weak`). Returns `None` when there is
+    /// nothing to remove.
+    fn trimmed_clause(
+        &self,
+        generics: &syn::Generics,
+        types: &[&Type],
+        shared: bool,
+    ) -> Option<WhereClause> {
+        if self.bound_override {
+            return None;
+        }
+        let clause = self.clause.as_ref()?;
+
+        let unused: Vec<&Ident> = generics
+            .type_params()
+            .map(|param| &param.ident)
+            .filter(|ident| {
+                !types
+                    .iter()
+                    .any(|ty| !is_phantom_data(ty) && type_uses_ident(ty, ident, shared))
+            })
+            .collect();
+
+        let mut clause = clause.clone();
+        let mut changed = false;
+        let predicates = std::mem::take(&mut clause.predicates);
+        for pair in predicates.into_pairs() {
+            let (mut pred, punct) = pair.into_tuple();
+            if let WherePredicate::Type(ref mut pred) = pred {
+                let is_unused = match pred.bounded_ty {
+                    Type::Path(ref path) if path.qself.is_none() => path
+                        .path
+                        .get_ident()
+                        .map(|ident| unused.contains(&ident))
+                        .unwrap_or(false),
+                    _ => false,
+                };
+                if is_unused {
+                    let len = pred.bounds.len();
+                    pred.bounds = std::mem::take(&mut pred.bounds)
+                        .into_pairs()
+                        .filter(|pair| !matches!(pair.value(), TypeParamBound::TraitSubst(_)))
+                        .collect();
+                    if pred.bounds.len() != len {
+                        changed = true;
+                        if pred.bounds.is_empty() {
+                            continue;
+                        }
+                    }
+                }
+            }
+            clause.predicates.push_value(pred);
+            if let Some(punct) = punct {
+                clause.predicates.push_punct(punct);
+            }
+        }
+
+        changed.then(|| clause)
+    }
+
     /// Call the given closure over all non-ignored fields
     pub fn for_fields<'f>(&self, fields: &'f Fields, f: impl FnMut(Member, &'f Field)) {
         self.for_fields_iter(fields.iter().enumerate(), f);
@@ -553,10 +2459,7 @@ impl ImplArgs {
         mut f: impl FnMut(Member, &'f Field),
     ) {
         for (i, field) in fields {
-            let member = match field.ident.clone() {
-                Some(ident) => Member::Named(ident),
-                None => Member::Unnamed(Index::from(i)),
-            };
+            let member = field_member(i, field);
             if !self.ignore(&member) {
                 f(member, field);
             }
@@ -0,0 +1,170 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Impls for `arbitrary` traits
+//!
+//! Generated code refers to `::arbitrary`; the user's crate must depend on it.
+
+use super::{field_members, Error, ImplArgs, ImplTrait, Result};
+use crate::generics::ImplGenericsBuilder;
+use crate::SimplePath;
+use proc_macro2::{Span, TokenStream as Toks};
+use quote::quote;
+use syn::{
+    Fields, GenericParam, Generics, Ident, ItemEnum, ItemStruct, Lifetime, LifetimeParam, Member,
+    Path,
+};
+
+/// Construct `tag` with fields `fields` from `u`
+///
+/// Fields for which `ignore` returns true use `Default::default()`.
+fn construct(
+    root: &Option<Path>,
+    tag: Toks,
+    fields: &Fields,
+    ignore: impl Fn(&Member) -> bool,
+) -> Toks {
+    let inits = field_members(fields).map(|(member, _)| {
+        if ignore(&member) {
+            quote! { #member: #root::core::default::Default::default() }
+        } else {
+            quote! { #member: #root::arbitrary::Arbitrary::arbitrary(u)? }
+        }
+    });
+    quote! { #tag { #(#inits),* } }
+}
+
+/// Generate the impl, adding lifetime `'arbitrary` to `generics`
+fn impl_with_lifetime(
+    generics: &Generics,
+    ident: &Ident,
+    args: &ImplArgs,
+    path: &Toks,
+    items: &Toks,
+) -> Toks {
+    let lt = Lifetime::new("'arbitrary", Span::call_site());
+    let mut lt_generics = generics.clone();
+    lt_generics
+        .params
+        .insert(0, GenericParam::Lifetime(LifetimeParam::new(lt)));
+    let (impl_generics, _, where_clause) = ImplGenericsBuilder::new(&lt_generics)
+        .clause(args.clause.as_ref())
+        .split_for_impl(path);
+    let (_, ty_generics, _) = generics.split_for_impl();
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics #path for #ident #ty_generics #where_clause {
+            #items
+        }
+    }
+}
+
+/// Implement `arbitrary::Arbitrary`
+///
+/// Each field is constructed via `Arbitrary::arbitrary`, except that ignored
+/// fields use `Default::default()`. For enums, the variant is chosen from
+/// the input; once the input is exhausted, recursion (e.g. via a
+/// `Box<Self>` field) fails with `Error::NotEnoughData`, tracked using a
+/// thread-local counter (thus requiring `std`). Within a `where` clause,
+/// `trait` is substituted with `::arbitrary::Arbitrary<'arbitrary>`.
+pub struct ImplArbitrary;
+impl ImplTrait for ImplArbitrary {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "arbitrary", "Arbitrary"])
+    }
+
+    fn support_ignore(&self) -> bool {
+        true
+    }
+
+    fn enum_impl(&self, item: &ItemEnum, args: &ImplArgs) -> Result<Toks> {
+        let (path, items) = self.enum_items(item, args)?;
+        Ok(impl_with_lifetime(
+            &item.generics,
+            &item.ident,
+            args,
+            &path,
+            &items,
+        ))
+    }
+
+    fn struct_impl(&self, item: &ItemStruct, args: &ImplArgs) -> Result<Toks> {
+        let (path, items) = self.struct_items(item, args)?;
+        Ok(impl_with_lifetime(
+            &item.generics,
+            &item.ident,
+            args,
+            &path,
+            &items,
+        ))
+    }
+
+    fn enum_items(&self, item: &ItemEnum, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        if item.variants.is_empty() {
+            return Err(Error::CallSite(
+                "`Arbitrary` is not supported on an enum without variants",
+            ));
+        }
+
+        let name = &item.ident;
+        let len = item.variants.len() as u64;
+        let arms = item.variants.iter().enumerate().map(|(i, v)| {
+            let i = i as u64;
+            let ident = &v.ident;
+            let value = construct(root, quote! { #name :: #ident }, &v.fields, |_| false);
+            quote! { #i => #value, }
+        });
+        // With exhausted input, each integer is zero, thus a recursive enum
+        // would choose the same variant indefinitely. Like the derive of
+        // `arbitrary`, we fail on recursion once the input is exhausted.
+        let method = quote! {
+            fn arbitrary(
+                u: &mut #root::arbitrary::Unstructured<'arbitrary>,
+            ) -> #root::arbitrary::Result<Self> {
+                #root::std::thread_local! {
+                    static __DEPTH: #root::core::cell::Cell<u32> = #root::core::cell::Cell::new(0);
+                }
+                let guard = u.is_empty();
+                if guard {
+                    let depth = __DEPTH.with(|depth| depth.replace(depth.get() + 1));
+                    if depth > 0 {
+                        __DEPTH.with(|depth| depth.set(depth.get() - 1));
+                        return #root::core::result::Result::Err(#root::arbitrary::Error::NotEnoughData);
+                    }
+                }
+                let mut inner = || -> #root::arbitrary::Result<Self> {
+                    let index = (u64::from(<u32 as #root::arbitrary::Arbitrary>::arbitrary(u)?) * #len) >> 32;
+                    #root::core::result::Result::Ok(match index {
+                        #(#arms)*
+                        _ => #root::core::unreachable!(),
+                    })
+                };
+                let result = inner();
+                if guard {
+                    __DEPTH.with(|depth| depth.set(depth.get() - 1));
+                }
+                result
+            }
+        };
+        Ok((quote! { #root::arbitrary::Arbitrary<'arbitrary> }, method))
+    }
+
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let ident = &item.ident;
+        let value = construct(root, quote! { #ident }, &item.fields, |member| {
+            args.ignore(member)
+        });
+        let method = quote! {
+            fn arbitrary(
+                u: &mut #root::arbitrary::Unstructured<'arbitrary>,
+            ) -> #root::arbitrary::Result<Self> {
+                #root::core::result::Result::Ok(#value)
+            }
+        };
+        Ok((quote! { #root::arbitrary::Arbitrary<'arbitrary> }, method))
+    }
+}
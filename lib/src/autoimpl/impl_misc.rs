@@ -5,11 +5,20 @@
 
 //! Miscellaneous impls
 
-use super::{ImplArgs, ImplTrait, Result};
+use super::{
+    field_cfgs, field_is_hidden, field_members, field_name, field_override, is_phantom_data,
+    type_argument, Error, FieldOverride, ImplArgs, ImplTrait, Result,
+};
+use crate::generics::ImplGenericsBuilder;
 use crate::{IdentFormatter, SimplePath};
-use proc_macro2::TokenStream as Toks;
+use proc_macro2::{Group, Span, TokenStream as Toks, TokenTree};
 use quote::{quote, ToTokens, TokenStreamExt};
-use syn::{Fields, Index, ItemEnum, ItemStruct, Member, Token};
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{
+    Attribute, Field, Fields, Generics, Ident, Index, ItemEnum, ItemStruct, ItemUnion, Member,
+    Path, Token, Type,
+};
 
 /// Implement [`core::clone::Clone`]
 pub struct ImplClone;
@@ -18,11 +27,33 @@ impl ImplTrait for ImplClone {
         SimplePath::new(&["", "core", "clone", "Clone"])
     }
 
+    fn exempt_shared_pointers(&self) -> bool {
+        true
+    }
+
+    fn support_field_cfgs(&self) -> bool {
+        true
+    }
+
+    fn support_field_bounds(&self) -> bool {
+        true
+    }
+
     fn support_ignore(&self) -> bool {
         true
     }
 
-    fn enum_items(&self, item: &ItemEnum, _: &ImplArgs) -> Result<(Toks, Toks)> {
+    fn support_option(&self, name: &str) -> bool {
+        name == "copy"
+    }
+
+    fn enum_items(&self, item: &ItemEnum, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        if let Some(method) = clone_by_copy(args)? {
+            return Ok((quote! { #root::core::clone::Clone }, method));
+        }
+        check_no_variant_override(item, FieldOverride::Clone)?;
+
         let mut idfmt = IdentFormatter::new();
         let name = &item.ident;
         let mut variants = Toks::new();
@@ -31,21 +62,47 @@ impl ImplTrait for ImplClone {
             let tag = quote! { #name :: #ident };
             variants.append_all(match v.fields {
                 Fields::Named(ref fields) => {
-                    let idents = fields.named.iter().map(|f| f.ident.as_ref().unwrap());
+                    let bindings =
+                        fields
+                            .named
+                            .iter()
+                            .filter(|f| !is_phantom_data(&f.ty))
+                            .map(|f| {
+                                let ident = f.ident.as_ref().unwrap();
+                                let cfgs = field_cfgs(f);
+                                quote! { #(#cfgs)* ref #ident }
+                            });
                     let clones = fields.named.iter().map(|f| {
                         let ident = f.ident.as_ref().unwrap();
-                        quote! { #ident: #ident.clone() }
+                        let cfgs = field_cfgs(f);
+                        let value = if is_phantom_data(&f.ty) {
+                            quote! { #root::core::marker::PhantomData }
+                        } else if is_copy_type(&f.ty) {
+                            quote! { *#ident }
+                        } else {
+                            quote! { #root::core::clone::Clone::clone(#ident) }
+                        };
+                        quote! { #(#cfgs)* #ident: #value }
                     });
-                    quote! { #tag { #(#idents),* } => #tag { #(#clones),* }, }
+                    quote! { #tag { #(#bindings,)* .. } => #tag { #(#clones),* }, }
                 }
                 Fields::Unnamed(ref fields) => {
                     let len = fields.unnamed.len();
                     let mut bindings = Vec::with_capacity(len);
                     let mut items = Vec::with_capacity(len);
-                    for i in 0..len {
+                    for (i, field) in fields.unnamed.iter().enumerate() {
+                        if is_phantom_data(&field.ty) {
+                            bindings.push(quote! { _ });
+                            items.push(quote! { #root::core::marker::PhantomData });
+                            continue;
+                        }
                         let ident = idfmt.make_call_site(format_args!("_{i}"));
                         bindings.push(quote! { ref #ident });
-                        items.push(quote! { #ident.clone() });
+                        if is_copy_type(&field.ty) {
+                            items.push(quote! { *#ident });
+                        } else {
+                            items.push(quote! { #root::core::clone::Clone::clone(#ident) });
+                        }
                     }
                     quote! { #tag ( #(#bindings),* ) => #tag ( #(#items),* ), }
                 }
@@ -59,44 +116,172 @@ impl ImplTrait for ImplClone {
                 }
             }
         };
-        Ok((quote! { ::core::clone::Clone }, method))
+        Ok((quote! { #root::core::clone::Clone }, method))
+    }
+
+    fn union_items(&self, _: &ItemUnion, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        // A union may only be cloned by copy
+        let method = quote! {
+            #[inline]
+            fn clone(&self) -> Self {
+                *self
+            }
+        };
+        Ok((quote! { #root::core::clone::Clone }, method))
     }
 
     fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        if let Some(method) = clone_by_copy(args)? {
+            return Ok((quote! { #root::core::clone::Clone }, method));
+        }
+
         let type_ident = &item.ident;
         let inner = match &item.fields {
             Fields::Named(fields) => {
                 let mut toks = Toks::new();
                 for field in fields.named.iter() {
                     let ident = field.ident.as_ref().unwrap();
-                    if args.ignore_named(ident) {
-                        toks.append_all(quote! { #ident: Default::default(), });
+                    let value = if args.ignore_named(ident) {
+                        ignored_value(args, Member::Named(ident.clone()))
+                    } else if let Some(expr) = field_override(field, FieldOverride::Clone) {
+                        expr.to_token_stream()
+                    } else if is_phantom_data(&field.ty) {
+                        quote! { #root::core::marker::PhantomData }
+                    } else if is_copy_type(&field.ty) {
+                        quote! { self.#ident }
                     } else {
-                        toks.append_all(quote! { #ident: self.#ident.clone(), });
-                    }
+                        quote! { #root::core::clone::Clone::clone(&self.#ident) }
+                    };
+                    let cfgs = field_cfgs(field);
+                    toks.append_all(quote! { #(#cfgs)* #ident: #value, });
                 }
                 quote! { #type_ident { #toks } }
             }
             Fields::Unnamed(fields) => {
                 let mut toks = Toks::new();
-                for i in 0..fields.unnamed.len() {
+                for (i, field) in fields.unnamed.iter().enumerate() {
                     let index = Index::from(i);
                     if args.ignore_unnamed(&index) {
-                        toks.append_all(quote! { Default::default(), });
+                        let value = ignored_value(args, Member::Unnamed(index));
+                        toks.append_all(quote! { #value, });
+                    } else if let Some(expr) = field_override(field, FieldOverride::Clone) {
+                        toks.append_all(quote! { #expr, });
+                    } else if is_phantom_data(&field.ty) {
+                        toks.append_all(quote! { #root::core::marker::PhantomData, });
+                    } else if is_copy_type(&field.ty) {
+                        toks.append_all(quote! { self.#index, });
                     } else {
-                        toks.append_all(quote! { self.#index.clone(), });
+                        toks.append_all(quote! { #root::core::clone::Clone::clone(&self.#index), });
                     }
                 }
                 quote! { #type_ident ( #toks ) }
             }
             Fields::Unit => quote! { #type_ident },
         };
+
+        // clone_from reuses the resources of each field of self
+        let mut assigns = Toks::new();
+        for (member, field) in field_members(&item.fields) {
+            let assign = if args.ignore(&member) {
+                let value = ignored_value(args, member.clone());
+                quote! { self.#member = #value; }
+            } else if is_phantom_data(&field.ty) {
+                continue;
+            } else if is_copy_type(&field.ty) {
+                quote! { self.#member = source.#member; }
+            } else {
+                quote! {
+                    #root::core::clone::Clone::clone_from(&mut self.#member, &source.#member);
+                }
+            };
+            // Attributes on assignment expressions are unstable, thus use a block
+            let cfgs = field_cfgs(field);
+            if cfgs.is_empty() {
+                assigns.append_all(assign);
+            } else {
+                assigns.append_all(quote! { #(#cfgs)* { #assign } });
+            }
+        }
+        // Override expressions are over `self`, thus clone_from uses the default
+        let has_override = item
+            .fields
+            .iter()
+            .any(|field| field_override(field, FieldOverride::Clone).is_some());
+        let clone_from = if item.fields.is_empty() || has_override {
+            Toks::new()
+        } else {
+            quote! {
+                #[allow(unused_variables)]
+                fn clone_from(&mut self, source: &Self) {
+                    #assigns
+                }
+            }
+        };
+
         let method = quote! {
             fn clone(&self) -> Self {
                 #inner
             }
+            #clone_from
         };
-        Ok((quote! { ::core::clone::Clone }, method))
+        Ok((quote! { #root::core::clone::Clone }, method))
+    }
+}
+
+/// Value of an ignored field: the given value or `Default::default()`
+fn ignored_value(args: &ImplArgs, member: Member) -> Toks {
+    let root = &args.root;
+    match args.ignore_value(&member) {
+        Some(expr) => expr.to_token_stream(),
+        None => quote! { #root::core::default::Default::default() },
+    }
+}
+
+/// With option `copy`, generate a `clone` method returning `*self`
+fn clone_by_copy(args: &ImplArgs) -> Result<Option<Toks>> {
+    if !args.option("copy") {
+        return Ok(None);
+    }
+    if let Some(mem) = args.ignores.first() {
+        return Err(Error::WithSpan(
+            mem.span(),
+            "`Clone with copy` does not support `ignore`",
+        ));
+    }
+    Ok(Some(quote! {
+        #[inline]
+        fn clone(&self) -> Self {
+            *self
+        }
+    }))
+}
+
+/// True if `ty` is known to be `Copy`
+///
+/// This matches primitive numeric types, `bool`, `char`, shared references
+/// and tuples and arrays of these. Other types are assumed not to be `Copy`.
+fn is_copy_type(ty: &Type) -> bool {
+    const PRIMITIVES: &[&str] = &[
+        "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16",
+        "u32", "u64", "u128", "usize",
+    ];
+    match ty {
+        Type::Path(path) => {
+            path.qself.is_none()
+                && path
+                    .path
+                    .get_ident()
+                    .map(|ident| PRIMITIVES.iter().any(|prim| ident == prim))
+                    .unwrap_or(false)
+        }
+        Type::Reference(reference) => reference.mutability.is_none(),
+        Type::Tuple(tuple) => tuple.elems.iter().all(is_copy_type),
+        Type::Array(array) => is_copy_type(&array.elem),
+        Type::Paren(paren) => is_copy_type(&paren.elem),
+        Type::Group(group) => is_copy_type(&group.elem),
+        _ => false,
     }
 }
 
@@ -107,19 +292,143 @@ impl ImplTrait for ImplCopy {
         SimplePath::new(&["", "core", "marker", "Copy"])
     }
 
-    fn allow_ignore_with(&self) -> Option<SimplePath> {
-        Some(SimplePath::new(&["", "core", "clone", "Clone"]))
+    fn support_field_bounds(&self) -> bool {
+        true
+    }
+
+    fn support_field_cfgs(&self) -> bool {
+        true
+    }
+
+    fn enum_items(&self, _: &ItemEnum, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        Ok((quote! { #root::core::marker::Copy }, quote! {}))
+    }
+
+    fn union_items(&self, _: &ItemUnion, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        Ok((quote! { #root::core::marker::Copy }, quote! {}))
     }
 
-    fn enum_items(&self, _: &ItemEnum, _: &ImplArgs) -> Result<(Toks, Toks)> {
-        Ok((quote! { ::core::marker::Copy }, quote! {}))
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        // `Clone` must be equivalent to a copy
+        if let Some(expr) = item
+            .fields
+            .iter()
+            .find_map(|field| field_override(field, FieldOverride::Clone))
+        {
+            return Err(Error::WithSpan(
+                expr.span(),
+                "field attribute `clone = EXPR` is not supported with `Copy`",
+            ));
+        }
+        Ok((quote! { #root::core::marker::Copy }, quote! {}))
     }
+}
 
-    fn struct_items(&self, _: &ItemStruct, _: &ImplArgs) -> Result<(Toks, Toks)> {
-        Ok((quote! { ::core::marker::Copy }, quote! {}))
+/// Report an error if any variant field of `item` has an override of `kind`
+///
+/// Overrides of `clone` and `debug` are expressions over `self.FIELD`, which
+/// is not meaningful for enum variants.
+fn check_no_variant_override(item: &ItemEnum, kind: FieldOverride) -> Result<()> {
+    let fields = item.variants.iter().flat_map(|v| v.fields.iter());
+    if let Some(expr) = fields
+        .filter_map(|field| field_override(field, kind))
+        .next()
+    {
+        let msg = if kind == FieldOverride::Clone {
+            "field attribute `clone = EXPR` is not supported on enums"
+        } else {
+            "field attribute `debug = EXPR` is not supported on enums"
+        };
+        return Err(Error::WithSpan(expr.span(), msg));
+    }
+    Ok(())
+}
+
+/// Generate an `unsafe impl` of marker trait `path` for `ident`
+///
+/// Without a `where` clause, each type parameter is bounded by `path`.
+fn unsafe_marker_impl(ident: &Ident, generics: &Generics, args: &ImplArgs, path: &Toks) -> Toks {
+    let inferred;
+    let mut builder = ImplGenericsBuilder::new(generics).clause(args.clause.as_ref());
+    if args.clause.is_none() {
+        let params: Vec<_> = generics.type_params().map(|param| &param.ident).collect();
+        if !params.is_empty() {
+            inferred = syn::parse_quote! { where #(#params: trait),* };
+            builder = builder.clause(Some(&inferred));
+        }
     }
+    let (impl_generics, ty_generics, where_clause) = builder.split_for_impl(path);
+    quote! {
+        unsafe impl #impl_generics #path for #ident #ty_generics #where_clause {}
+    }
+}
+
+macro_rules! impl_unsafe_marker {
+    ($(#[$doc:meta])* $name:ident, $trait:ident) => {
+        $(#[$doc])*
+        pub struct $name;
+        impl ImplTrait for $name {
+            fn path(&self) -> SimplePath {
+                SimplePath::new(&["", "core", "marker", stringify!($trait)])
+            }
+
+            fn require_unsafe(&self) -> bool {
+                true
+            }
+
+            fn support_field_cfgs(&self) -> bool {
+                true
+            }
+
+            fn enum_impl(&self, item: &ItemEnum, args: &ImplArgs) -> Result<Toks> {
+                let (path, _) = self.enum_items(item, args)?;
+                Ok(unsafe_marker_impl(&item.ident, &item.generics, args, &path))
+            }
+
+            fn struct_impl(&self, item: &ItemStruct, args: &ImplArgs) -> Result<Toks> {
+                let (path, _) = self.struct_items(item, args)?;
+                Ok(unsafe_marker_impl(&item.ident, &item.generics, args, &path))
+            }
+
+            fn union_impl(&self, item: &ItemUnion, args: &ImplArgs) -> Result<Toks> {
+                let (path, _) = self.union_items(item, args)?;
+                Ok(unsafe_marker_impl(&item.ident, &item.generics, args, &path))
+            }
+
+            fn enum_items(&self, _: &ItemEnum, args: &ImplArgs) -> Result<(Toks, Toks)> {
+                let root = &args.root;
+                Ok((quote! { #root::core::marker::$trait }, quote! {}))
+            }
+
+            fn struct_items(&self, _: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+                let root = &args.root;
+                Ok((quote! { #root::core::marker::$trait }, quote! {}))
+            }
+
+            fn union_items(&self, _: &ItemUnion, args: &ImplArgs) -> Result<(Toks, Toks)> {
+                let root = &args.root;
+                Ok((quote! { #root::core::marker::$trait }, quote! {}))
+            }
+        }
+    };
 }
 
+impl_unsafe_marker!(
+    /// Implement [`core::marker::Send`] (`unsafe`)
+    ///
+    /// The target must be written as `unsafe Send`.
+    ImplSend, Send
+);
+impl_unsafe_marker!(
+    /// Implement [`core::marker::Sync`] (`unsafe`)
+    ///
+    /// The target must be written as `unsafe Sync`.
+    ImplSync, Sync
+);
+
 /// Implement [`core::fmt::Debug`]
 pub struct ImplDebug;
 impl ImplTrait for ImplDebug {
@@ -127,40 +436,82 @@ impl ImplTrait for ImplDebug {
         SimplePath::new(&["", "core", "fmt", "Debug"])
     }
 
+    fn support_field_bounds(&self) -> bool {
+        true
+    }
+
+    fn support_field_cfgs(&self) -> bool {
+        true
+    }
+
     fn support_ignore(&self) -> bool {
         true
     }
 
-    fn enum_items(&self, item: &ItemEnum, _: &ImplArgs) -> Result<(Toks, Toks)> {
+    fn support_option(&self, name: &str) -> bool {
+        name == "type_name"
+    }
+
+    fn support_redact(&self) -> bool {
+        true
+    }
+
+    fn support_fmt(&self) -> bool {
+        true
+    }
+
+    fn enum_items(&self, item: &ItemEnum, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        check_no_variant_override(item, FieldOverride::Debug)?;
         let mut idfmt = IdentFormatter::new();
         let name = &item.ident;
-        let type_name = item.ident.to_string();
+        let (type_name, name_stmt) = debug_type_name(&item.ident, args);
+        let debug = debug_ident();
         let mut variants = Toks::new();
         for v in item.variants.iter() {
             let ident = &v.ident;
-            let var_name = ident.to_string();
+            let var_name = ident.unraw().to_string();
             let tag = quote! { #name :: #ident };
             variants.append_all(match v.fields {
                 Fields::Named(ref fields) => {
-                    let idents = fields.named.iter().map(|f| f.ident.as_ref().unwrap());
-                    let mut items = Toks::new();
+                    let mut bindings = Vec::with_capacity(fields.named.len());
+                    let mut stmts = Toks::new();
+                    let mut finish = quote! { finish };
                     for field in fields.named.iter() {
                         let ident = field.ident.as_ref().unwrap();
-                        let name = ident.to_string();
-                        items.append_all(quote! { .field(#name, #ident) });
+                        let cfgs = field_cfgs(field);
+                        bindings.push(quote! { #(#cfgs)* ref #ident });
+                        if field_is_hidden(field) {
+                            finish = quote! { finish_non_exhaustive };
+                            continue;
+                        } else if is_phantom_data(&field.ty) {
+                            continue;
+                        }
+                        let name = field_name(field).unwrap();
+                        stmts.append_all(quote! { #(#cfgs)* #debug.field(#name, #ident); });
                     }
                     quote! {
-                        #tag { #(ref #idents),* } => f.debug_struct(#var_name) #items .finish(),
+                        #tag { #(#bindings),* } => {
+                            let mut #debug = f.debug_struct(#var_name);
+                            #stmts
+                            #debug.#finish()
+                        }
                     }
                 }
                 Fields::Unnamed(ref fields) => {
                     let len = fields.unnamed.len();
                     let mut bindings = Vec::with_capacity(len);
                     let mut items = Toks::new();
-                    for i in 0..len {
+                    for (i, field) in fields.unnamed.iter().enumerate() {
                         let ident = idfmt.make_call_site(format_args!("_{i}"));
                         bindings.push(quote! { ref #ident });
-                        items.append_all(quote! { .field(#ident) });
+                        if field_is_hidden(field) {
+                            items.append_all(quote! { .field(&#root::core::format_args!("_")) });
+                        } else if is_phantom_data(&field.ty) {
+                            continue;
+                        } else {
+                            items.append_all(quote! { .field(#ident) });
+                        }
                     }
                     quote! {
                         #tag ( #(#bindings),* ) => f.debug_tuple(#var_name) #items .finish(),
@@ -172,51 +523,85 @@ impl ImplTrait for ImplDebug {
 
         // Note: unlike #[derive(Debug)], we include the name of the enum!
         let method = quote! {
-            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-                write!(f, "{}::", #type_name)?;
+            fn fmt(&self, f: &mut #root::core::fmt::Formatter) -> #root::core::fmt::Result {
+                #name_stmt
+                #root::core::write!(f, "{}::", #type_name)?;
                 match *self {
                     #variants
                 }
             }
         };
-        Ok((quote! { ::core::fmt::Debug }, method))
+        Ok((quote! { #root::core::fmt::Debug }, method))
     }
 
     fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
-        let type_name = item.ident.to_string();
+        let root = &args.root;
+        let (type_name, name_stmt) = debug_type_name(&item.ident, args);
         let mut inner;
         match &item.fields {
             Fields::Named(fields) => {
-                inner = quote! { f.debug_struct(#type_name) };
+                let debug = debug_ident();
+                let mut stmts = Toks::new();
                 let mut no_skips = true;
                 for field in fields.named.iter() {
                     let ident = field.ident.as_ref().unwrap();
-                    if !args.ignore_named(ident) {
-                        let name = ident.to_string();
-                        inner.append_all(quote! {
-                            .field(#name, &self.#ident)
+                    if !args.ignore_named(ident) && !field_is_hidden(field) {
+                        let name = field_name(field).unwrap();
+                        let member = Member::Named(ident.clone());
+                        let value = if let Some(expr) = args.fmt(&member) {
+                            quote! { &(#expr) }
+                        } else if let Some(expr) = field_override(field, FieldOverride::Debug) {
+                            quote! { &(#expr) }
+                        } else if is_phantom_data(&field.ty) {
+                            continue;
+                        } else if args.redact(&member) {
+                            quote! { &#root::core::format_args!("***") }
+                        } else {
+                            quote! { &self.#ident }
+                        };
+                        let cfgs = field_cfgs(field);
+                        stmts.append_all(quote! {
+                            #(#cfgs)*
+                            #debug.field(#name, #value);
                         });
                     } else {
                         no_skips = false;
                     }
                 }
-                if no_skips {
-                    inner.append_all(quote! { .finish() });
+                let finish = if no_skips {
+                    quote! { finish }
                 } else {
-                    inner.append_all(quote! { .finish_non_exhaustive() });
+                    quote! { finish_non_exhaustive }
+                };
+                inner = quote! {
+                    let mut #debug = f.debug_struct(#type_name);
+                    #stmts
+                    #debug.#finish()
                 };
             }
             Fields::Unnamed(fields) => {
                 inner = quote! { f.debug_tuple(#type_name) };
-                for i in 0..fields.unnamed.len() {
+                for (i, field) in fields.unnamed.iter().enumerate() {
                     let index = Index::from(i);
-                    if !args.ignore_unnamed(&index) {
+                    if !args.ignore_unnamed(&index) && !field_is_hidden(field) {
+                        let member = Member::Unnamed(index.clone());
+                        let value = if let Some(expr) = args.fmt(&member) {
+                            quote! { &(#expr) }
+                        } else if let Some(expr) = field_override(field, FieldOverride::Debug) {
+                            quote! { &(#expr) }
+                        } else if is_phantom_data(&field.ty) {
+                            continue;
+                        } else if args.redact(&member) {
+                            quote! { &#root::core::format_args!("***") }
+                        } else {
+                            quote! { &self.#index }
+                        };
                         inner.append_all(quote! {
-                            .field(&self.#index)
+                            .field(#value)
                         });
                     } else {
                         inner.append_all(quote! {
-                            .field(&format_args!("_"))
+                            .field(&#root::core::format_args!("_"))
                         });
                     }
                 }
@@ -225,14 +610,202 @@ impl ImplTrait for ImplDebug {
             Fields::Unit => inner = quote! { f.write_str(#type_name) },
         };
         let method = quote! {
-            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            fn fmt(&self, f: &mut #root::core::fmt::Formatter) -> #root::core::fmt::Result {
+                #name_stmt
                 #inner
             }
         };
-        Ok((quote! { ::core::fmt::Debug }, method))
+        Ok((quote! { #root::core::fmt::Debug }, method))
     }
 }
 
+/// Identifier of the `DebugStruct` builder local to `fmt`
+///
+/// Field statements may be configured out (see [`field_cfgs`]), thus the
+/// builder is bound to a (hygienic) local rather than chained.
+fn debug_ident() -> Ident {
+    Ident::new("debug", Span::mixed_site())
+}
+
+/// Get the type name used by `Debug` output, plus any statement required to compute it
+///
+/// With option `type_name`, this is the result of [`core::any::type_name`]
+/// less the module path of the type (but not of its generic arguments).
+fn debug_type_name(ident: &Ident, args: &ImplArgs) -> (Toks, Toks) {
+    let root = &args.root;
+    if args.option("type_name") {
+        let stmt = quote! {
+            let type_name = #root::core::any::type_name::<Self>();
+            let type_name = {
+                let end = type_name.find('<').unwrap_or(type_name.len());
+                let start = type_name[..end].rfind("::").map(|i| i + 2).unwrap_or(0);
+                &type_name[start..]
+            };
+        };
+        (quote! { type_name }, stmt)
+    } else {
+        let type_name = ident.unraw().to_string();
+        (quote! { #type_name }, Toks::new())
+    }
+}
+
+/// Find the type of the first non-ignored field which is or contains `f32` or `f64`
+///
+/// Only built-in compound types (arrays, slices, tuples, references) are
+/// searched. If a field is "used", only that field is checked.
+fn find_float_field<'a>(item: &'a ItemStruct, args: &ImplArgs) -> Option<&'a Type> {
+    fn is_float(ty: &Type) -> bool {
+        match ty {
+            Type::Path(path) if path.qself.is_none() => path
+                .path
+                .get_ident()
+                .map(|ident| ident == "f32" || ident == "f64")
+                .unwrap_or(false),
+            Type::Array(array) => is_float(&array.elem),
+            Type::Slice(slice) => is_float(&slice.elem),
+            Type::Reference(reference) => is_float(&reference.elem),
+            Type::Paren(paren) => is_float(&paren.elem),
+            Type::Group(group) => is_float(&group.elem),
+            Type::Tuple(tuple) => tuple.elems.iter().any(is_float),
+            _ => false,
+        }
+    }
+
+    if args.using_member().is_some() {
+        return args
+            .using_field(&item.fields)
+            .map(|field| &field.ty)
+            .filter(|ty| is_float(ty));
+    } else if !args.by.is_empty() {
+        // Types of keys are unknown
+        return None;
+    }
+
+    let mut found = None;
+    args.for_fields(&item.fields, |_, field| {
+        if found.is_none() && is_float(&field.ty) {
+            found = Some(&field.ty);
+        }
+    });
+    found
+}
+
+/// Get `(cfgs, lhs, rhs)` operands for comparison of `self` and `other`
+///
+/// These are either the keys given by `by` (with `self` replaced by `other` on
+/// the right-hand side) or non-ignored fields (with their `#[cfg]` attributes).
+fn operands<'a>(item: &'a ItemStruct, args: &ImplArgs) -> Vec<Operand<'a>> {
+    fn replace_self(tokens: Toks) -> Toks {
+        tokens
+            .into_iter()
+            .map(|tt| match tt {
+                TokenTree::Ident(ident) if ident == "self" => {
+                    TokenTree::Ident(Ident::new("other", ident.span()))
+                }
+                TokenTree::Group(group) => {
+                    let mut g = Group::new(group.delimiter(), replace_self(group.stream()));
+                    g.set_span(group.span());
+                    TokenTree::Group(g)
+                }
+                tt => tt,
+            })
+            .collect()
+    }
+
+    let mut operands = vec![];
+    if let Some(member) = args.using_member() {
+        // Compare only the key field
+        operands.push((vec![], quote! { self.#member }, quote! { other.#member }));
+    } else if !args.by.is_empty() {
+        for key in &args.by {
+            let lhs = quote! { (#key) };
+            let rhs = replace_self(lhs.clone());
+            operands.push((vec![], lhs, rhs));
+        }
+    } else {
+        args.for_fields(&item.fields, |member: Member, field| {
+            let lhs = quote! { self.#member };
+            let rhs = quote! { other.#member };
+            operands.push((field_cfgs(field), lhs, rhs));
+        });
+    }
+    operands
+}
+
+/// A comparison operand: `#[cfg]` attributes, left- and right-hand sides
+type Operand<'a> = (Vec<&'a Attribute>, Toks, Toks);
+
+/// Generate an expression testing equality of all `operands`
+///
+/// If any operand has `#[cfg]` attributes, this is a block of conditional
+/// early-return statements, otherwise a chain of `&&`.
+fn eq_chain(operands: Vec<Operand>) -> Toks {
+    if operands.iter().any(|(cfgs, _, _)| !cfgs.is_empty()) {
+        let stmts = operands.into_iter().map(|(cfgs, lhs, rhs)| {
+            quote! {
+                #(#cfgs)*
+                if !(#lhs == #rhs) {
+                    return false;
+                }
+            }
+        });
+        return quote! { { #(#stmts)* true } };
+    }
+
+    let mut toks = Toks::new();
+    let mut require_sep = false;
+    for (_, lhs, rhs) in operands {
+        if require_sep {
+            <Token![&&]>::default().to_tokens(&mut toks);
+        }
+        toks.append_all(quote! { #lhs == #rhs });
+        require_sep = true;
+    }
+    if toks.is_empty() {
+        toks = quote! { true };
+    }
+    toks
+}
+
+/// Generate an expression comparing `operands` lexicographically via `path`
+///
+/// `equal` is the result (and pattern) of equal comparison. If any operand has
+/// `#[cfg]` attributes, this is a block of conditional early-return
+/// statements, otherwise nested `match` expressions.
+fn cmp_chain(operands: Vec<Operand>, path: &Toks, equal: &Toks) -> Toks {
+    if operands.iter().any(|(cfgs, _, _)| !cfgs.is_empty()) {
+        let stmts = operands.into_iter().map(|(cfgs, lhs, rhs)| {
+            quote! {
+                #(#cfgs)*
+                match #path(#lhs, #rhs) {
+                    #equal => (),
+                    cmp => return cmp,
+                }
+            }
+        });
+        return quote! { { #(#stmts)* #equal } };
+    }
+
+    let mut toks = Toks::new();
+    for (_, lhs, rhs) in operands.into_iter().rev() {
+        let cmp = quote! { #path(#lhs, #rhs) };
+        if toks.is_empty() {
+            toks = cmp;
+        } else {
+            toks = quote! {
+                match #cmp {
+                    #equal => #toks,
+                    cmp => cmp,
+                }
+            };
+        }
+    }
+    if toks.is_empty() {
+        toks = equal.clone();
+    }
+    toks
+}
+
 /// Implement [`core::default::Default`]
 pub struct ImplDefault;
 impl ImplTrait for ImplDefault {
@@ -240,7 +813,62 @@ impl ImplTrait for ImplDefault {
         SimplePath::new(&["", "core", "default", "Default"])
     }
 
-    fn struct_items(&self, item: &ItemStruct, _: &ImplArgs) -> Result<(Toks, Toks)> {
+    fn support_field_bounds(&self) -> bool {
+        true
+    }
+
+    fn support_field_cfgs(&self) -> bool {
+        true
+    }
+
+    fn enum_items(&self, item: &ItemEnum, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let mut variants = item
+            .variants
+            .iter()
+            .filter(|v| v.attrs.iter().any(|attr| attr.path().is_ident("default")));
+        let variant = variants.next().ok_or(Error::CallSite(
+            "enum expansion requires a variant marked `#[default]`",
+        ))?;
+        if let Some(v) = variants.next() {
+            return Err(Error::WithSpan(
+                v.ident.span(),
+                "multiple variants marked `#[default]`",
+            ));
+        }
+
+        let name = &item.ident;
+        let ident = &variant.ident;
+        let value = |f: &Field| match field_override(f, FieldOverride::Default) {
+            Some(expr) => expr.to_token_stream(),
+            None => default_value(root, f),
+        };
+        let inner = match &variant.fields {
+            Fields::Named(fields) => {
+                let values = fields.named.iter().map(|f| {
+                    let ident = f.ident.as_ref().unwrap();
+                    let cfgs = field_cfgs(f);
+                    let value = value(f);
+                    quote! { #(#cfgs)* #ident: #value }
+                });
+                quote! { #name::#ident { #(#values),* } }
+            }
+            Fields::Unnamed(fields) => {
+                let values = fields.unnamed.iter().map(value);
+                quote! { #name::#ident(#(#values),*) }
+            }
+            Fields::Unit => quote! { #name::#ident },
+        };
+        let method = quote! {
+            fn default() -> Self {
+                #inner
+            }
+        };
+        Ok((quote! { #root::core::default::Default }, method))
+    }
+
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
         let type_ident = &item.ident;
         let mut inner;
         match &item.fields {
@@ -248,14 +876,25 @@ impl ImplTrait for ImplDefault {
                 inner = quote! {};
                 for field in fields.named.iter() {
                     let ident = field.ident.as_ref().unwrap();
-                    inner.append_all(quote! { #ident: Default::default(), });
+                    let cfgs = field_cfgs(field);
+                    let value = match field_override(field, FieldOverride::Default) {
+                        Some(expr) => expr.to_token_stream(),
+                        None => default_value(root, field),
+                    };
+                    inner.append_all(quote! { #(#cfgs)* #ident: #value, });
                 }
                 inner = quote! { #type_ident { #inner } };
             }
             Fields::Unnamed(fields) => {
                 inner = quote! {};
-                for _ in 0..fields.unnamed.len() {
-                    inner.append_all(quote! { Default::default(), });
+                for field in fields.unnamed.iter() {
+                    match field_override(field, FieldOverride::Default) {
+                        Some(expr) => inner.append_all(quote! { #expr, }),
+                        None => {
+                            let value = default_value(root, field);
+                            inner.append_all(quote! { #value, });
+                        }
+                    }
                 }
                 inner = quote! { #type_ident(#inner) };
             }
@@ -266,7 +905,16 @@ impl ImplTrait for ImplDefault {
                 #inner
             }
         };
-        Ok((quote! { ::core::default::Default }, method))
+        Ok((quote! { #root::core::default::Default }, method))
+    }
+}
+
+/// Default value of a field: `PhantomData` or `Default::default()`
+fn default_value(root: &Option<Path>, field: &Field) -> Toks {
+    if is_phantom_data(&field.ty) {
+        quote! { #root::core::marker::PhantomData }
+    } else {
+        quote! { #root::core::default::Default::default() }
     }
 }
 
@@ -279,11 +927,42 @@ impl ImplTrait for ImplPartialEq {
         SimplePath::new(&["", "core", "cmp", "PartialEq"])
     }
 
+    fn support_field_bounds(&self) -> bool {
+        true
+    }
+
+    fn support_field_cfgs(&self) -> bool {
+        true
+    }
+
+    fn support_path_arguments(&self) -> bool {
+        true
+    }
+
+    fn support_by(&self) -> bool {
+        true
+    }
+
     fn support_ignore(&self) -> bool {
         true
     }
 
-    fn enum_items(&self, item: &ItemEnum, _: &ImplArgs) -> Result<(Toks, Toks)> {
+    fn support_enum_ignore(&self) -> bool {
+        true
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn enum_items(&self, item: &ItemEnum, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        if type_argument(args)?.is_some() {
+            return Err(Error::PathArguments(
+                "enum expansion does not support a comparison type",
+            ));
+        }
+
         let mut idfmt = IdentFormatter::new();
         let name = &item.ident;
         let mut variants = Toks::new();
@@ -294,19 +973,23 @@ impl ImplTrait for ImplPartialEq {
                 Fields::Named(ref fields) => {
                     let mut l_args = quote! {};
                     let mut r_args = quote! {};
-                    let mut cond = quote! {};
+                    let mut operands = vec![];
                     for (i, field) in fields.named.iter().enumerate() {
                         let ident = field.ident.as_ref().unwrap();
+                        let cfgs = field_cfgs(field);
+                        if args.ignore_named(ident) {
+                            l_args.append_all(quote! { #(#cfgs)* #ident: _, });
+                            r_args.append_all(quote! { #(#cfgs)* #ident: _, });
+                            continue;
+                        }
                         let li = idfmt.make_call_site(format_args!("__l{i}"));
                         let ri = idfmt.make_call_site(format_args!("__r{i}"));
-                        l_args.append_all(quote! { #ident: #li, });
-                        r_args.append_all(quote! { #ident: #ri, });
-                        if !cond.is_empty() {
-                            cond.append_all(quote! { && });
-                        }
-                        cond.append_all(quote! { #li == #ri });
+                        l_args.append_all(quote! { #(#cfgs)* #ident: #li, });
+                        r_args.append_all(quote! { #(#cfgs)* #ident: #ri, });
+                        operands.push((cfgs, quote! { #li }, quote! { #ri }));
                     }
 
+                    let cond = eq_chain(operands);
                     quote! { (#tag { #l_args }, #tag { #r_args }) => #cond, }
                 }
                 Fields::Unnamed(ref fields) => {
@@ -315,6 +998,11 @@ impl ImplTrait for ImplPartialEq {
                     let mut r_args = quote! {};
                     let mut cond = quote! {};
                     for i in 0..len {
+                        if args.ignore_unnamed(&Index::from(i)) {
+                            l_args.append_all(quote! { _, });
+                            r_args.append_all(quote! { _, });
+                            continue;
+                        }
                         let li = idfmt.make_call_site(format_args!("__l{i}"));
                         let ri = idfmt.make_call_site(format_args!("__r{i}"));
                         l_args.append_all(quote! { #li, });
@@ -325,6 +1013,9 @@ impl ImplTrait for ImplPartialEq {
                         cond.append_all(quote! { #li == #ri });
                     }
 
+                    if cond.is_empty() {
+                        cond = quote! { true };
+                    }
                     quote! { (#tag ( #l_args ), #tag ( #r_args )) => #cond, }
                 }
                 Fields::Unit => quote! { (#tag, #tag) => true, },
@@ -340,30 +1031,37 @@ impl ImplTrait for ImplPartialEq {
                 }
             }
         };
-        Ok((quote! { ::core::cmp::PartialEq }, method))
+        Ok((quote! { #root::core::cmp::PartialEq }, method))
     }
 
     fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
-        let mut toks = Toks::new();
-        let mut require_sep = false;
-        args.for_fields(&item.fields, |member: Member, _| {
-            if require_sep {
-                <Token![&&]>::default().to_tokens(&mut toks);
+        let root = &args.root;
+        if let Some(rhs) = type_argument(args)? {
+            // Compare with a foreign type through the `using` field
+            let member = args.using_member().ok_or(Error::RequireUsing)?;
+            if !args.by.is_empty() {
+                return Err(Error::CallSite(
+                    "`by` is not supported together with a comparison type",
+                ));
             }
-            toks.append_all(quote! { self.#member == other.#member });
-            require_sep = true;
-        });
-        if toks.is_empty() {
-            toks = quote! { true };
+            let method = quote! {
+                #[inline]
+                fn eq(&self, other: &#rhs) -> bool {
+                    #root::core::cmp::PartialEq::<#rhs>::eq(&self.#member, other)
+                }
+            };
+            return Ok((quote! { #root::core::cmp::PartialEq<#rhs> }, method));
         }
 
+        let toks = eq_chain(operands(item, args));
+
         let method = quote! {
             #[inline]
             fn eq(&self, other: &Self) -> bool {
                 #toks
             }
         };
-        Ok((quote! { ::core::cmp::PartialEq }, method))
+        Ok((quote! { #root::core::cmp::PartialEq }, method))
     }
 }
 
@@ -374,16 +1072,139 @@ impl ImplTrait for ImplEq {
         SimplePath::new(&["", "core", "cmp", "Eq"])
     }
 
-    fn allow_ignore_with(&self) -> Option<SimplePath> {
-        Some(SimplePath::new(&["", "core", "cmp", "PartialEq"]))
+    fn support_field_bounds(&self) -> bool {
+        true
+    }
+
+    fn support_field_cfgs(&self) -> bool {
+        true
+    }
+
+    fn support_by(&self) -> bool {
+        true
+    }
+
+    // Ignored fields are as for PartialEq: this affects bounds and float checks
+    fn support_ignore(&self) -> bool {
+        true
+    }
+
+    fn support_enum_ignore(&self) -> bool {
+        true
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn enum_items(&self, _: &ItemEnum, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        Ok((quote! { #root::core::cmp::Eq }, quote! {}))
+    }
+
+    fn union_items(&self, _: &ItemUnion, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        Ok((quote! { #root::core::cmp::Eq }, quote! {}))
     }
 
-    fn enum_items(&self, _: &ItemEnum, _: &ImplArgs) -> Result<(Toks, Toks)> {
-        Ok((quote! { ::core::cmp::Eq }, quote! {}))
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        if let Some(ty) = find_float_field(item, args) {
+            return Err(Error::WithSpan(
+                ty.span(),
+                "floating-point types do not support `Eq`; consider `ignore self.FIELD`, comparing a key via `using self.FIELD` or comparing bits via `by self.FIELD.to_bits()` (equality as for `total_cmp`)",
+            ));
+        }
+        Ok((quote! { #root::core::cmp::Eq }, quote! {}))
+    }
+}
+
+/// Generate the body of `cmp` (or `partial_cmp` if `partial`) for an enum
+///
+/// Variants compare by declaration order, then by (non-ignored) fields.
+fn enum_cmp(item: &ItemEnum, args: &ImplArgs, partial: bool) -> Toks {
+    let root = &args.root;
+    if item.variants.is_empty() {
+        return quote! { match *self {} };
+    }
+
+    let (trait_path, equal) = if partial {
+        (
+            quote! { #root::core::cmp::PartialOrd::partial_cmp },
+            quote! { #root::core::option::Option::Some(#root::core::cmp::Ordering::Equal) },
+        )
+    } else {
+        (
+            quote! { #root::core::cmp::Ord::cmp },
+            quote! { #root::core::cmp::Ordering::Equal },
+        )
+    };
+
+    let mut idfmt = IdentFormatter::new();
+    let name = &item.ident;
+    let mut indices = Toks::new();
+    let mut variants = Toks::new();
+    for (index, v) in item.variants.iter().enumerate() {
+        let ident = &v.ident;
+        let tag = quote! { #name :: #ident };
+        let mut l_args = Toks::new();
+        let mut r_args = Toks::new();
+        let mut operands = vec![];
+        for (i, (member, field)) in field_members(&v.fields).enumerate() {
+            let prefix = match member {
+                Member::Named(ref ident) => quote! { #ident: },
+                Member::Unnamed(_) => quote! {},
+            };
+            let cfgs = field_cfgs(field);
+            if args.ignore(&member) {
+                l_args.append_all(quote! { #(#cfgs)* #prefix _, });
+                r_args.append_all(quote! { #(#cfgs)* #prefix _, });
+                continue;
+            }
+            let li = idfmt.make_call_site(format_args!("__l{i}"));
+            let ri = idfmt.make_call_site(format_args!("__r{i}"));
+            l_args.append_all(quote! { #(#cfgs)* #prefix #li, });
+            r_args.append_all(quote! { #(#cfgs)* #prefix #ri, });
+            operands.push((cfgs, quote! { #li }, quote! { #ri }));
+        }
+
+        let toks = cmp_chain(operands, &trait_path, &equal);
+
+        let (pat, l_pat, r_pat) = match v.fields {
+            Fields::Named(_) => (
+                quote! { #tag { .. } },
+                quote! { #tag { #l_args } },
+                quote! { #tag { #r_args } },
+            ),
+            Fields::Unnamed(_) => (
+                quote! { #tag(..) },
+                quote! { #tag(#l_args) },
+                quote! { #tag(#r_args) },
+            ),
+            Fields::Unit => (tag.clone(), tag.clone(), tag),
+        };
+        indices.append_all(quote! { #pat => #index, });
+        variants.append_all(quote! { (#l_pat, #r_pat) => #toks, });
     }
 
-    fn struct_items(&self, _: &ItemStruct, _: &ImplArgs) -> Result<(Toks, Toks)> {
-        Ok((quote! { ::core::cmp::Eq }, quote! {}))
+    if item.variants.len() > 1 {
+        quote! {
+            let index = |value: &Self| -> usize {
+                match *value {
+                    #indices
+                }
+            };
+            match (self, other) {
+                #variants
+                _ => #trait_path(&index(self), &index(other)),
+            }
+        }
+    } else {
+        quote! {
+            match (self, other) {
+                #variants
+            }
+        }
     }
 }
 
@@ -396,37 +1217,61 @@ impl ImplTrait for ImplPartialOrd {
         SimplePath::new(&["", "core", "cmp", "PartialOrd"])
     }
 
+    fn support_field_bounds(&self) -> bool {
+        true
+    }
+
+    fn support_field_cfgs(&self) -> bool {
+        true
+    }
+
+    fn support_by(&self) -> bool {
+        true
+    }
+
     fn support_ignore(&self) -> bool {
         true
     }
 
-    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
-        let mut toks = Toks::new();
-        args.for_fields_iter(item.fields.iter().enumerate().rev(), |member: Member, _| {
-            let cmp =
-                quote! { ::core::cmp::PartialOrd::partial_cmp(&self.#member, &other.#member) };
-            if toks.is_empty() {
-                toks = cmp;
-            } else {
-                toks = quote! {
-                    match #cmp {
-                        ::core::option::Option::Some(::core::cmp::Ordering::Equal) => #toks,
-                        cmp => cmp,
-                    }
-                }
+    fn support_enum_ignore(&self) -> bool {
+        true
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn enum_items(&self, item: &ItemEnum, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let toks = enum_cmp(item, args, true);
+        let method = quote! {
+            #[inline]
+            fn partial_cmp(&self, other: &Self) -> #root::core::option::Option<#root::core::cmp::Ordering> {
+                #toks
             }
-        });
-        if toks.is_empty() {
-            toks = quote! { ::core::option::Option::Some(::core::cmp::Ordering::Equal) };
-        }
+        };
+        Ok((quote! { #root::core::cmp::PartialOrd }, method))
+    }
+
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let operands = operands(item, args)
+            .into_iter()
+            .map(|(cfgs, lhs, rhs)| (cfgs, quote! { &#lhs }, quote! { &#rhs }))
+            .collect();
+        let toks = cmp_chain(
+            operands,
+            &quote! { #root::core::cmp::PartialOrd::partial_cmp },
+            &quote! { #root::core::option::Option::Some(#root::core::cmp::Ordering::Equal) },
+        );
 
         let method = quote! {
             #[inline]
-            fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+            fn partial_cmp(&self, other: &Self) -> #root::core::option::Option<#root::core::cmp::Ordering> {
                 #toks
             }
         };
-        Ok((quote! { ::core::cmp::PartialOrd }, method))
+        Ok((quote! { #root::core::cmp::PartialOrd }, method))
     }
 }
 
@@ -437,36 +1282,61 @@ impl ImplTrait for ImplOrd {
         SimplePath::new(&["", "core", "cmp", "Ord"])
     }
 
+    fn support_field_bounds(&self) -> bool {
+        true
+    }
+
+    fn support_field_cfgs(&self) -> bool {
+        true
+    }
+
+    fn support_by(&self) -> bool {
+        true
+    }
+
     fn support_ignore(&self) -> bool {
         true
     }
 
-    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
-        let mut toks = Toks::new();
-        args.for_fields_iter(item.fields.iter().enumerate().rev(), |member: Member, _| {
-            let cmp = quote! { ::core::cmp::Ord::cmp(&self.#member, &other.#member) };
-            if toks.is_empty() {
-                toks = cmp;
-            } else {
-                toks = quote! {
-                    match #cmp {
-                        ::core::cmp::Ordering::Equal => #toks,
-                        cmp => cmp,
-                    }
-                }
+    fn support_enum_ignore(&self) -> bool {
+        true
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn enum_items(&self, item: &ItemEnum, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let toks = enum_cmp(item, args, false);
+        let method = quote! {
+            #[inline]
+            fn cmp(&self, other: &Self) -> #root::core::cmp::Ordering {
+                #toks
             }
-        });
-        if toks.is_empty() {
-            toks = quote! { ::core::cmp::Ordering::Equal };
-        }
+        };
+        Ok((quote! { #root::core::cmp::Ord }, method))
+    }
+
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let operands = operands(item, args)
+            .into_iter()
+            .map(|(cfgs, lhs, rhs)| (cfgs, quote! { &#lhs }, quote! { &#rhs }))
+            .collect();
+        let toks = cmp_chain(
+            operands,
+            &quote! { #root::core::cmp::Ord::cmp },
+            &quote! { #root::core::cmp::Ordering::Equal },
+        );
 
         let method = quote! {
             #[inline]
-            fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+            fn cmp(&self, other: &Self) -> #root::core::cmp::Ordering {
                 #toks
             }
         };
-        Ok((quote! { ::core::cmp::Ord }, method))
+        Ok((quote! { #root::core::cmp::Ord }, method))
     }
 }
 
@@ -477,11 +1347,32 @@ impl ImplTrait for ImplHash {
         SimplePath::new(&["", "core", "hash", "Hash"])
     }
 
+    fn support_field_bounds(&self) -> bool {
+        true
+    }
+
+    fn support_field_cfgs(&self) -> bool {
+        true
+    }
+
+    fn support_by(&self) -> bool {
+        true
+    }
+
     fn support_ignore(&self) -> bool {
         true
     }
 
-    fn enum_items(&self, item: &ItemEnum, _: &ImplArgs) -> Result<(Toks, Toks)> {
+    fn support_enum_ignore(&self) -> bool {
+        true
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn enum_items(&self, item: &ItemEnum, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
         let mut idfmt = IdentFormatter::new();
         let name = &item.ident;
         let mut variants = Toks::new();
@@ -490,22 +1381,36 @@ impl ImplTrait for ImplHash {
             variants.append_all(quote! { #name :: #ident });
             variants.append_all(match v.fields {
                 Fields::Named(ref fields) => {
-                    let idents = fields.named.iter().map(|f| f.ident.as_ref().unwrap());
-                    let hashes = fields.named.iter().map(|f| {
-                        let ident = f.ident.as_ref().unwrap();
-                        quote! { ::core::hash::Hash::hash(&#ident, state); }
-                    });
-                    quote! { { #(#idents),* } => { #(#hashes);* } }
+                    let mut bindings = Vec::with_capacity(fields.named.len());
+                    let mut hashes = quote! {};
+                    for field in fields.named.iter() {
+                        let ident = field.ident.as_ref().unwrap();
+                        let cfgs = field_cfgs(field);
+                        if args.ignore_named(ident) {
+                            bindings.push(quote! { #(#cfgs)* #ident: _ });
+                            continue;
+                        }
+                        bindings.push(quote! { #(#cfgs)* ref #ident });
+                        hashes.append_all(quote! {
+                            #(#cfgs)*
+                            #root::core::hash::Hash::hash(&#ident, state);
+                        });
+                    }
+                    quote! { { #(#bindings,)* } => { #hashes } }
                 }
                 Fields::Unnamed(ref fields) => {
                     let len = fields.unnamed.len();
                     let mut bindings = Vec::with_capacity(len);
                     let mut hashes = quote! {};
                     for i in 0..len {
+                        if args.ignore_unnamed(&Index::from(i)) {
+                            bindings.push(quote! { _ });
+                            continue;
+                        }
                         let ident = idfmt.make_call_site(format_args!("_{i}"));
                         bindings.push(quote! { ref #ident });
                         hashes.append_all(quote! {
-                            ::core::hash::Hash::hash(&#ident, state);
+                            #root::core::hash::Hash::hash(&#ident, state);
                         });
                     }
                     quote! { ( #(#bindings),* ) => { #hashes } }
@@ -514,27 +1419,49 @@ impl ImplTrait for ImplHash {
             });
         }
         let method = quote! {
-            fn hash<__H: ::core::hash::Hasher>(&self, state: &mut __H) {
+            fn hash<__H: #root::core::hash::Hasher>(&self, state: &mut __H) {
+                #root::core::hash::Hash::hash(&#root::core::mem::discriminant(self), state);
                 match *self {
                     #variants
                 }
             }
         };
-        Ok((quote! { ::core::hash::Hash }, method))
+        Ok((quote! { #root::core::hash::Hash }, method))
     }
 
     fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        if let Some(ty) = find_float_field(item, args) {
+            return Err(Error::WithSpan(
+                ty.span(),
+                "floating-point types do not support `Hash`; consider `ignore self.FIELD`, hashing a key via `using self.FIELD` or hashing bits via `by self.FIELD.to_bits()` (equality as for `total_cmp`)",
+            ));
+        }
+
         let mut toks = Toks::new();
-        args.for_fields_iter(item.fields.iter().enumerate().rev(), |member: Member, _| {
-            toks.append_all(quote! { ::core::hash::Hash::hash(&self.#member, state); });
-        });
+        if let Some(member) = args.using_member() {
+            // Hash only the designated field
+            toks.append_all(quote! { #root::core::hash::Hash::hash(&self.#member, state); });
+        } else if !args.by.is_empty() {
+            for (_, key, _) in operands(item, args) {
+                toks.append_all(quote! { #root::core::hash::Hash::hash(&#key, state); });
+            }
+        } else {
+            args.for_fields_iter(item.fields.iter().enumerate().rev(), |member, field| {
+                let cfgs = field_cfgs(field);
+                toks.append_all(quote! {
+                    #(#cfgs)*
+                    #root::core::hash::Hash::hash(&self.#member, state);
+                });
+            });
+        }
 
         let method = quote! {
             #[inline]
-            fn hash<__H: ::core::hash::Hasher>(&self, state: &mut __H) {
+            fn hash<__H: #root::core::hash::Hasher>(&self, state: &mut __H) {
                 #toks
             }
         };
-        Ok((quote! { ::core::hash::Hash }, method))
+        Ok((quote! { #root::core::hash::Hash }, method))
     }
 }
@@ -0,0 +1,190 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Field-wise operator impls
+
+use super::{field_members, ImplArgs, ImplTrait, Result};
+use crate::SimplePath;
+use proc_macro2::{Ident, Span, TokenStream as Toks};
+use quote::quote;
+use syn::ItemStruct;
+
+/// Generate items for a binary operator `trait_path::method`
+///
+/// Each field is combined with the corresponding field of `rhs`; ignored
+/// fields take the value of `self`.
+fn bin_op_items(
+    item: &ItemStruct,
+    args: &ImplArgs,
+    trait_path: Toks,
+    method: &str,
+) -> (Toks, Toks) {
+    let method = Ident::new(method, Span::call_site());
+    let ident = &item.ident;
+    let mut uses_rhs = false;
+    let inits = field_members(&item.fields).map(|(member, _)| {
+        if args.ignore(&member) {
+            quote! { #member: self.#member }
+        } else {
+            uses_rhs = true;
+            quote! { #member: #trait_path::#method(self.#member, rhs.#member) }
+        }
+    });
+    let inits: Vec<_> = inits.collect();
+    let rhs = if uses_rhs {
+        quote! { rhs }
+    } else {
+        quote! { _ }
+    };
+    let items = quote! {
+        type Output = Self;
+        #[inline]
+        fn #method(self, #rhs: Self) -> Self {
+            #ident { #(#inits),* }
+        }
+    };
+    (trait_path, items)
+}
+
+/// Generate items for a compound-assignment operator `trait_path::method`
+///
+/// Each field is updated using the corresponding field of `rhs`; ignored
+/// fields are unchanged.
+fn assign_op_items(
+    item: &ItemStruct,
+    args: &ImplArgs,
+    trait_path: Toks,
+    method: &str,
+) -> (Toks, Toks) {
+    let method = Ident::new(method, Span::call_site());
+    let stmts: Vec<_> = field_members(&item.fields)
+        .filter_map(|(member, _)| {
+            (!args.ignore(&member))
+                .then(|| quote! { #trait_path::#method(&mut self.#member, rhs.#member); })
+        })
+        .collect();
+    let rhs = if stmts.is_empty() {
+        quote! { _ }
+    } else {
+        quote! { rhs }
+    };
+    let items = quote! {
+        #[inline]
+        fn #method(&mut self, #rhs: Self) {
+            #(#stmts)*
+        }
+    };
+    (trait_path, items)
+}
+
+macro_rules! impl_op {
+    ($(#[$doc:meta])* $name:ident, $trait:ident, $method:ident) => {
+        impl_op!($(#[$doc])* $name, $trait, $method, bin_op_items);
+    };
+    ($(#[$doc:meta])* $name:ident, $trait:ident, $method:ident, $items:ident) => {
+        $(#[$doc])*
+        pub struct $name;
+        impl ImplTrait for $name {
+            fn path(&self) -> SimplePath {
+                SimplePath::new(&["", "core", "ops", stringify!($trait)])
+            }
+
+            fn support_ignore(&self) -> bool {
+                true
+            }
+
+            fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+                let root = &args.root;
+                Ok($items(
+                    item,
+                    args,
+                    quote! { #root::core::ops::$trait },
+                    stringify!($method),
+                ))
+            }
+        }
+    };
+}
+
+impl_op!(
+    /// Implement [`core::ops::Add`] field-wise
+    ImplAdd, Add, add
+);
+impl_op!(
+    /// Implement [`core::ops::Sub`] field-wise
+    ImplSub, Sub, sub
+);
+impl_op!(
+    /// Implement [`core::ops::Mul`] field-wise
+    ImplMul, Mul, mul
+);
+impl_op!(
+    /// Implement [`core::ops::Div`] field-wise
+    ImplDiv, Div, div
+);
+impl_op!(
+    /// Implement [`core::ops::Rem`] field-wise
+    ImplRem, Rem, rem
+);
+impl_op!(
+    /// Implement [`core::ops::BitAnd`] field-wise
+    ImplBitAnd, BitAnd, bitand
+);
+impl_op!(
+    /// Implement [`core::ops::BitOr`] field-wise
+    ImplBitOr, BitOr, bitor
+);
+impl_op!(
+    /// Implement [`core::ops::BitXor`] field-wise
+    ImplBitXor, BitXor, bitxor
+);
+impl_op!(
+    /// Implement [`core::ops::Shl`] field-wise
+    ImplShl, Shl, shl
+);
+impl_op!(
+    /// Implement [`core::ops::Shr`] field-wise
+    ImplShr, Shr, shr
+);
+impl_op!(
+    /// Implement [`core::ops::AddAssign`] field-wise
+    ImplAddAssign, AddAssign, add_assign, assign_op_items
+);
+impl_op!(
+    /// Implement [`core::ops::SubAssign`] field-wise
+    ImplSubAssign, SubAssign, sub_assign, assign_op_items
+);
+impl_op!(
+    /// Implement [`core::ops::MulAssign`] field-wise
+    ImplMulAssign, MulAssign, mul_assign, assign_op_items
+);
+impl_op!(
+    /// Implement [`core::ops::DivAssign`] field-wise
+    ImplDivAssign, DivAssign, div_assign, assign_op_items
+);
+impl_op!(
+    /// Implement [`core::ops::RemAssign`] field-wise
+    ImplRemAssign, RemAssign, rem_assign, assign_op_items
+);
+impl_op!(
+    /// Implement [`core::ops::BitAndAssign`] field-wise
+    ImplBitAndAssign, BitAndAssign, bitand_assign, assign_op_items
+);
+impl_op!(
+    /// Implement [`core::ops::BitOrAssign`] field-wise
+    ImplBitOrAssign, BitOrAssign, bitor_assign, assign_op_items
+);
+impl_op!(
+    /// Implement [`core::ops::BitXorAssign`] field-wise
+    ImplBitXorAssign, BitXorAssign, bitxor_assign, assign_op_items
+);
+impl_op!(
+    /// Implement [`core::ops::ShlAssign`] field-wise
+    ImplShlAssign, ShlAssign, shl_assign, assign_op_items
+);
+impl_op!(
+    /// Implement [`core::ops::ShrAssign`] field-wise
+    ImplShrAssign, ShrAssign, shr_assign, assign_op_items
+);
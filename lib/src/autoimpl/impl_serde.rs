@@ -0,0 +1,367 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Impls for `serde` traits
+//!
+//! Generated code refers to `::serde`; the user's crate must depend on it.
+
+use super::{field_members, field_name, ImplArgs, ImplTrait, Result};
+use crate::generics::ImplGenericsBuilder;
+use crate::SimplePath;
+use proc_macro2::{Span, TokenStream as Toks};
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Fields, GenericParam, Index, ItemStruct, Lifetime, LifetimeParam, Member};
+
+/// Implement `serde::Serialize`
+///
+/// Ignored fields are skipped. With `using self.FIELD`, only that field is
+/// serialized (as a transparent newtype).
+pub struct ImplSerialize;
+impl ImplTrait for ImplSerialize {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "serde", "Serialize"])
+    }
+
+    fn support_ignore(&self) -> bool {
+        true
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let name = item.ident.unraw().to_string();
+        let body = if let Some(member) = args.using_member() {
+            quote! { #root::serde::Serialize::serialize(&self.#member, serializer) }
+        } else {
+            match &item.fields {
+                Fields::Named(fields) => {
+                    let len = fields
+                        .named
+                        .iter()
+                        .filter(|field| !args.ignore_named(field.ident.as_ref().unwrap()))
+                        .count();
+                    let stmts = fields.named.iter().map(|field| {
+                        let ident = field.ident.as_ref().unwrap();
+                        let key = field_name(field).unwrap();
+                        if args.ignore_named(ident) {
+                            quote! { #root::serde::ser::SerializeStruct::skip_field(&mut state, #key)?; }
+                        } else {
+                            quote! {
+                                #root::serde::ser::SerializeStruct::serialize_field(&mut state, #key, &self.#ident)?;
+                            }
+                        }
+                    });
+                    quote! {
+                        let mut state = #root::serde::Serializer::serialize_struct(serializer, #name, #len)?;
+                        #(#stmts)*
+                        #root::serde::ser::SerializeStruct::end(state)
+                    }
+                }
+                Fields::Unnamed(fields) => {
+                    let members: Vec<_> = (0..fields.unnamed.len())
+                        .map(Index::from)
+                        .filter(|index| !args.ignore_unnamed(index))
+                        .map(Member::Unnamed)
+                        .collect();
+                    let len = members.len();
+                    quote! {
+                        let mut state = #root::serde::Serializer::serialize_tuple_struct(serializer, #name, #len)?;
+                        #(#root::serde::ser::SerializeTupleStruct::serialize_field(&mut state, &self.#members)?;)*
+                        #root::serde::ser::SerializeTupleStruct::end(state)
+                    }
+                }
+                Fields::Unit => quote! {
+                    #root::serde::Serializer::serialize_unit_struct(serializer, #name)
+                },
+            }
+        };
+
+        let method = quote! {
+            fn serialize<__S: #root::serde::Serializer>(
+                &self,
+                serializer: __S,
+            ) -> #root::core::result::Result<__S::Ok, __S::Error> {
+                #body
+            }
+        };
+        Ok((quote! { #root::serde::Serialize }, method))
+    }
+}
+
+/// Implement `serde::Deserialize`
+///
+/// Ignored fields are not deserialized but initialized with
+/// `Default::default()`. With `using self.FIELD`, only that field is
+/// deserialized (as a transparent newtype); other fields use
+/// `Default::default()`.
+pub struct ImplDeserialize;
+impl ImplTrait for ImplDeserialize {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "serde", "Deserialize"])
+    }
+
+    fn support_ignore(&self) -> bool {
+        true
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn struct_impl(&self, item: &ItemStruct, args: &ImplArgs) -> Result<Toks> {
+        let (path, items) = self.struct_items(item, args)?;
+        let de = Lifetime::new("'de", Span::call_site());
+        let mut generics = item.generics.clone();
+        generics
+            .params
+            .insert(0, GenericParam::Lifetime(LifetimeParam::new(de)));
+        let (impl_generics, _, where_clause) = ImplGenericsBuilder::new(&generics)
+            .clause(args.clause.as_ref())
+            .split_for_impl(&path);
+        let (_, ty_generics, _) = item.generics.split_for_impl();
+        let ident = &item.ident;
+        Ok(quote! {
+            #[automatically_derived]
+            impl #impl_generics #path for #ident #ty_generics #where_clause {
+                #items
+            }
+        })
+    }
+
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let ident = &item.ident;
+        let name = ident.unraw().to_string();
+        let path = quote! { #root::serde::Deserialize<'de> };
+
+        // (field, binding) of each deserialized field; others use Default
+        let mut fields = vec![];
+        let mut inits = vec![];
+        for (i, (member, field)) in field_members(&item.fields).enumerate() {
+            let is_used = match args.using_member() {
+                Some(using) => *using == member,
+                None => !args.ignore(&member),
+            };
+            if is_used {
+                let binding = format_ident!("__field{}", i);
+                inits.push(quote! { #member: #binding });
+                fields.push((field, binding));
+            } else {
+                inits.push(quote! { #member: #root::core::default::Default::default() });
+            }
+        }
+        let construct = quote! { #ident { #(#inits),* } };
+
+        if args.using_member().is_some() {
+            let binding = &fields[0].1;
+            let method = quote! {
+                fn deserialize<__D: #root::serde::Deserializer<'de>>(
+                    deserializer: __D,
+                ) -> #root::core::result::Result<Self, __D::Error> {
+                    let #binding = #root::serde::Deserialize::deserialize(deserializer)?;
+                    #root::core::result::Result::Ok(#construct)
+                }
+            };
+            return Ok((path, method));
+        }
+
+        let len = fields.len();
+        let bindings: Vec<_> = fields.iter().map(|(_, binding)| binding).collect();
+        let visit_seq = {
+            let indices = 0..len;
+            let expecting = format!("struct {} with {} elements", name, len);
+            quote! {
+                fn visit_seq<__A: #root::serde::de::SeqAccess<'de>>(
+                    self,
+                    mut seq: __A,
+                ) -> #root::core::result::Result<Self::Value, __A::Error> {
+                    #(
+                        let #bindings = match #root::serde::de::SeqAccess::next_element(&mut seq)? {
+                            #root::core::option::Option::Some(value) => value,
+                            #root::core::option::Option::None => {
+                                return #root::core::result::Result::Err(
+                                    #root::serde::de::Error::invalid_length(#indices, &#expecting),
+                                );
+                            }
+                        };
+                    )*
+                    #root::core::result::Result::Ok(#construct)
+                }
+            }
+        };
+
+        let (field_ident, visit_map, deserialize) = match &item.fields {
+            Fields::Named(_) => {
+                let keys: Vec<_> = fields
+                    .iter()
+                    .map(|(field, _)| field_name(field).unwrap())
+                    .collect();
+                let byte_keys = keys
+                    .iter()
+                    .map(|key| syn::LitByteStr::new(key.as_bytes(), Span::call_site()));
+                let variants: Vec<_> = (0..len).map(|i| format_ident!("__F{}", i)).collect();
+                let indices = (0..len).map(|i| i as u64);
+                let field_ident = quote! {
+                    enum __Field {
+                        #(#variants,)*
+                        __Ignore,
+                    }
+                    impl<'de> #root::serde::Deserialize<'de> for __Field {
+                        fn deserialize<__D: #root::serde::Deserializer<'de>>(
+                            deserializer: __D,
+                        ) -> #root::core::result::Result<Self, __D::Error> {
+                            struct __FieldVisitor;
+                            impl<'de> #root::serde::de::Visitor<'de> for __FieldVisitor {
+                                type Value = __Field;
+                                fn expecting(
+                                    &self,
+                                    f: &mut #root::core::fmt::Formatter,
+                                ) -> #root::core::fmt::Result {
+                                    f.write_str("field identifier")
+                                }
+                                fn visit_u64<__E: #root::serde::de::Error>(
+                                    self,
+                                    value: u64,
+                                ) -> #root::core::result::Result<__Field, __E> {
+                                    #root::core::result::Result::Ok(match value {
+                                        #(#indices => __Field::#variants,)*
+                                        _ => __Field::__Ignore,
+                                    })
+                                }
+                                fn visit_str<__E: #root::serde::de::Error>(
+                                    self,
+                                    value: &str,
+                                ) -> #root::core::result::Result<__Field, __E> {
+                                    #root::core::result::Result::Ok(match value {
+                                        #(#keys => __Field::#variants,)*
+                                        _ => __Field::__Ignore,
+                                    })
+                                }
+                                fn visit_bytes<__E: #root::serde::de::Error>(
+                                    self,
+                                    value: &[u8],
+                                ) -> #root::core::result::Result<__Field, __E> {
+                                    #root::core::result::Result::Ok(match value {
+                                        #(#byte_keys => __Field::#variants,)*
+                                        _ => __Field::__Ignore,
+                                    })
+                                }
+                            }
+                            #root::serde::Deserializer::deserialize_identifier(deserializer, __FieldVisitor)
+                        }
+                    }
+                };
+                let visit_map = quote! {
+                    fn visit_map<__A: #root::serde::de::MapAccess<'de>>(
+                        self,
+                        mut map: __A,
+                    ) -> #root::core::result::Result<Self::Value, __A::Error> {
+                        #(let mut #bindings = #root::core::option::Option::None;)*
+                        while let #root::core::option::Option::Some(key) =
+                            #root::serde::de::MapAccess::next_key::<__Field>(&mut map)?
+                        {
+                            match key {
+                                #(__Field::#variants => {
+                                    if #bindings.is_some() {
+                                        return #root::core::result::Result::Err(
+                                            #root::serde::de::Error::duplicate_field(#keys),
+                                        );
+                                    }
+                                    #bindings = #root::core::option::Option::Some(
+                                        #root::serde::de::MapAccess::next_value(&mut map)?,
+                                    );
+                                })*
+                                __Field::__Ignore => {
+                                    #root::serde::de::MapAccess::next_value::<#root::serde::de::IgnoredAny>(
+                                        &mut map,
+                                    )?;
+                                }
+                            }
+                        }
+                        #(
+                            let #bindings = match #bindings {
+                                #root::core::option::Option::Some(value) => value,
+                                #root::core::option::Option::None => {
+                                    return #root::core::result::Result::Err(
+                                        #root::serde::de::Error::missing_field(#keys),
+                                    );
+                                }
+                            };
+                        )*
+                        #root::core::result::Result::Ok(#construct)
+                    }
+                };
+                let deserialize = quote! {
+                    const FIELDS: &[&str] = &[#(#keys),*];
+                    #root::serde::Deserializer::deserialize_struct(deserializer, #name, FIELDS, visitor)
+                };
+                (field_ident, visit_map, deserialize)
+            }
+            Fields::Unnamed(_) => (
+                Toks::new(),
+                Toks::new(),
+                quote! {
+                    #root::serde::Deserializer::deserialize_tuple_struct(deserializer, #name, #len, visitor)
+                },
+            ),
+            Fields::Unit => (
+                Toks::new(),
+                quote! {
+                    fn visit_unit<__E: #root::serde::de::Error>(
+                        self,
+                    ) -> #root::core::result::Result<Self::Value, __E> {
+                        #root::core::result::Result::Ok(#construct)
+                    }
+                },
+                quote! {
+                    #root::serde::Deserializer::deserialize_unit_struct(deserializer, #name, visitor)
+                },
+            ),
+        };
+
+        // The visitor re-declares the item's generics
+        let generics = &item.generics;
+        let (_, ty_generics, item_wc) = generics.split_for_impl();
+        let mut de_generics = generics.clone();
+        de_generics.params.insert(
+            0,
+            GenericParam::Lifetime(LifetimeParam::new(Lifetime::new("'de", Span::call_site()))),
+        );
+        let (impl_generics, _, where_clause) = ImplGenericsBuilder::new(&de_generics)
+            .clause(args.clause.as_ref())
+            .split_for_impl(&path);
+        let expecting = format!("struct {}", name);
+        let method = quote! {
+            fn deserialize<__D: #root::serde::Deserializer<'de>>(
+                deserializer: __D,
+            ) -> #root::core::result::Result<Self, __D::Error> {
+                #field_ident
+
+                struct __Visitor #generics (
+                    #root::core::marker::PhantomData<fn() -> #ident #ty_generics>,
+                ) #item_wc;
+
+                impl #impl_generics #root::serde::de::Visitor<'de> for __Visitor #ty_generics #where_clause {
+                    type Value = #ident #ty_generics;
+
+                    fn expecting(&self, f: &mut #root::core::fmt::Formatter) -> #root::core::fmt::Result {
+                        f.write_str(#expecting)
+                    }
+
+                    #visit_seq
+                    #visit_map
+                }
+
+                let visitor = __Visitor(#root::core::marker::PhantomData);
+                #deserialize
+            }
+        };
+        Ok((path, method))
+    }
+}
@@ -5,11 +5,25 @@
 
 //! Impls "using" a field
 
-use super::{Error, ImplArgs, ImplTrait, Result};
-use crate::SimplePath;
-use proc_macro2::TokenStream as Toks;
-use quote::quote;
-use syn::{ItemStruct, PathArguments};
+use super::{field_members, type_argument, Error, ImplArgs, ImplTrait, Result};
+use crate::generics::ImplGenericsBuilder;
+use crate::{is_non_exhaustive, SimplePath};
+use proc_macro2::{Ident, Span, TokenStream as Toks};
+use quote::{quote, TokenStreamExt};
+use syn::spanned::Spanned;
+use syn::visit_mut::{self, VisitMut};
+use syn::{
+    Fields, GenericArgument, GenericParam, ItemEnum, ItemStruct, Lifetime, LifetimeParam, Member,
+    PathArguments, Type,
+};
+
+/// Get the target type `T` from path arguments `<T>`, defaulting to `field_ty`
+///
+/// The generated method returns `&self.FIELD` (or `&mut self.FIELD`), thus
+/// an explicit target type must be reachable by deref coercion.
+fn ref_target(args: &ImplArgs, field_ty: &Type) -> Result<Type> {
+    Ok(type_argument(args)?.unwrap_or_else(|| field_ty.clone()))
+}
 
 /// Implement [`core::borrow::Borrow`]
 pub struct ImplBorrow;
@@ -18,20 +32,29 @@ impl ImplTrait for ImplBorrow {
         SimplePath::new(&["", "core", "borrow", "Borrow"])
     }
 
+    fn support_path_arguments(&self) -> bool {
+        true
+    }
+
     fn support_using(&self) -> bool {
         true
     }
 
+    fn require_using(&self) -> bool {
+        true
+    }
+
     fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
         if let Some(field) = args.using_field(&item.fields) {
-            let ty = field.ty.clone();
+            let ty = ref_target(args, &field.ty)?;
             let member = args.using_member().unwrap();
             let method = quote! {
                 fn borrow(&self) -> & #ty {
                     &self.#member
                 }
             };
-            Ok((quote! { ::core::borrow::Borrow<#ty> }, method))
+            Ok((quote! { #root::core::borrow::Borrow<#ty> }, method))
         } else {
             Err(Error::RequireUsing)
         }
@@ -45,20 +68,29 @@ impl ImplTrait for ImplBorrowMut {
         SimplePath::new(&["", "core", "borrow", "BorrowMut"])
     }
 
+    fn support_path_arguments(&self) -> bool {
+        true
+    }
+
     fn support_using(&self) -> bool {
         true
     }
 
+    fn require_using(&self) -> bool {
+        true
+    }
+
     fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
         if let Some(field) = args.using_field(&item.fields) {
-            let ty = field.ty.clone();
+            let ty = ref_target(args, &field.ty)?;
             let member = args.using_member().unwrap();
             let method = quote! {
                 fn borrow_mut(&mut self) -> &mut #ty {
                     &mut self.#member
                 }
             };
-            Ok((quote! { ::core::borrow::BorrowMut<#ty> }, method))
+            Ok((quote! { #root::core::borrow::BorrowMut<#ty> }, method))
         } else {
             Err(Error::RequireUsing)
         }
@@ -72,20 +104,29 @@ impl ImplTrait for ImplAsRef {
         SimplePath::new(&["", "core", "convert", "AsRef"])
     }
 
+    fn support_path_arguments(&self) -> bool {
+        true
+    }
+
     fn support_using(&self) -> bool {
         true
     }
 
+    fn require_using(&self) -> bool {
+        true
+    }
+
     fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
         if let Some(field) = args.using_field(&item.fields) {
-            let ty = field.ty.clone();
+            let ty = ref_target(args, &field.ty)?;
             let member = args.using_member().unwrap();
             let method = quote! {
                 fn as_ref(&self) -> & #ty {
                     &self.#member
                 }
             };
-            Ok((quote! { ::core::convert::AsRef<#ty> }, method))
+            Ok((quote! { #root::core::convert::AsRef<#ty> }, method))
         } else {
             Err(Error::RequireUsing)
         }
@@ -99,26 +140,1190 @@ impl ImplTrait for ImplAsMut {
         SimplePath::new(&["", "core", "convert", "AsMut"])
     }
 
+    fn support_path_arguments(&self) -> bool {
+        true
+    }
+
     fn support_using(&self) -> bool {
         true
     }
 
+    fn require_using(&self) -> bool {
+        true
+    }
+
     fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
         if let Some(field) = args.using_field(&item.fields) {
-            let ty = field.ty.clone();
+            let ty = ref_target(args, &field.ty)?;
             let member = args.using_member().unwrap();
             let method = quote! {
                 fn as_mut(&mut self) -> &mut #ty {
                     &mut self.#member
                 }
             };
-            Ok((quote! { ::core::convert::AsMut<#ty> }, method))
+            Ok((quote! { #root::core::convert::AsMut<#ty> }, method))
+        } else {
+            Err(Error::RequireUsing)
+        }
+    }
+}
+
+macro_rules! impl_fmt {
+    ($(#[$doc:meta])* $name:ident, $trait:ident $(, { $($items:tt)* })?) => {
+        $(#[$doc])*
+        pub struct $name;
+        impl ImplTrait for $name {
+            fn path(&self) -> SimplePath {
+                SimplePath::new(&["", "core", "fmt", stringify!($trait)])
+            }
+
+            fn support_using(&self) -> bool {
+                true
+            }
+
+            fn require_using(&self) -> bool {
+                true
+            }
+
+            $($($items)*)?
+
+            fn struct_items(&self, _: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+                let root = &args.root;
+                if let Some(member) = args.using_member() {
+                    let method = quote! {
+                        fn fmt(&self, f: &mut #root::core::fmt::Formatter) -> #root::core::fmt::Result {
+                            #root::core::fmt::$trait::fmt(&self.#member, f)
+                        }
+                    };
+                    Ok((quote! { #root::core::fmt::$trait }, method))
+                } else {
+                    Err(Error::RequireUsing)
+                }
+            }
+        }
+    };
+}
+
+impl_fmt!(
+    /// Implement [`core::fmt::Display`]
+    ///
+    /// On enums, each variant is formatted using the format string given by
+    /// `fmt Self::VARIANT(..) => "FORMAT"`.
+    ImplDisplay, Display, {
+        fn support_variant_fmt(&self) -> bool {
+            true
+        }
+
+        fn enum_items(&self, _: &ItemEnum, args: &ImplArgs) -> Result<(Toks, Toks)> {
+            let root = &args.root;
+            if args.variant_fmts.is_empty() {
+                return Err(Error::CallSite(
+                    "enum expansion requires `fmt Self::VARIANT => \"FORMAT\"`",
+                ));
+            }
+            let arms = args.variant_fmts.iter().map(|(pat, fmt)| {
+                quote! { #pat => #root::core::write!(f, #fmt), }
+            });
+            let method = quote! {
+                #[allow(unused_variables)]
+                fn fmt(&self, f: &mut #root::core::fmt::Formatter) -> #root::core::fmt::Result {
+                    match self {
+                        #(#arms)*
+                    }
+                }
+            };
+            Ok((quote! { #root::core::fmt::Display }, method))
+        }
+    }
+);
+impl_fmt!(
+    /// Implement [`core::fmt::LowerHex`]
+    ImplLowerHex, LowerHex
+);
+impl_fmt!(
+    /// Implement [`core::fmt::UpperHex`]
+    ImplUpperHex, UpperHex
+);
+impl_fmt!(
+    /// Implement [`core::fmt::Octal`]
+    ImplOctal, Octal
+);
+impl_fmt!(
+    /// Implement [`core::fmt::Binary`]
+    ImplBinary, Binary
+);
+impl_fmt!(
+    /// Implement [`core::fmt::Pointer`]
+    ImplPointer, Pointer
+);
+
+/// Implement [`core::fmt::Write`]
+///
+/// Forwards `write_str`, `write_char` and `write_fmt` to the field given by
+/// `using`.
+pub struct ImplFmtWrite;
+impl ImplTrait for ImplFmtWrite {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "core", "fmt", "Write"])
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn require_using(&self) -> bool {
+        true
+    }
+
+    fn struct_items(&self, _: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        if let Some(member) = args.using_member() {
+            let method = quote! {
+                #[inline]
+                fn write_str(&mut self, s: &str) -> #root::core::fmt::Result {
+                    #root::core::fmt::Write::write_str(&mut self.#member, s)
+                }
+                #[inline]
+                fn write_char(&mut self, c: char) -> #root::core::fmt::Result {
+                    #root::core::fmt::Write::write_char(&mut self.#member, c)
+                }
+                #[inline]
+                fn write_fmt(&mut self, args: #root::core::fmt::Arguments<'_>) -> #root::core::fmt::Result {
+                    #root::core::fmt::Write::write_fmt(&mut self.#member, args)
+                }
+            };
+            Ok((quote! { #root::core::fmt::Write }, method))
+        } else {
+            Err(Error::RequireUsing)
+        }
+    }
+}
+
+/// Implement [`core::hash::Hasher`]
+///
+/// Forwards `finish`, `write` and each `write_*` method for integer types to
+/// the field given by `using`.
+pub struct ImplHasher;
+impl ImplTrait for ImplHasher {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "core", "hash", "Hasher"])
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn require_using(&self) -> bool {
+        true
+    }
+
+    fn struct_items(&self, _: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let member = args.using_member().ok_or(Error::RequireUsing)?;
+        let mut method = quote! {
+            #[inline]
+            fn finish(&self) -> u64 {
+                #root::core::hash::Hasher::finish(&self.#member)
+            }
+            #[inline]
+            fn write(&mut self, bytes: &[u8]) {
+                #root::core::hash::Hasher::write(&mut self.#member, bytes)
+            }
+        };
+        for int in INTS {
+            let ty = Ident::new(int, Span::call_site());
+            let name = Ident::new(&format!("write_{int}"), Span::call_site());
+            method.append_all(quote! {
+                #[inline]
+                fn #name(&mut self, i: #ty) {
+                    #root::core::hash::Hasher::#name(&mut self.#member, i)
+                }
+            });
+        }
+        Ok((quote! { #root::core::hash::Hasher }, method))
+    }
+}
+
+/// Get the type argument of `ty` if it is (assumed to be) `NAME<T>`
+///
+/// Matches `NAME` alone or `CRATE::MODULE::NAME` for any of `crates`.
+fn std_type_argument<'a>(
+    ty: &'a Type,
+    crates: &[&str],
+    module: &str,
+    name: &str,
+) -> Option<&'a Type> {
+    let path = match ty {
+        Type::Path(path) if path.qself.is_none() => &path.path,
+        _ => return None,
+    };
+    let segments = &path.segments;
+    let matches = match segments.len() {
+        1 => path.leading_colon.is_none() && segments[0].ident == name,
+        3 => {
+            crates.iter().any(|krate| segments[0].ident == krate)
+                && segments[1].ident == module
+                && segments[2].ident == name
+        }
+        _ => false,
+    };
+    if !matches {
+        return None;
+    }
+    match &segments.last().unwrap().arguments {
+        PathArguments::AngleBracketed(args) => match args.args.first() {
+            Some(GenericArgument::Type(ty)) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Implement [`std::error::Error`]
+///
+/// With `using self.FIELD`, `source` returns the field, which must implement
+/// `Error + 'static`, be a `Box` of such a type (including
+/// `Box<dyn Error + Send + Sync>`) or an `Option` of either. The generated
+/// code requires `std`.
+pub struct ImplError;
+impl ImplTrait for ImplError {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "std", "error", "Error"])
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn require_using(&self) -> bool {
+        true
+    }
+
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let field = args.using_field(&item.fields).ok_or(Error::RequireUsing)?;
+        let member = args.using_member().unwrap();
+        let option = std_type_argument(&field.ty, &["core", "std"], "option", "Option");
+        let inner = option.unwrap_or(&field.ty);
+        let is_box = std_type_argument(inner, &["alloc", "std"], "boxed", "Box").is_some();
+        let source = match (option.is_some(), is_box) {
+            (true, false) => quote! { self.#member.as_ref().map(|source| source as _) },
+            (true, true) => quote! { self.#member.as_deref().map(|source| source as _) },
+            (false, false) => quote! { #root::core::option::Option::Some(&self.#member) },
+            (false, true) => quote! { #root::core::option::Option::Some(&*self.#member as _) },
+        };
+        let method = quote! {
+            fn source(&self) -> #root::core::option::Option<&(dyn #root::std::error::Error + 'static)> {
+                #source
+            }
+        };
+        Ok((quote! { #root::std::error::Error }, method))
+    }
+}
+
+/// Implement [`std::io::BufRead`]
+///
+/// Forwards `fill_buf`, `consume`, `read_until` and `read_line` to the field
+/// given by `using`. This requires an implementation of `std::io::Read`. The
+/// generated code requires `std`.
+pub struct ImplBufRead;
+impl ImplTrait for ImplBufRead {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "std", "io", "BufRead"])
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn require_using(&self) -> bool {
+        true
+    }
+
+    fn struct_items(&self, _: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        if let Some(member) = args.using_member() {
+            let method = quote! {
+                #[inline]
+                fn fill_buf(&mut self) -> #root::std::io::Result<&[u8]> {
+                    #root::std::io::BufRead::fill_buf(&mut self.#member)
+                }
+                #[inline]
+                fn consume(&mut self, amt: usize) {
+                    #root::std::io::BufRead::consume(&mut self.#member, amt)
+                }
+                #[inline]
+                fn read_until(
+                    &mut self,
+                    byte: u8,
+                    buf: &mut #root::std::vec::Vec<u8>,
+                ) -> #root::std::io::Result<usize> {
+                    #root::std::io::BufRead::read_until(&mut self.#member, byte, buf)
+                }
+                #[inline]
+                fn read_line(&mut self, buf: &mut #root::std::string::String) -> #root::std::io::Result<usize> {
+                    #root::std::io::BufRead::read_line(&mut self.#member, buf)
+                }
+            };
+            Ok((quote! { #root::std::io::BufRead }, method))
         } else {
             Err(Error::RequireUsing)
         }
     }
 }
 
+/// Implement [`core::future::Future`]
+///
+/// Forwards `poll` to the field given by `using`, which is structurally
+/// pinned. To uphold the pinning guarantees, this also:
+///
+/// -   implements `Unpin` for the struct only where the field's type is
+///     `Unpin` (thus a user-implemented `Unpin` is an error)
+/// -   forbids a user implementation of `Drop` (which could move the field)
+/// -   forbids `#[repr(packed)]` (which could require moving the field)
+pub struct ImplFuture;
+impl ImplTrait for ImplFuture {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "core", "future", "Future"])
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn require_using(&self) -> bool {
+        true
+    }
+
+    fn struct_impl(&self, item: &ItemStruct, args: &ImplArgs) -> Result<Toks> {
+        let root = &args.root;
+        let (path, items) = self.struct_items(item, args)?;
+        let ty = &args.using_field(&item.fields).unwrap().ty;
+        let ident = &item.ident;
+        let builder = ImplGenericsBuilder::new(&item.generics).clause(args.clause.as_ref());
+        let future = builder.impl_block(ident, &path, &items);
+        let (impl_generics, ty_generics, where_clause) = builder.split_for_impl(&path);
+        let (_, _, item_wc) = item.generics.split_for_impl();
+        let unpin_wc = match item_wc {
+            Some(wc) => quote! { #wc, #ty: #root::core::marker::Unpin },
+            None => quote! { where #ty: #root::core::marker::Unpin },
+        };
+        let (item_impl_generics, _, _) = item.generics.split_for_impl();
+        Ok(quote! {
+            #future
+
+            impl #item_impl_generics #root::core::marker::Unpin for #ident #ty_generics #unpin_wc {}
+
+            const _: () = {
+                trait MustNotImplDrop {}
+                #[allow(drop_bounds)]
+                impl<T: #root::core::ops::Drop> MustNotImplDrop for T {}
+                impl #impl_generics MustNotImplDrop for #ident #ty_generics #where_clause {}
+            };
+        })
+    }
+
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let field = args.using_field(&item.fields).ok_or(Error::RequireUsing)?;
+        if is_packed(item) {
+            return Err(Error::CallSite(
+                "`Future` requires structural pinning, which is incompatible with `#[repr(packed)]`",
+            ));
+        }
+
+        let member = args.using_member().unwrap();
+        let ty = &field.ty;
+        let method = quote! {
+            type Output = <#ty as #root::core::future::Future>::Output;
+            fn poll(
+                self: #root::core::pin::Pin<&mut Self>,
+                cx: &mut #root::core::task::Context<'_>,
+            ) -> #root::core::task::Poll<Self::Output> {
+                // SAFETY: the field is structurally pinned: `Self` is `Unpin`
+                // only if the field is, `Drop` is not implemented and `Self`
+                // is not packed.
+                let inner = unsafe { self.map_unchecked_mut(|this| &mut this.#member) };
+                #root::core::future::Future::poll(inner, cx)
+            }
+        };
+        Ok((quote! { #root::core::future::Future }, method))
+    }
+}
+
+/// True if `item` has attribute `#[repr(packed)]` or `#[repr(packed(N))]`
+fn is_packed(item: &ItemStruct) -> bool {
+    let mut packed = false;
+    for attr in &item.attrs {
+        if attr.path().is_ident("repr") {
+            let _ = attr.parse_nested_meta(|meta| {
+                packed |= meta.path.is_ident("packed");
+                if meta.input.peek(syn::token::Paren) {
+                    let _content;
+                    syn::parenthesized!(_content in meta.input);
+                }
+                Ok(())
+            });
+        }
+    }
+    packed
+}
+
+/// Implement [`core::convert::From`]
+///
+/// This implements `From<T>` where `T` is the type of the field given by
+/// `using`, initializing other fields with `Default::default()`.
+pub struct ImplFrom;
+impl ImplTrait for ImplFrom {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "core", "convert", "From"])
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn require_using(&self) -> bool {
+        true
+    }
+
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let field = args.using_field(&item.fields).ok_or(Error::RequireUsing)?;
+        if is_non_exhaustive(&item.attrs) {
+            return Err(Error::CallSite(
+                "`From` would allow construction of a `#[non_exhaustive]` struct",
+            ));
+        }
+
+        let member = args.using_member().unwrap();
+        let inits = field_members(&item.fields).map(|(m, _)| {
+            if m == *member {
+                quote! { #m: value }
+            } else {
+                quote! { #m: #root::core::default::Default::default() }
+            }
+        });
+        let ty = &field.ty;
+        let ident = &item.ident;
+        let method = quote! {
+            fn from(value: #ty) -> Self {
+                #ident { #(#inits),* }
+            }
+        };
+        Ok((quote! { #root::core::convert::From<#ty> }, method))
+    }
+}
+
+/// Implement [`core::convert::TryFrom`]
+///
+/// This implements `TryFrom<S>` (where `S` must be given, e.g.
+/// `TryFrom<&str>`) by converting into the type of the field given by
+/// `using`, initializing other fields with `Default::default()`.
+///
+/// On fieldless enums with `#[repr(INT)]`, the value is converted to `INT`
+/// then compared with each discriminant; other values are the error.
+pub struct ImplTryFrom;
+impl ImplTryFrom {
+    /// Get the source type, with elided lifetimes named `'__src`
+    ///
+    /// Also returns true if any lifetime was named.
+    fn source(args: &ImplArgs) -> Result<(Type, bool)> {
+        struct NameElided(bool);
+        impl VisitMut for NameElided {
+            fn visit_type_reference_mut(&mut self, ty: &mut syn::TypeReference) {
+                if ty.lifetime.is_none() {
+                    ty.lifetime = Some(Lifetime::new("'__src", Span::call_site()));
+                    self.0 = true;
+                }
+                visit_mut::visit_type_reference_mut(self, ty);
+            }
+
+            fn visit_lifetime_mut(&mut self, lt: &mut Lifetime) {
+                if lt.ident == "_" {
+                    *lt = Lifetime::new("'__src", Span::call_site());
+                    self.0 = true;
+                }
+            }
+        }
+
+        let mut ty = type_argument(args)?.ok_or(Error::CallSite(
+            "expected source type, e.g. `TryFrom<&str>`",
+        ))?;
+        let mut visitor = NameElided(false);
+        visitor.visit_type_mut(&mut ty);
+        Ok((ty, visitor.0))
+    }
+}
+impl ImplTrait for ImplTryFrom {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "core", "convert", "TryFrom"])
+    }
+
+    fn support_path_arguments(&self) -> bool {
+        true
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn require_using(&self) -> bool {
+        true
+    }
+
+    fn struct_impl(&self, item: &ItemStruct, args: &ImplArgs) -> Result<Toks> {
+        let (path, items) = self.struct_items(item, args)?;
+        let mut generics = item.generics.clone();
+        if Self::source(args)?.1 {
+            let lt = Lifetime::new("'__src", Span::call_site());
+            generics
+                .params
+                .insert(0, GenericParam::Lifetime(LifetimeParam::new(lt)));
+        }
+        let (impl_generics, _, where_clause) = ImplGenericsBuilder::new(&generics)
+            .clause(args.clause.as_ref())
+            .split_for_impl(&path);
+        let (_, ty_generics, _) = item.generics.split_for_impl();
+        let ident = &item.ident;
+        Ok(quote! {
+            #[automatically_derived]
+            impl #impl_generics #path for #ident #ty_generics #where_clause {
+                #items
+            }
+        })
+    }
+
+    fn enum_items(&self, item: &ItemEnum, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let source = type_argument(args)?
+            .ok_or(Error::CallSite("expected source type, e.g. `TryFrom<u8>`"))?;
+        let ident = &item.ident;
+        let variants = fieldless_variants(item)?;
+        let repr = int_repr(item)?;
+        let method = quote! {
+            type Error = #source;
+            fn try_from(value: #source) -> #root::core::result::Result<Self, Self::Error> {
+                if let #root::core::result::Result::Ok(repr) =
+                    <#repr as #root::core::convert::TryFrom<#source>>::try_from(value)
+                {
+                    #(if repr == #ident::#variants as #repr {
+                        return #root::core::result::Result::Ok(#ident::#variants);
+                    })*
+                }
+                #root::core::result::Result::Err(value)
+            }
+        };
+        Ok((quote! { #root::core::convert::TryFrom<#source> }, method))
+    }
+
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let source = Self::source(args)?.0;
+        let field = args.using_field(&item.fields).ok_or(Error::RequireUsing)?;
+        if is_non_exhaustive(&item.attrs) {
+            return Err(Error::CallSite(
+                "`TryFrom` would allow construction of a `#[non_exhaustive]` struct",
+            ));
+        }
+
+        let member = args.using_member().unwrap();
+        let inits = field_members(&item.fields).map(|(m, _)| {
+            if m == *member {
+                quote! { #m: #root::core::convert::TryFrom::try_from(value)? }
+            } else {
+                quote! { #m: #root::core::default::Default::default() }
+            }
+        });
+        let ty = &field.ty;
+        let ident = &item.ident;
+        let method = quote! {
+            type Error = <#ty as #root::core::convert::TryFrom<#source>>::Error;
+            fn try_from(value: #source) -> #root::core::result::Result<Self, Self::Error> {
+                #root::core::result::Result::Ok(#ident { #(#inits),* })
+            }
+        };
+        Ok((quote! { #root::core::convert::TryFrom<#source> }, method))
+    }
+}
+
+/// Primitive integer types
+const INTS: &[&str] = &[
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+];
+
+/// Get the integer type `INT` of an enum with attribute `#[repr(INT)]`
+///
+/// Discriminants are compared and cast in this type, thus conversions to
+/// other integer types are checked.
+fn int_repr(item: &ItemEnum) -> Result<Ident> {
+    let mut repr = None;
+    for attr in &item.attrs {
+        if attr.path().is_ident("repr") {
+            let _ = attr.parse_nested_meta(|meta| {
+                if let Some(ident) = meta.path.get_ident() {
+                    if INTS.iter().any(|int| ident == int) {
+                        repr = Some(ident.clone());
+                    }
+                }
+                if meta.input.peek(syn::token::Paren) {
+                    let _content;
+                    syn::parenthesized!(_content in meta.input);
+                }
+                Ok(())
+            });
+        }
+    }
+    repr.ok_or(Error::CallSite(
+        "integer conversion requires an integer representation, e.g. `#[repr(u8)]`",
+    ))
+}
+
+/// Get the variants of a fieldless enum
+fn fieldless_variants(item: &ItemEnum) -> Result<Vec<&Ident>> {
+    item.variants
+        .iter()
+        .map(|v| match v.fields {
+            Fields::Unit => Ok(&v.ident),
+            _ => Err(Error::WithSpan(
+                v.fields.span(),
+                "integer conversion requires a fieldless enum",
+            )),
+        })
+        .collect()
+}
+
+/// Implement [`core::convert::Into`] (via `From`) for the type of a field
+///
+/// This generates `impl From<Self> for T` where `T` is the type of the field
+/// given by `using`, moving the field out of `self`. By the orphan rules,
+/// `T` may not be a type parameter of the struct.
+///
+/// On fieldless enums with `#[repr(INT)]`, `Into<T>` (for integer type `T`)
+/// casts the discriminant to `INT`, then converts via `T: From<INT>` (thus
+/// narrowing conversions are an error).
+pub struct ImplInto;
+impl ImplTrait for ImplInto {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "core", "convert", "Into"])
+    }
+
+    fn support_path_arguments(&self) -> bool {
+        true
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn require_using(&self) -> bool {
+        true
+    }
+
+    fn enum_impl(&self, item: &ItemEnum, args: &ImplArgs) -> Result<Toks> {
+        let root = &args.root;
+        let ty =
+            type_argument(args)?.ok_or(Error::CallSite("expected target type, e.g. `Into<u8>`"))?;
+        fieldless_variants(item)?;
+        let repr = int_repr(item)?;
+        let ident = &item.ident;
+        let path = quote! { #root::core::convert::Into<#ty> };
+        let (impl_generics, ty_generics, where_clause) = ImplGenericsBuilder::new(&item.generics)
+            .clause(args.clause.as_ref())
+            .split_for_impl(&path);
+        Ok(quote! {
+            #[automatically_derived]
+            impl #impl_generics #root::core::convert::From<#ident #ty_generics> for #ty #where_clause {
+                fn from(value: #ident #ty_generics) -> Self {
+                    #root::core::convert::From::from(value as #repr)
+                }
+            }
+        })
+    }
+
+    fn struct_impl(&self, item: &ItemStruct, args: &ImplArgs) -> Result<Toks> {
+        let root = &args.root;
+        let (path, items) = self.struct_items(item, args)?;
+        let ident = &item.ident;
+        let ty = &args.using_field(&item.fields).unwrap().ty;
+        let (impl_generics, ty_generics, where_clause) = ImplGenericsBuilder::new(&item.generics)
+            .clause(args.clause.as_ref())
+            .split_for_impl(&path);
+        Ok(quote! {
+            #[automatically_derived]
+            impl #impl_generics #root::core::convert::From<#ident #ty_generics> for #ty #where_clause {
+                #items
+            }
+        })
+    }
+
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        if type_argument(args)?.is_some() {
+            return Err(Error::PathArguments(
+                "unexpected: target is the type of the `using` field",
+            ));
+        }
+        let field = args.using_field(&item.fields).ok_or(Error::RequireUsing)?;
+        let member = args.using_member().unwrap();
+        let ty = &field.ty;
+        let ident = &item.ident;
+        let (_, ty_generics, _) = item.generics.split_for_impl();
+        let method = quote! {
+            fn from(value: #ident #ty_generics) -> Self {
+                value.#member
+            }
+        };
+        Ok((quote! { #root::core::convert::Into<#ty> }, method))
+    }
+}
+
+/// Implement [`core::iter::Iterator`]
+///
+/// Forwards `next` and `size_hint` to the field given by `using`.
+pub struct ImplIterator;
+impl ImplTrait for ImplIterator {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "core", "iter", "Iterator"])
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn require_using(&self) -> bool {
+        true
+    }
+
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        if let Some(field) = args.using_field(&item.fields) {
+            let ty = &field.ty;
+            let member = args.using_member().unwrap();
+            let method = quote! {
+                type Item = <#ty as #root::core::iter::Iterator>::Item;
+                #[inline]
+                fn next(&mut self) -> #root::core::option::Option<Self::Item> {
+                    #root::core::iter::Iterator::next(&mut self.#member)
+                }
+                #[inline]
+                fn size_hint(&self) -> (usize, #root::core::option::Option<usize>) {
+                    #root::core::iter::Iterator::size_hint(&self.#member)
+                }
+            };
+            Ok((quote! { #root::core::iter::Iterator }, method))
+        } else {
+            Err(Error::RequireUsing)
+        }
+    }
+}
+
+/// Implement [`core::iter::IntoIterator`]
+///
+/// Forwards to the field given by `using`. By default this implements
+/// `IntoIterator` for `Self`, `&Self` and `&mut Self`; options `by_value`,
+/// `by_ref` and `by_mut` select a subset of these.
+pub struct ImplIntoIterator;
+impl ImplTrait for ImplIntoIterator {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "core", "iter", "IntoIterator"])
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn require_using(&self) -> bool {
+        true
+    }
+
+    fn support_option(&self, name: &str) -> bool {
+        matches!(name, "by_value" | "by_ref" | "by_mut")
+    }
+
+    fn struct_impl(&self, item: &ItemStruct, args: &ImplArgs) -> Result<Toks> {
+        let root = &args.root;
+        let field = args.using_field(&item.fields).ok_or(Error::RequireUsing)?;
+        let member = args.using_member().unwrap();
+        let ty = &field.ty;
+        let ident = &item.ident;
+        let path = quote! { #root::core::iter::IntoIterator };
+        let all = !["by_value", "by_ref", "by_mut"]
+            .iter()
+            .any(|name| args.option(name));
+
+        let mut toks = Toks::new();
+        if all || args.option("by_value") {
+            let (path, items) = self.struct_items(item, args)?;
+            let mut generics = item.generics.clone();
+            generics
+                .make_where_clause()
+                .predicates
+                .push(syn::parse_quote! { #ty: #root::core::iter::IntoIterator });
+            toks.append_all(
+                ImplGenericsBuilder::new(&generics)
+                    .clause(args.clause.as_ref())
+                    .impl_block(ident, &path, &items),
+            );
+        }
+
+        let lt = Lifetime::new("'__a", Span::call_site());
+        let (_, ty_generics, _) = item.generics.split_for_impl();
+        let mut by_ref = |mutability: Toks, expr: Toks| {
+            let mut generics = item.generics.clone();
+            generics
+                .params
+                .insert(0, GenericParam::Lifetime(LifetimeParam::new(lt.clone())));
+            let field_ref = quote! { &#lt #mutability #ty };
+            generics
+                .make_where_clause()
+                .predicates
+                .push(syn::parse_quote! { #field_ref: #root::core::iter::IntoIterator });
+            let (impl_generics, _, where_clause) = ImplGenericsBuilder::new(&generics)
+                .clause(args.clause.as_ref())
+                .split_for_impl(&path);
+            toks.append_all(quote! {
+                #[automatically_derived]
+                impl #impl_generics #root::core::iter::IntoIterator
+                    for &#lt #mutability #ident #ty_generics #where_clause
+                {
+                    type Item = <#field_ref as #root::core::iter::IntoIterator>::Item;
+                    type IntoIter = <#field_ref as #root::core::iter::IntoIterator>::IntoIter;
+                    fn into_iter(self) -> Self::IntoIter {
+                        #root::core::iter::IntoIterator::into_iter(#expr)
+                    }
+                }
+            });
+        };
+        if all || args.option("by_ref") {
+            by_ref(Toks::new(), quote! { &self.#member });
+        }
+        if all || args.option("by_mut") {
+            by_ref(quote! { mut }, quote! { &mut self.#member });
+        }
+        Ok(toks)
+    }
+
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let field = args.using_field(&item.fields).ok_or(Error::RequireUsing)?;
+        let member = args.using_member().unwrap();
+        let ty = &field.ty;
+        let method = quote! {
+            type Item = <#ty as #root::core::iter::IntoIterator>::Item;
+            type IntoIter = <#ty as #root::core::iter::IntoIterator>::IntoIter;
+            fn into_iter(self) -> Self::IntoIter {
+                #root::core::iter::IntoIterator::into_iter(self.#member)
+            }
+        };
+        Ok((quote! { #root::core::iter::IntoIterator }, method))
+    }
+}
+
+/// Implement [`core::iter::FromIterator`]
+///
+/// This implements `FromIterator<A>` for each `A` supported by the field
+/// given by `using`, collecting into the field and initializing other fields
+/// with `Default::default()`.
+pub struct ImplFromIterator;
+impl ImplTrait for ImplFromIterator {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "core", "iter", "FromIterator"])
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn require_using(&self) -> bool {
+        true
+    }
+
+    fn struct_impl(&self, item: &ItemStruct, args: &ImplArgs) -> Result<Toks> {
+        let ty = &args
+            .using_field(&item.fields)
+            .ok_or(Error::RequireUsing)?
+            .ty;
+        let (path, items) = self.struct_items(item, args)?;
+        Ok(impl_with_item_param(
+            item,
+            args,
+            &path,
+            &items,
+            quote! { #ty: #path },
+        ))
+    }
+
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let member = args.using_member().ok_or(Error::RequireUsing)?;
+        if is_non_exhaustive(&item.attrs) {
+            return Err(Error::CallSite(
+                "`FromIterator` would allow construction of a `#[non_exhaustive]` struct",
+            ));
+        }
+
+        let inits = field_members(&item.fields).map(|(m, _)| {
+            if m == *member {
+                quote! { #m: #root::core::iter::FromIterator::from_iter(iter) }
+            } else {
+                quote! { #m: #root::core::default::Default::default() }
+            }
+        });
+        let ident = &item.ident;
+        let method = quote! {
+            fn from_iter<__I: #root::core::iter::IntoIterator<Item = __A>>(iter: __I) -> Self {
+                #ident { #(#inits),* }
+            }
+        };
+        Ok((quote! { #root::core::iter::FromIterator<__A> }, method))
+    }
+}
+
+/// Implement [`core::iter::Extend`]
+///
+/// This implements `Extend<A>` for each `A` supported by the field given by
+/// `using`.
+pub struct ImplExtend;
+impl ImplTrait for ImplExtend {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "core", "iter", "Extend"])
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn require_using(&self) -> bool {
+        true
+    }
+
+    fn struct_impl(&self, item: &ItemStruct, args: &ImplArgs) -> Result<Toks> {
+        let ty = &args
+            .using_field(&item.fields)
+            .ok_or(Error::RequireUsing)?
+            .ty;
+        let (path, items) = self.struct_items(item, args)?;
+        Ok(impl_with_item_param(
+            item,
+            args,
+            &path,
+            &items,
+            quote! { #ty: #path },
+        ))
+    }
+
+    fn struct_items(&self, _: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let member = args.using_member().ok_or(Error::RequireUsing)?;
+        let method = quote! {
+            fn extend<__I: #root::core::iter::IntoIterator<Item = __A>>(&mut self, iter: __I) {
+                #root::core::iter::Extend::extend(&mut self.#member, iter)
+            }
+        };
+        Ok((quote! { #root::core::iter::Extend<__A> }, method))
+    }
+}
+
+/// Implement [`core::ops::Index`]
+///
+/// With an index type, e.g. `Index<usize>`, this implements only `Index<usize>`;
+/// otherwise this implements `Index<A>` for each `A` supported by the field.
+pub struct ImplIndex;
+impl ImplTrait for ImplIndex {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "core", "ops", "Index"])
+    }
+
+    fn support_path_arguments(&self) -> bool {
+        true
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn require_using(&self) -> bool {
+        true
+    }
+
+    fn struct_impl(&self, item: &ItemStruct, args: &ImplArgs) -> Result<Toks> {
+        let ty = &args
+            .using_field(&item.fields)
+            .ok_or(Error::RequireUsing)?
+            .ty;
+        let (path, items) = self.struct_items(item, args)?;
+        Ok(match type_argument(args)? {
+            Some(_) => ImplGenericsBuilder::new(&item.generics)
+                .clause(args.clause.as_ref())
+                .impl_block(&item.ident, &path, &items),
+            None => impl_with_item_param(item, args, &path, &items, quote! { #ty: #path }),
+        })
+    }
+
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let field = args.using_field(&item.fields).ok_or(Error::RequireUsing)?;
+        let member = args.using_member().unwrap();
+        let ty = &field.ty;
+        let index = match type_argument(args)? {
+            Some(index) => quote! { #index },
+            None => quote! { __A },
+        };
+        let method = quote! {
+            type Output = <#ty as #root::core::ops::Index<#index>>::Output;
+            #[inline]
+            fn index(&self, index: #index) -> &Self::Output {
+                #root::core::ops::Index::index(&self.#member, index)
+            }
+        };
+        Ok((quote! { #root::core::ops::Index<#index> }, method))
+    }
+}
+
+/// Implement [`core::ops::RangeBounds`]
+///
+/// The bound type `T` may be given, e.g. `RangeBounds<u32>`; otherwise the
+/// impl is generic over all `T` supported by the field.
+pub struct ImplRangeBounds;
+impl ImplTrait for ImplRangeBounds {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "core", "ops", "RangeBounds"])
+    }
+
+    fn support_path_arguments(&self) -> bool {
+        true
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn require_using(&self) -> bool {
+        true
+    }
+
+    fn struct_impl(&self, item: &ItemStruct, args: &ImplArgs) -> Result<Toks> {
+        let ty = &args
+            .using_field(&item.fields)
+            .ok_or(Error::RequireUsing)?
+            .ty;
+        let (path, items) = self.struct_items(item, args)?;
+        Ok(match type_argument(args)? {
+            Some(_) => ImplGenericsBuilder::new(&item.generics)
+                .clause(args.clause.as_ref())
+                .impl_block(&item.ident, &path, &items),
+            None => impl_with_item_param(item, args, &path, &items, quote! { #ty: #path }),
+        })
+    }
+
+    fn struct_items(&self, _: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let member = args.using_member().ok_or(Error::RequireUsing)?;
+        let bound = match type_argument(args)? {
+            Some(ty) => quote! { #ty },
+            None => quote! { __A },
+        };
+        let method = quote! {
+            #[inline]
+            fn start_bound(&self) -> #root::core::ops::Bound<&#bound> {
+                #root::core::ops::RangeBounds::start_bound(&self.#member)
+            }
+            #[inline]
+            fn end_bound(&self) -> #root::core::ops::Bound<&#bound> {
+                #root::core::ops::RangeBounds::end_bound(&self.#member)
+            }
+        };
+        Ok((quote! { #root::core::ops::RangeBounds<#bound> }, method))
+    }
+}
+
+/// Implement [`core::ops::IndexMut`]
+///
+/// The index type is as for [`ImplIndex`].
+pub struct ImplIndexMut;
+impl ImplTrait for ImplIndexMut {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "core", "ops", "IndexMut"])
+    }
+
+    fn support_path_arguments(&self) -> bool {
+        true
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn require_using(&self) -> bool {
+        true
+    }
+
+    fn struct_impl(&self, item: &ItemStruct, args: &ImplArgs) -> Result<Toks> {
+        let ty = &args
+            .using_field(&item.fields)
+            .ok_or(Error::RequireUsing)?
+            .ty;
+        let (path, items) = self.struct_items(item, args)?;
+        Ok(match type_argument(args)? {
+            Some(_) => ImplGenericsBuilder::new(&item.generics)
+                .clause(args.clause.as_ref())
+                .impl_block(&item.ident, &path, &items),
+            None => impl_with_item_param(item, args, &path, &items, quote! { #ty: #path }),
+        })
+    }
+
+    fn struct_items(&self, _: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let member = args.using_member().ok_or(Error::RequireUsing)?;
+        let index = match type_argument(args)? {
+            Some(index) => quote! { #index },
+            None => quote! { __A },
+        };
+        let method = quote! {
+            #[inline]
+            fn index_mut(&mut self, index: #index) -> &mut Self::Output {
+                #root::core::ops::IndexMut::index_mut(&mut self.#member, index)
+            }
+        };
+        Ok((quote! { #root::core::ops::IndexMut<#index> }, method))
+    }
+}
+
+/// Generate `impl<__A, ..> #path for #ident<..> where #bound`
+fn impl_with_item_param(
+    item: &ItemStruct,
+    args: &ImplArgs,
+    path: &Toks,
+    items: &Toks,
+    bound: Toks,
+) -> Toks {
+    let mut generics = item.generics.clone();
+    // Type parameters must precede const parameters (before Rust 1.59)
+    let index = generics
+        .params
+        .iter()
+        .position(|param| matches!(param, syn::GenericParam::Const(_)))
+        .unwrap_or(generics.params.len());
+    generics.params.insert(index, syn::parse_quote! { __A });
+    generics
+        .make_where_clause()
+        .predicates
+        .push(syn::parse_quote! { #bound });
+    let (impl_generics, _, where_clause) = ImplGenericsBuilder::new(&generics)
+        .clause(args.clause.as_ref())
+        .split_for_impl(path);
+    let (_, ty_generics, _) = item.generics.split_for_impl();
+    let ident = &item.ident;
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics #path for #ident #ty_generics #where_clause {
+            #items
+        }
+    }
+}
+
 /// Implement [`core::ops::Deref`]
 pub struct ImplDeref;
 impl ImplTrait for ImplDeref {
@@ -134,7 +1339,12 @@ impl ImplTrait for ImplDeref {
         true
     }
 
+    fn require_using(&self) -> bool {
+        true
+    }
+
     fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
         if let Some(field) = args.using_field(&item.fields) {
             let target = match args.path_arguments {
                 PathArguments::None => field.ty.clone(),
@@ -167,7 +1377,7 @@ impl ImplTrait for ImplDeref {
                     &self.#member
                 }
             };
-            Ok((quote! { ::core::ops::Deref }, method))
+            Ok((quote! { #root::core::ops::Deref }, method))
         } else {
             Err(Error::RequireUsing)
         }
@@ -185,16 +1395,219 @@ impl ImplTrait for ImplDerefMut {
         true
     }
 
+    fn require_using(&self) -> bool {
+        true
+    }
+
     fn struct_items(&self, _: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
         if let Some(member) = args.using_member() {
             let method = quote! {
                 fn deref_mut(&mut self) -> &mut Self::Target {
                     &mut self.#member
                 }
             };
-            Ok((quote! { ::core::ops::DerefMut }, method))
+            Ok((quote! { #root::core::ops::DerefMut }, method))
         } else {
             Err(Error::RequireUsing)
         }
     }
 }
+
+/// Implement inner-access methods `into_inner`, `as_inner` and `as_inner_mut`
+///
+/// Unlike other targets, this generates an inherent `impl` block, not a trait
+/// implementation.
+pub struct ImplInner;
+impl ImplTrait for ImplInner {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["Inner"])
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn require_using(&self) -> bool {
+        true
+    }
+
+    fn support_visibility(&self) -> bool {
+        true
+    }
+
+    fn struct_impl(&self, item: &ItemStruct, args: &ImplArgs) -> Result<Toks> {
+        let type_ident = &item.ident;
+        let (impl_generics, ty_generics, wc) = ImplGenericsBuilder::new(&item.generics)
+            .clause(args.clause.as_ref())
+            .split_for_impl(&quote! {});
+
+        let items = self.struct_items(item, args)?.1;
+
+        Ok(quote! {
+            impl #impl_generics #type_ident #ty_generics #wc {
+                #items
+            }
+        })
+    }
+
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        if let Some(field) = args.using_field(&item.fields) {
+            let ty = field.ty.clone();
+            let member = args.using_member().unwrap();
+            let vis = match args.vis {
+                Some(ref vis) => quote! { #vis },
+                None => quote! { pub },
+            };
+            // Accessors of a deprecated field are deprecated
+            let mut deprecated = Toks::new();
+            for attr in &field.attrs {
+                if attr.path().is_ident("deprecated") {
+                    deprecated.append_all(quote! { #attr #[allow(deprecated)] });
+                }
+            }
+            let methods = quote! {
+                /// Unwrap the inner value
+                #[inline]
+                #deprecated
+                #vis fn into_inner(self) -> #ty {
+                    self.#member
+                }
+
+                /// Borrow the inner value
+                #[inline]
+                #deprecated
+                #vis fn as_inner(&self) -> & #ty {
+                    &self.#member
+                }
+
+                /// Mutably borrow the inner value
+                #[inline]
+                #deprecated
+                #vis fn as_inner_mut(&mut self) -> &mut #ty {
+                    &mut self.#member
+                }
+            };
+            Ok((quote! {}, methods))
+        } else {
+            Err(Error::RequireUsing)
+        }
+    }
+}
+
+/// Implement a bundle of traits for a string newtype
+///
+/// Given a field of type `S` which dereferences to `str` (e.g. `String`),
+/// this implements `Deref<Target = str>`, `AsRef<str>`, `Borrow<str>`,
+/// `Display`, `From<S>` and `PartialEq<str>`.
+///
+/// The field may be specified via `using`; otherwise the struct must have a
+/// single field. `From<S>` initializes other fields with `Default::default()`.
+pub struct ImplStrNewtype;
+impl ImplStrNewtype {
+    fn member(item: &ItemStruct, args: &ImplArgs) -> Result<Member> {
+        if let Some(member) = args.using_member() {
+            return Ok(member.clone());
+        }
+        let mut iter = field_members(&item.fields);
+        match (iter.next(), iter.next()) {
+            (Some((member, _)), None) => Ok(member),
+            _ => Err(Error::RequireUsing),
+        }
+    }
+}
+impl ImplTrait for ImplStrNewtype {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["StrNewtype"])
+    }
+
+    fn support_using(&self) -> bool {
+        true
+    }
+
+    fn require_using(&self) -> bool {
+        true
+    }
+
+    fn struct_impl(&self, item: &ItemStruct, args: &ImplArgs) -> Result<Toks> {
+        let root = &args.root;
+        let member = Self::member(item, args)?;
+        let mut field_ty = None;
+        let mut inits = Vec::with_capacity(item.fields.len());
+        for (m, field) in field_members(&item.fields) {
+            if m == member {
+                field_ty = Some(&field.ty);
+                inits.push(quote! { #m: value });
+            } else {
+                inits.push(quote! { #m: #root::core::default::Default::default() });
+            }
+        }
+        let field_ty = field_ty.ok_or(Error::RequireUsing)?;
+
+        let builder = ImplGenericsBuilder::new(&item.generics).clause(args.clause.as_ref());
+        let ident = &item.ident;
+        let mut toks = Toks::new();
+        let (path, items) = self.struct_items(item, args)?;
+        toks.append_all(builder.impl_block(ident, &path, &items));
+        toks.append_all(builder.impl_block(
+            ident,
+            &quote! { #root::core::convert::AsRef<str> },
+            &quote! {
+                fn as_ref(&self) -> &str {
+                    &self.#member
+                }
+            },
+        ));
+        toks.append_all(builder.impl_block(
+            ident,
+            &quote! { #root::core::borrow::Borrow<str> },
+            &quote! {
+                fn borrow(&self) -> &str {
+                    &self.#member
+                }
+            },
+        ));
+        toks.append_all(builder.impl_block(
+            ident,
+            &quote! { #root::core::fmt::Display },
+            &quote! {
+                fn fmt(&self, f: &mut #root::core::fmt::Formatter) -> #root::core::fmt::Result {
+                    <str as #root::core::fmt::Display>::fmt(&self.#member, f)
+                }
+            },
+        ));
+        if !is_non_exhaustive(&item.attrs) {
+            toks.append_all(builder.impl_block(
+                ident,
+                &quote! { #root::core::convert::From<#field_ty> },
+                &quote! {
+                    fn from(value: #field_ty) -> Self {
+                        #ident { #(#inits),* }
+                    }
+                },
+            ));
+        }
+        toks.append_all(builder.impl_block(
+            ident,
+            &quote! { #root::core::cmp::PartialEq<str> },
+            &quote! {
+                fn eq(&self, other: &str) -> bool {
+                    <str as #root::core::cmp::PartialEq>::eq(&self.#member, other)
+                }
+            },
+        ));
+        Ok(toks)
+    }
+
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let member = Self::member(item, args)?;
+        let items = quote! {
+            type Target = str;
+            fn deref(&self) -> &str {
+                &self.#member
+            }
+        };
+        Ok((quote! { #root::core::ops::Deref }, items))
+    }
+}
@@ -0,0 +1,97 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Impls for `zeroize` traits
+//!
+//! Generated code refers to `::zeroize`; the user's crate must depend on it.
+
+use super::{field_members, ImplArgs, ImplTrait, Result};
+use crate::generics::ImplGenericsBuilder;
+use crate::SimplePath;
+use proc_macro2::TokenStream as Toks;
+use quote::quote;
+use syn::ItemStruct;
+
+/// Statements zeroizing each non-ignored field of `self`
+fn zeroize_stmts(item: &ItemStruct, args: &ImplArgs) -> Vec<Toks> {
+    let root = &args.root;
+    field_members(&item.fields)
+        .filter_map(|(member, _)| {
+            (!args.ignore(&member))
+                .then(|| quote! { #root::zeroize::Zeroize::zeroize(&mut self.#member); })
+        })
+        .collect()
+}
+
+/// Implement `zeroize::Zeroize`
+///
+/// Each field not ignored is zeroized.
+pub struct ImplZeroize;
+impl ImplTrait for ImplZeroize {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "zeroize", "Zeroize"])
+    }
+
+    fn support_ignore(&self) -> bool {
+        true
+    }
+
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let stmts = zeroize_stmts(item, args);
+        let method = quote! {
+            fn zeroize(&mut self) {
+                #(#stmts)*
+            }
+        };
+        Ok((quote! { #root::zeroize::Zeroize }, method))
+    }
+}
+
+/// Implement `zeroize::ZeroizeOnDrop`
+///
+/// This implements `Drop`, zeroizing each field not ignored, as well as the
+/// marker trait `ZeroizeOnDrop`. Within a `where` clause, `trait` is
+/// substituted with `::zeroize::Zeroize`. Since `Drop` impls may not be
+/// more restrictive than the struct, bounds must also appear on the struct.
+pub struct ImplZeroizeOnDrop;
+impl ImplTrait for ImplZeroizeOnDrop {
+    fn path(&self) -> SimplePath {
+        SimplePath::new(&["", "zeroize", "ZeroizeOnDrop"])
+    }
+
+    fn support_ignore(&self) -> bool {
+        true
+    }
+
+    fn struct_impl(&self, item: &ItemStruct, args: &ImplArgs) -> Result<Toks> {
+        let root = &args.root;
+        let (path, items) = self.struct_items(item, args)?;
+        let ident = &item.ident;
+        let builder = ImplGenericsBuilder::new(&item.generics).clause(args.clause.as_ref());
+        let (impl_generics, ty_generics, where_clause) =
+            builder.split_for_impl(&quote! { #root::zeroize::Zeroize });
+        Ok(quote! {
+            #[automatically_derived]
+            impl #impl_generics #root::core::ops::Drop for #ident #ty_generics #where_clause {
+                #items
+            }
+
+            #[automatically_derived]
+            impl #impl_generics #path for #ident #ty_generics #where_clause {}
+        })
+    }
+
+    fn struct_items(&self, item: &ItemStruct, args: &ImplArgs) -> Result<(Toks, Toks)> {
+        let root = &args.root;
+        let stmts = zeroize_stmts(item, args);
+        let method = quote! {
+            fn drop(&mut self) {
+                #(#stmts)*
+            }
+        };
+        Ok((quote! { #root::zeroize::ZeroizeOnDrop }, method))
+    }
+}
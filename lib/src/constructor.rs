@@ -0,0 +1,196 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+use crate::default::{check_not_skipped, parse_validate, validate_or_panic};
+use crate::generics::{ImplGenericsBuilder, WhereClause};
+use crate::{Scope, ScopeAttr, ScopeItem, SimplePath};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Error, ParseStream, Result};
+use syn::spanned::Spanned;
+use syn::{Attribute, Expr, Lit, Meta, Token, UnOp, Visibility};
+
+/// [`ScopeAttr`] rule enabling `#[impl_new]` within `impl_scope!`
+///
+/// This generates an inherent method `fn new() -> Self`, constructing the
+/// struct from field initializers. Fields without an initializer use
+/// `Default::default()`.
+///
+/// The method is a `const fn` when every field has an initializer built only
+/// from literals, tuples, arrays and (non-floating-point) operators and when
+/// the struct has no bounds on type parameters (which `const fn` does not
+/// support before Rust 1.61). Other initializers, in particular paths (which
+/// may name a `static`) and calls, are not inferred to be const. With
+/// `#[impl_new(const)]`, the method is always a `const fn` and a missing
+/// initializer is an error.
+///
+/// With `validate = PATH`, e.g. `#[impl_new(validate = Self::check)]`, the
+/// constructed value is passed to `PATH(&value)` which must return a `Result`;
+/// `new` panics on error. In this case `new` is not a `const fn`.
+///
+/// An optional `where` clause, e.g. `#[impl_new(where T: trait)]`, adds
+/// bounds to the impl; `trait` is substituted with `Default`.
+///
+/// The method has the same visibility as the struct unless a visibility is
+/// given, e.g. `#[impl_new(pub(crate))]`. Since `new` takes no field values,
+/// it is also generated for `#[non_exhaustive]` structs.
+pub struct AttrImplNew;
+impl ScopeAttr for AttrImplNew {
+    fn path(&self) -> SimplePath {
+        SimplePath(&["impl_new"])
+    }
+
+    fn uses_field_initializers(&self) -> bool {
+        true
+    }
+
+    fn apply(&self, attr: Attribute, scope: &mut Scope) -> Result<()> {
+        let span = attr.span();
+        let (vis, require_const, validate, where_clause) = match attr.meta {
+            Meta::Path(_) => (None, false, None, None),
+            Meta::List(list) => list.parse_args_with(|input: ParseStream| {
+                let vis = match input.parse()? {
+                    Visibility::Inherited => None,
+                    vis => Some(vis),
+                };
+                let constness: Option<Token![const]> = input.parse()?;
+                let validate = if (vis.is_some() || constness.is_some()) && input.peek(Token![,]) {
+                    let _: Token![,] = input.parse()?;
+                    match parse_validate(input)? {
+                        Some(path) => Some(path),
+                        None => return Err(input.error("expected `validate = PATH`")),
+                    }
+                } else {
+                    parse_validate(input)?
+                };
+                if let (Some(token), Some(path)) = (constness, validate.as_ref()) {
+                    return Err(Error::new(
+                        token.span.join(path.span()).unwrap_or(token.span),
+                        "#[impl_new(const)] does not support `validate`",
+                    ));
+                }
+                let mut where_clause: Option<WhereClause> = None;
+                if input.peek(Token![where]) {
+                    where_clause = Some(input.parse()?);
+                }
+                Ok((vis, constness.is_some(), validate, where_clause))
+            })?,
+            Meta::NameValue(meta) => {
+                return Err(Error::new_spanned(
+                    meta,
+                    "expected #[impl_new] or #[impl_new(VIS const, validate = PATH where ..)]",
+                ))
+            }
+        };
+
+        // Bounds on type parameters are not supported by `const fn` on our MSRV
+        let mut is_const = validate.is_none()
+            && where_clause.is_none()
+            && scope.generics.where_clause.is_none()
+            && scope
+                .generics
+                .type_params()
+                .all(|param| param.bounds.is_empty());
+        let fields = match &scope.item {
+            ScopeItem::Struct { fields, .. } => {
+                let mut toks = Vec::with_capacity(fields.len());
+                for (member, field) in fields.iter() {
+                    let cfgs = field.cfg_attrs(&member)?;
+                    check_not_skipped(field, "#[impl_new]")?;
+                    if let Some(expr) = field.initializer() {
+                        is_const &= is_const_expr(expr);
+                        toks.push(quote! { #(#cfgs)* #member : #expr });
+                    } else if require_const {
+                        return Err(Error::new(
+                            field.ty.span(),
+                            "#[impl_new(const)]: field requires an initializer",
+                        ));
+                    } else {
+                        is_const = false;
+                        toks.push(quote! {
+                            #(#cfgs)* #member : ::core::default::Default::default()
+                        });
+                    }
+                }
+                quote! { #(#toks),* }
+            }
+            _ => {
+                return Err(Error::new(span, "#[impl_new] is only supported on structs"));
+            }
+        };
+
+        let constness = if is_const || require_const {
+            quote! { const }
+        } else {
+            TokenStream::new()
+        };
+
+        let ident = &scope.ident;
+        let validate = validate_or_panic(validate.as_ref(), ident, "new");
+        let vis = vis.as_ref().unwrap_or(&scope.vis);
+        let (impl_generics, ty_generics, where_clause) = ImplGenericsBuilder::new(&scope.generics)
+            .clause(where_clause.as_ref())
+            .split_for_impl(&quote! { ::core::default::Default });
+        scope.generated.push(quote! {
+            impl #impl_generics #ident #ty_generics #where_clause {
+                /// Construct a new instance
+                #[must_use]
+                #vis #constness fn new() -> Self {
+                    let value = #ident {
+                        #fields
+                    };
+                    #validate
+                    value
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+/// True if `expr` may be evaluated in any `const fn`
+///
+/// This holds for literals and for tuples, arrays and unary and binary
+/// operators over these. Operators are restricted to integer, `bool` and
+/// `char` operands since floating-point arithmetic in `const fn` requires
+/// Rust 1.82.
+fn is_const_expr(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(_) => true,
+        Expr::Paren(expr) => is_const_expr(&expr.expr),
+        Expr::Group(expr) => is_const_expr(&expr.expr),
+        Expr::Tuple(expr) => expr.elems.iter().all(is_const_expr),
+        Expr::Array(expr) => expr.elems.iter().all(is_const_expr),
+        Expr::Unary(_) | Expr::Binary(_) => is_const_operand(expr),
+        _ => false,
+    }
+}
+
+/// True if `expr` is a `const` operator expression over non-float literals
+fn is_const_operand(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(expr) => matches!(
+            expr.lit,
+            Lit::Int(_) | Lit::Bool(_) | Lit::Byte(_) | Lit::Char(_)
+        ),
+        Expr::Paren(expr) => is_const_operand(&expr.expr),
+        Expr::Group(expr) => is_const_operand(&expr.expr),
+        Expr::Unary(expr) => {
+            matches!(expr.op, UnOp::Neg(_) | UnOp::Not(_)) && is_const_operand(&expr.expr)
+        }
+        Expr::Binary(expr) => is_const_operand(&expr.left) && is_const_operand(&expr.right),
+        _ => false,
+    }
+}
+
+/// Helper fn which can be passed to [`Scope::apply_attrs`]
+///
+/// This optionally matches [`AttrImplNew`].
+pub fn find_attr_impl_new(path: &syn::Path) -> Option<&'static dyn ScopeAttr> {
+    AttrImplNew
+        .path()
+        .matches(path)
+        .then(|| &AttrImplNew as &dyn ScopeAttr)
+}
@@ -3,23 +3,84 @@
 // You may obtain a copy of the License in the LICENSE-APACHE file or at:
 //     https://www.apache.org/licenses/LICENSE-2.0
 
-use crate::fields::{Fields, FieldsNamed, FieldsUnnamed};
-use crate::generics::{clause_to_toks, WhereClause};
+use crate::fields::Field;
+use crate::generics::{ImplGenericsBuilder, WhereClause};
 use crate::{Scope, ScopeAttr, ScopeItem, SimplePath};
 use proc_macro2::{Span, TokenStream};
 use proc_macro_error::emit_error;
 use quote::quote;
 use syn::parse::{Error, Parse, ParseStream, Result};
 use syn::spanned::Spanned;
-use syn::{parse2, Attribute, Expr, Generics, Ident, Item, Meta, Token};
+use syn::{parse2, Attribute, Expr, Generics, Ident, Item, Meta, Path, Token, Type, Visibility};
 
 /// `#[impl_default]` attribute
 pub struct ImplDefault {
     expr: Option<Expr>,
+    validate: Option<Path>,
     where_clause: Option<WhereClause>,
     span: Span,
 }
 
+mod kw {
+    syn::custom_keyword!(skip);
+    syn::custom_keyword!(validate);
+}
+
+/// Parse `validate = PATH`, if present
+pub(crate) fn parse_validate(input: ParseStream) -> Result<Option<Path>> {
+    if input.peek(kw::validate) && input.peek2(Token![=]) {
+        let _: kw::validate = input.parse()?;
+        let _: Token![=] = input.parse()?;
+        Ok(Some(input.parse()?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// True if `field` is marked `#[impl_default(skip)]`
+///
+/// Such a field has no default value: rules constructing the struct from
+/// field initializers must not use `Default::default()` in its place.
+pub(crate) fn is_skipped(field: &Field) -> Result<bool> {
+    match field.helper_attr(&SimplePath(&["impl_default"])) {
+        Some(attr) => {
+            attr.parse_args::<kw::skip>()?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Report an error if `field` is marked `#[impl_default(skip)]`
+pub(crate) fn check_not_skipped(field: &Field, rule: &str) -> Result<()> {
+    if is_skipped(field)? {
+        let attr = field.helper_attr(&SimplePath(&["impl_default"])).unwrap();
+        return Err(Error::new(
+            attr.span(),
+            format!("{rule}: field marked #[impl_default(skip)] has no default value"),
+        ));
+    }
+    Ok(())
+}
+
+/// Generate a statement validating `value` using `validate`, panicking on error
+pub(crate) fn validate_or_panic(
+    validate: Option<&Path>,
+    ident: &Ident,
+    method: &str,
+) -> TokenStream {
+    if let Some(path) = validate {
+        let msg = format!("{}::{}(): validation failed: {{:?}}", ident, method);
+        quote! {
+            if let ::core::result::Result::Err(err) = #path(&value) {
+                ::core::panic!(#msg, err);
+            }
+        }
+    } else {
+        TokenStream::new()
+    }
+}
+
 impl ImplDefault {
     /// Expand over the given `item`
     ///
@@ -64,28 +125,29 @@ impl ImplDefault {
     }
 
     fn gen_expr(self, ident: &Ident, generics: &Generics) -> TokenStream {
-        let (impl_generics, ty_generics, _) = generics.split_for_impl();
-        let wc = clause_to_toks(
-            &self.where_clause,
-            generics.where_clause.as_ref(),
-            &quote! { Default },
-        );
         let expr = self.expr.unwrap();
+        let validate = validate_or_panic(self.validate.as_ref(), ident, "default");
 
-        quote! {
-            #[automatically_derived]
-            impl #impl_generics core::default::Default for #ident #ty_generics #wc {
-                fn default() -> Self {
-                    #expr
-                }
-            }
-        }
+        ImplGenericsBuilder::new(generics)
+            .clause(self.where_clause.as_ref())
+            .impl_block(
+                ident,
+                &quote! { ::core::default::Default },
+                &quote! {
+                    fn default() -> Self {
+                        let value = #expr;
+                        #validate
+                        value
+                    }
+                },
+            )
     }
 
     fn parse_attr(attr: Attribute) -> Result<Self> {
         match attr.meta {
             Meta::Path(_) => Ok(ImplDefault {
                 expr: None,
+                validate: None,
                 where_clause: None,
                 span: attr.span(),
             }),
@@ -105,6 +167,10 @@ impl ScopeAttr for AttrImplDefault {
         SimplePath(&["impl_default"])
     }
 
+    fn uses_field_initializers(&self) -> bool {
+        true
+    }
+
     fn apply(&self, attr: Attribute, scope: &mut Scope) -> Result<()> {
         let args = ImplDefault::parse_attr(attr)?;
 
@@ -113,22 +179,39 @@ impl ScopeAttr for AttrImplDefault {
                 .generated
                 .push(args.gen_expr(&scope.ident, &scope.generics));
         } else {
-            let fields = match &mut scope.item {
-                ScopeItem::Struct { fields, .. } => match fields {
-                    Fields::Named(FieldsNamed { fields, .. })
-                    | Fields::Unnamed(FieldsUnnamed { fields, .. }) => {
-                        let iter = fields.iter_mut().map(|field| {
-                            let ident = &field.ident;
-                            if let Some(expr) = field.assign.take().map(|a| a.1) {
-                                quote! { #ident : #expr }
-                            } else {
-                                quote! { #ident : Default::default() }
+            let mut params = vec![];
+            let fields = match &scope.item {
+                ScopeItem::Struct { fields, .. } => {
+                    let mut toks = Vec::with_capacity(fields.len());
+                    for (member, field) in fields.iter() {
+                        let cfgs = field.cfg_attrs(&member)?;
+                        let skip = is_skipped(field)?;
+                        if let Some(expr) = field.initializer() {
+                            if skip {
+                                return Err(Error::new(
+                                    expr.span(),
+                                    "#[impl_default(skip)]: field may not have an initializer",
+                                ));
                             }
-                        });
-                        quote! { #(#iter),* }
+                            toks.push(quote! { #(#cfgs)* #member : #expr });
+                        } else if skip {
+                            let param = match &member {
+                                syn::Member::Named(ident) => ident.clone(),
+                                syn::Member::Unnamed(index) => {
+                                    Ident::new(&format!("field{}", index.index), index.span)
+                                }
+                            };
+                            let ty = &field.ty;
+                            params.push(quote! { #(#cfgs)* #param: #ty });
+                            toks.push(quote! { #(#cfgs)* #member : #param });
+                        } else {
+                            toks.push(quote! {
+                                #(#cfgs)* #member : ::core::default::Default::default()
+                            });
+                        }
                     }
-                    Fields::Unit => quote! {},
-                },
+                    quote! { #(#toks),* }
+                }
                 _ => {
                     return Err(Error::new(
                         args.span,
@@ -138,36 +221,185 @@ impl ScopeAttr for AttrImplDefault {
             };
 
             let ident = &scope.ident;
-            let (impl_generics, ty_generics, _) = scope.generics.split_for_impl();
-            let wc = clause_to_toks(
-                &args.where_clause,
-                scope.generics.where_clause.as_ref(),
-                &quote! { Default },
-            );
-
-            scope.generated.push(quote! {
-                #[automatically_derived]
-                impl #impl_generics core::default::Default for #ident #ty_generics #wc {
-                    fn default() -> Self {
-                        #ident {
-                            #fields
+            if !params.is_empty() {
+                let validate = validate_or_panic(args.validate.as_ref(), ident, "new_with");
+                let vis = &scope.vis;
+                let (impl_generics, ty_generics, where_clause) =
+                    ImplGenericsBuilder::new(&scope.generics)
+                        .clause(args.where_clause.as_ref())
+                        .split_for_impl(&quote! {});
+                scope.generated.push(quote! {
+                    impl #impl_generics #ident #ty_generics #where_clause {
+                        /// Construct a new instance from fields without a default value
+                        #[must_use]
+                        #vis fn new_with(#(#params),*) -> Self {
+                            let value = #ident {
+                                #fields
+                            };
+                            #validate
+                            value
                         }
                     }
-                }
-            });
+                });
+                return Ok(());
+            }
+
+            let validate = validate_or_panic(args.validate.as_ref(), ident, "default");
+            let toks = ImplGenericsBuilder::new(&scope.generics)
+                .clause(args.where_clause.as_ref())
+                .impl_block(
+                    ident,
+                    &quote! { ::core::default::Default },
+                    &quote! {
+                        fn default() -> Self {
+                            let value = #ident {
+                                #fields
+                            };
+                            #validate
+                            value
+                        }
+                    },
+                );
+            scope.generated.push(toks);
         }
         Ok(())
     }
 }
 
+/// [`ScopeAttr`] rule enabling `#[impl_try_default(E)]` within `impl_scope!`
+///
+/// This generates an inherent method `fn try_default() -> Result<Self, E>`,
+/// constructing the struct from field initializers (which may use `?`).
+/// Fields without an initializer use `Default::default()`.
+///
+/// The method has the same visibility as the struct unless a visibility is
+/// given, e.g. `#[impl_try_default(pub(crate) E)]`.
+pub struct AttrImplTryDefault;
+impl ScopeAttr for AttrImplTryDefault {
+    fn path(&self) -> SimplePath {
+        SimplePath(&["impl_try_default"])
+    }
+
+    fn uses_field_initializers(&self) -> bool {
+        true
+    }
+
+    fn apply(&self, attr: Attribute, scope: &mut Scope) -> Result<()> {
+        let span = attr.span();
+        let args: ImplTryDefault = match attr.meta {
+            Meta::List(list) => list.parse_args()?,
+            meta => {
+                return Err(Error::new_spanned(
+                    meta,
+                    "expected #[impl_try_default(ERROR_TYPE)]",
+                ))
+            }
+        };
+
+        let fields = match &scope.item {
+            ScopeItem::Struct { fields, .. } => {
+                let mut toks = Vec::with_capacity(fields.len());
+                for (member, field) in fields.iter() {
+                    let cfgs = field.cfg_attrs(&member)?;
+                    check_not_skipped(field, "#[impl_try_default]")?;
+                    let value = match field.initializer() {
+                        Some(expr) => quote! { #expr },
+                        None => quote! { ::core::default::Default::default() },
+                    };
+                    toks.push(quote! { #(#cfgs)* #member : #value });
+                }
+                quote! { #(#toks),* }
+            }
+            _ => {
+                return Err(Error::new(
+                    span,
+                    "#[impl_try_default] is only supported on structs",
+                ));
+            }
+        };
+
+        let ident = &scope.ident;
+        let vis = args.vis.as_ref().unwrap_or(&scope.vis);
+        let ty = &args.ty;
+        let validate = args
+            .validate
+            .as_ref()
+            .map(|path| quote! { #path(&value)?; });
+        let (impl_generics, ty_generics, where_clause) = ImplGenericsBuilder::new(&scope.generics)
+            .clause(args.where_clause.as_ref())
+            .split_for_impl(&quote! { ::core::default::Default });
+        scope.generated.push(quote! {
+            impl #impl_generics #ident #ty_generics #where_clause {
+                /// Construct a default instance, or return an error
+                #vis fn try_default() -> ::core::result::Result<Self, #ty> {
+                    let value = #ident {
+                        #fields
+                    };
+                    #validate
+                    ::core::result::Result::Ok(value)
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+struct ImplTryDefault {
+    vis: Option<Visibility>,
+    ty: Type,
+    validate: Option<Path>,
+    where_clause: Option<WhereClause>,
+}
+
+impl Parse for ImplTryDefault {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let vis = match input.parse()? {
+            Visibility::Inherited => None,
+            vis => Some(vis),
+        };
+        let ty = input.parse()?;
+        let mut validate = None;
+        if input.peek(Token![,]) {
+            let _: Token![,] = input.parse()?;
+            validate = parse_validate(input)?;
+            if validate.is_none() {
+                return Err(input.error("expected `validate = PATH`"));
+            }
+        }
+        let mut where_clause = None;
+        if input.peek(Token![where]) {
+            where_clause = Some(input.parse()?);
+        }
+
+        if !input.is_empty() {
+            return Err(Error::new(input.span(), "unexpected"));
+        }
+
+        Ok(ImplTryDefault {
+            vis,
+            ty,
+            validate,
+            where_clause,
+        })
+    }
+}
+
 impl Parse for ImplDefault {
     fn parse(input: ParseStream) -> Result<Self> {
         let mut expr = None;
         let mut where_clause = None;
         let span = input.span();
 
-        if !input.peek(Token![where]) && !input.is_empty() {
+        let mut validate = parse_validate(input)?;
+        if validate.is_none() && !input.peek(Token![where]) && !input.is_empty() {
             expr = Some(input.parse()?);
+            if input.peek(Token![,]) {
+                let _: Token![,] = input.parse()?;
+                validate = parse_validate(input)?;
+                if validate.is_none() {
+                    return Err(input.error("expected `validate = PATH`"));
+                }
+            }
         }
 
         if input.peek(Token![where]) {
@@ -180,6 +412,7 @@ impl Parse for ImplDefault {
 
         Ok(ImplDefault {
             expr,
+            validate,
             where_clause,
             span,
         })
@@ -195,3 +428,13 @@ pub fn find_attr_impl_default(path: &syn::Path) -> Option<&'static dyn ScopeAttr
         .matches(path)
         .then(|| &AttrImplDefault as &dyn ScopeAttr)
 }
+
+/// Helper fn which can be passed to [`Scope::apply_attrs`]
+///
+/// This optionally matches [`AttrImplTryDefault`].
+pub fn find_attr_impl_try_default(path: &syn::Path) -> Option<&'static dyn ScopeAttr> {
+    AttrImplTryDefault
+        .path()
+        .matches(path)
+        .then(|| &AttrImplTryDefault as &dyn ScopeAttr)
+}
@@ -0,0 +1,198 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+//! Implementation of `#[autoimpl(dispatch ...)]`
+
+use crate::generics::Generics;
+use proc_macro2::{TokenStream, TokenTree};
+use proc_macro_error::{emit_call_site_error, emit_error};
+use quote::{quote, ToTokens};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::token::Comma;
+use syn::{parse_quote, FnArg, Ident, Item, ReturnType, Token, TraitItem, Type};
+
+/// Autoimpl of a trait for an enum by dispatch to variant payloads
+///
+/// Each listed variant must be a tuple variant with a single field whose
+/// type implements the trait.
+pub struct Dispatch {
+    generics: Generics,
+    target: Type,
+    variants: Punctuated<Ident, Comma>,
+}
+
+mod parsing {
+    use super::*;
+    use syn::parse::{Error, Parse, ParseStream, Result};
+
+    mod kw {
+        syn::custom_keyword!(dispatch);
+    }
+
+    impl Parse for Dispatch {
+        fn parse(input: ParseStream) -> Result<Self> {
+            let _: kw::dispatch = input.parse()?;
+            let mut generics: Generics = input.parse()?;
+            let target = input.parse()?;
+
+            let content;
+            let brace = syn::braced!(content in input);
+            let variants = content.parse_terminated(Ident::parse, Token![,])?;
+            if variants.is_empty() {
+                return Err(Error::new(
+                    brace.span.join(),
+                    "expected at least one variant",
+                ));
+            }
+
+            let lookahead = input.lookahead1();
+            if lookahead.peek(Token![where]) {
+                generics.where_clause = Some(input.parse()?);
+            } else if !input.is_empty() {
+                return Err(lookahead.error());
+            }
+            if !input.is_empty() {
+                return Err(input.error("unexpected"));
+            }
+
+            Ok(Dispatch {
+                generics,
+                target,
+                variants,
+            })
+        }
+    }
+}
+
+/// True if `tokens` contain the identifier `Self`
+fn mentions_self(tokens: TokenStream) -> bool {
+    tokens.into_iter().any(|tt| match tt {
+        TokenTree::Ident(ident) => ident == "Self",
+        TokenTree::Group(group) => mentions_self(group.stream()),
+        _ => false,
+    })
+}
+
+impl Dispatch {
+    /// Expand over the given `item`
+    ///
+    /// This attribute does not modify the item.
+    /// The caller should append the result to `item` tokens.
+    pub fn expand(self, item: TokenStream) -> TokenStream {
+        let trait_def = match syn::parse2::<Item>(item) {
+            Ok(Item::Trait(item)) => item,
+            Ok(item) => {
+                emit_error!(item, "expected trait");
+                return TokenStream::new();
+            }
+            Err(err) => return err.into_compile_error(),
+        };
+
+        let trait_ident = &trait_def.ident;
+        let (_, trait_generics, _) = trait_def.generics.split_for_impl();
+        let trait_ty = quote! { #trait_ident #trait_generics };
+        let trait_path = {
+            let turbofish = trait_generics.as_turbofish();
+            quote! { #trait_ident #turbofish }
+        };
+        let (impl_generics, where_clause) =
+            self.generics.impl_generics(&trait_def.generics, &trait_ty);
+
+        let mut impl_items = TokenStream::new();
+        for item in &trait_def.items {
+            match item {
+                TraitItem::Fn(item) => {
+                    let cfgs = item
+                        .attrs
+                        .iter()
+                        .filter(|attr| *attr.path() == parse_quote! { cfg });
+
+                    match item.sig.inputs.first() {
+                        Some(FnArg::Receiver(rec)) if rec.colon_token.is_none() => (),
+                        Some(FnArg::Receiver(rec)) => {
+                            emit_error!(rec, "cannot dispatch method with typed receiver");
+                            continue;
+                        }
+                        _ => {
+                            emit_error!(item.sig, "cannot dispatch method without receiver");
+                            continue;
+                        }
+                    }
+
+                    let mut uses_self = item.sig.inputs.iter().skip(1).any(|arg| match arg {
+                        FnArg::Typed(arg) => mentions_self(arg.ty.to_token_stream()),
+                        FnArg::Receiver(_) => false,
+                    });
+                    if let ReturnType::Type(_, ty) = &item.sig.output {
+                        uses_self |= mentions_self(ty.to_token_stream());
+                    }
+                    if uses_self {
+                        emit_error!(
+                            item.sig,
+                            "cannot dispatch method using `Self` other than as receiver"
+                        );
+                        continue;
+                    }
+
+                    let sig = &item.sig;
+                    let ident = &sig.ident;
+                    let params: Vec<_> = sig
+                        .inputs
+                        .iter()
+                        .skip(1)
+                        .filter_map(|arg| match arg {
+                            FnArg::Typed(arg) => Some(&arg.pat),
+                            FnArg::Receiver(_) => None,
+                        })
+                        .collect();
+                    let await_ = sig.asyncness.map(|_| quote! { .await });
+                    let arms = self.variants.iter().map(|variant| {
+                        quote! {
+                            Self::#variant(__inner) => #trait_path::#ident(__inner #(, #params)*) #await_,
+                        }
+                    });
+
+                    impl_items.extend(quote! {
+                        #(#cfgs)*
+                        #[inline]
+                        #sig {
+                            match self {
+                                #(#arms)*
+                            }
+                        }
+                    });
+                }
+                TraitItem::Const(item) => {
+                    emit_call_site_error!(
+                        "cannot autoimpl trait with dispatch";
+                        note = item.span() => "associated constants are not supported";
+                    );
+                }
+                TraitItem::Type(item) => {
+                    emit_call_site_error!(
+                        "cannot autoimpl trait with dispatch";
+                        note = item.span() => "associated types are not supported";
+                    );
+                }
+                TraitItem::Macro(item) => {
+                    emit_error!(item, "unsupported: macro item in trait");
+                }
+                TraitItem::Verbatim(item) => {
+                    emit_error!(item, "unsupported: verbatim item in trait");
+                }
+                _ => (),
+            }
+        }
+
+        let target = &self.target;
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics #trait_ty for #target #where_clause {
+                #impl_items
+            }
+        }
+    }
+}
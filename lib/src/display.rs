@@ -0,0 +1,240 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License in the LICENSE-APACHE file or at:
+//     https://www.apache.org/licenses/LICENSE-2.0
+
+use crate::autoimpl::{is_autoimpl, remove_autoimpl_attrs, ImplArgs, ImplDebug, ImplTrait};
+use crate::fields::{Field, Fields};
+use crate::generics::{ImplGenericsBuilder, WhereClause};
+use crate::{Scope, ScopeAttr, ScopeItem, SimplePath};
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::parse::{Error, Parse, ParseStream, Result};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    Attribute, Expr, FieldMutability, Ident, ItemStruct, LitStr, Member, Meta, PathArguments, Token,
+};
+
+/// [`ScopeAttr`] rule enabling `#[impl_debug_display]` within `impl_scope!`
+///
+/// This implements both `Display` and `Debug` for a struct. `Display` uses
+/// the format string and arguments given, e.g.
+/// `#[impl_debug_display("{x}, {y}")]` or
+/// `#[impl_debug_display("{}", self.x + 1)]`. Within the format string,
+/// named fields are available as bindings by their name and unnamed fields
+/// as `_0`, `_1`, etc. `Debug` is implemented structurally over all fields,
+/// as by `#[autoimpl(Debug)]`, including the effect of field attributes
+/// `#[autoimpl(rename = "NAME")]`, `#[autoimpl(hidden)]` and
+/// `#[autoimpl(debug = EXPR)]`. These field attributes are removed (and
+/// malformed attributes reported) unless the struct also has an `#[autoimpl]`
+/// attribute (which then does so). Fields may be redacted from `Debug` output
+/// with trailing arguments `redact self.FIELD, ...`, e.g.
+/// `#[impl_debug_display("{user}", redact self.password)]`.
+///
+/// An optional `where` clause applies to both implementations; `trait` is
+/// substituted with the trait being implemented.
+pub struct AttrImplDebugDisplay;
+impl ScopeAttr for AttrImplDebugDisplay {
+    fn path(&self) -> SimplePath {
+        SimplePath(&["impl_debug_display"])
+    }
+
+    fn apply(&self, attr: Attribute, scope: &mut Scope) -> Result<()> {
+        let span = attr.span();
+        let args: ImplDebugDisplay = match attr.meta {
+            Meta::List(list) => list.parse_args()?,
+            meta => {
+                return Err(Error::new_spanned(
+                    meta,
+                    "expected #[impl_debug_display(\"FORMAT\", ARGS...)]",
+                ))
+            }
+        };
+
+        let fields = match &scope.item {
+            ScopeItem::Struct { fields, .. } => fields,
+            _ => {
+                return Err(Error::new(
+                    span,
+                    "#[impl_debug_display] is only supported on structs",
+                ));
+            }
+        };
+
+        for member in &args.redacts {
+            if !fields.iter().any(|(m, _)| m == *member) {
+                return Err(Error::new(member.span(), "not a struct field"));
+            }
+        }
+
+        let mut bindings = Vec::with_capacity(fields.len());
+        for (member, field) in fields.iter() {
+            let cfgs = field.cfg_attrs(&member)?;
+            bindings.push(match member {
+                Member::Named(ident) => quote! { #(#cfgs)* #ident },
+                Member::Unnamed(index) => {
+                    let ident = format_ident!("_{}", index.index);
+                    quote! { #ident }
+                }
+            });
+        }
+        let bindings = match fields {
+            Fields::Named(_) => quote! { let Self { #(#bindings),* } = self; },
+            Fields::Unnamed(_) => quote! { let Self(#(#bindings),*) = self; },
+            Fields::Unit => TokenStream::new(),
+        };
+
+        // Debug is implemented as by #[autoimpl(Debug redact ..)]
+        let item = ItemStruct {
+            attrs: vec![],
+            vis: scope.vis.clone(),
+            struct_token: Default::default(),
+            ident: scope.ident.clone(),
+            generics: scope.generics.clone(),
+            fields: syn_fields(fields),
+            semi_token: None,
+        };
+        let impl_args = ImplArgs {
+            path_arguments: PathArguments::None,
+            vis: None,
+            ignores: vec![],
+            ignore_values: vec![],
+            using: None,
+            options: vec![],
+            by: vec![],
+            redacts: args.redacts,
+            fmts: vec![],
+            variant_fmts: vec![],
+            clause: None,
+            bound_override: false,
+            bound_fields: false,
+            bound_used: false,
+            root: None,
+        };
+        let debug = ImplDebug.struct_items(&item, &impl_args);
+
+        // The formatter is hygienic, thus may not be shadowed by a field binding
+        let f = Ident::new("f", Span::mixed_site());
+        let ident = &scope.ident;
+        let fmt = &args.fmt;
+        let fmt_args = args.args.iter();
+        let builder = ImplGenericsBuilder::new(&scope.generics).clause(args.where_clause.as_ref());
+        scope.generated.push(builder.impl_block(
+            ident,
+            &quote! { ::core::fmt::Display },
+            &quote! {
+                #[allow(unused_variables)]
+                fn fmt(&self, #f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                    #bindings
+                    ::core::write!(#f, #fmt #(, #fmt_args)*)
+                }
+            },
+        ));
+        match debug {
+            Ok((path, items)) => scope
+                .generated
+                .push(builder.impl_block(ident, &path, &items)),
+            Err(err) => err.emit(span, span),
+        }
+
+        if !scope.attrs.iter().any(is_autoimpl) {
+            if let ScopeItem::Struct { fields, .. } = &mut scope.item {
+                for field in fields.iter_mut() {
+                    remove_autoimpl_attrs(&mut field.attrs);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Convert fields to [`syn::Fields`], less initializers
+fn syn_fields(fields: &Fields) -> syn::Fields {
+    let convert = |field: &Field| syn::Field {
+        attrs: field.attrs.clone(),
+        vis: field.vis.clone(),
+        mutability: FieldMutability::None,
+        ident: field.ident.clone(),
+        colon_token: field.colon_token,
+        ty: field.ty.clone(),
+    };
+    match fields {
+        Fields::Named(fields) => syn::Fields::Named(syn::FieldsNamed {
+            brace_token: fields.brace_token,
+            named: fields.fields.iter().map(convert).collect(),
+        }),
+        Fields::Unnamed(fields) => syn::Fields::Unnamed(syn::FieldsUnnamed {
+            paren_token: fields.paren_token,
+            unnamed: fields.fields.iter().map(convert).collect(),
+        }),
+        Fields::Unit => syn::Fields::Unit,
+    }
+}
+
+mod kw {
+    syn::custom_keyword!(redact);
+}
+
+struct ImplDebugDisplay {
+    fmt: LitStr,
+    args: Punctuated<Expr, Token![,]>,
+    redacts: Vec<Member>,
+    where_clause: Option<WhereClause>,
+}
+
+impl Parse for ImplDebugDisplay {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let fmt = input.parse()?;
+        let mut args = Punctuated::new();
+        let mut redacts = Vec::new();
+        while input.peek(Token![,]) {
+            let _: Token![,] = input.parse()?;
+            if input.is_empty() || input.peek(Token![where]) {
+                break;
+            }
+            if input.peek(kw::redact) && input.peek2(Token![self]) {
+                let _: kw::redact = input.parse()?;
+                loop {
+                    let _: Token![self] = input.parse()?;
+                    let _: Token![.] = input.parse()?;
+                    redacts.push(input.parse()?);
+
+                    if input.peek(Token![,]) && input.peek2(Token![self]) {
+                        let _: Token![,] = input.parse()?;
+                        continue;
+                    }
+                    break;
+                }
+                break;
+            }
+            args.push(input.parse()?);
+        }
+
+        let mut where_clause = None;
+        if input.peek(Token![where]) {
+            where_clause = Some(input.parse()?);
+        }
+
+        if !input.is_empty() {
+            return Err(Error::new(input.span(), "unexpected"));
+        }
+
+        Ok(ImplDebugDisplay {
+            fmt,
+            args,
+            redacts,
+            where_clause,
+        })
+    }
+}
+
+/// Helper fn which can be passed to [`Scope::apply_attrs`]
+///
+/// This optionally matches [`AttrImplDebugDisplay`].
+pub fn find_attr_impl_debug_display(path: &syn::Path) -> Option<&'static dyn ScopeAttr> {
+    AttrImplDebugDisplay
+        .path()
+        .matches(path)
+        .then(|| &AttrImplDebugDisplay as &dyn ScopeAttr)
+}
@@ -5,12 +5,13 @@
 
 //! Custom version of [`syn`] fields types supporting initializers
 
+use crate::SimplePath;
 use proc_macro2::TokenStream;
 use proc_macro_error::emit_error;
 use quote::{ToTokens, TokenStreamExt};
 use syn::parse::{Parse, ParseStream, Result};
 use syn::punctuated::Punctuated;
-use syn::{token, Attribute, Expr, Ident, Token, Type, Visibility};
+use syn::{token, Attribute, Expr, Ident, Index, Member, Token, Type, Visibility};
 
 /// Struct style: unit/tuple/regular
 #[derive(Debug)]
@@ -39,6 +40,16 @@ pub enum Fields {
 /// Named fields of a struct or struct variant such as `Point { x: f64, y: f64 }`.
 ///
 /// This is a variant of [`syn::FieldsNamed`] supporting field initializers.
+///
+/// When parsing, errors are reported for all malformed fields, not only the
+/// first:
+///
+/// ```
+/// # use impl_tools_lib::fields::FieldsNamed;
+/// let result = syn::parse_str::<FieldsNamed>("{ a: u8 = , b: u8, c 3, d: u8 }");
+/// let errors: Vec<String> = result.unwrap_err().into_iter().map(|e| e.to_string()).collect();
+/// assert_eq!(errors.len(), 2);
+/// ```
 #[derive(Debug)]
 pub struct FieldsNamed {
     /// `{ ... }` around fields
@@ -76,16 +87,171 @@ pub struct Field {
     /// Optional field initializer.
     ///
     /// This is considered legal input when parsing, but not legal output. An
-    /// attribute rule such as [`crate::AttrImplDefault`] must remove the
-    /// initializer before output is generated.
+    /// attribute rule such as [`crate::AttrImplDefault`] must read the
+    /// initializer (see [`crate::ScopeAttr::uses_field_initializers`]) or
+    /// remove it before output is generated.
     pub assign: Option<(Token![=], Expr)>,
 }
 
+impl Fields {
+    /// Number of fields
+    pub fn len(&self) -> usize {
+        match self {
+            Fields::Named(FieldsNamed { fields, .. })
+            | Fields::Unnamed(FieldsUnnamed { fields, .. }) => fields.len(),
+            Fields::Unit => 0,
+        }
+    }
+
+    /// True if there are no fields
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over fields, each paired with its [`Member`]
+    ///
+    /// This yields nothing for [`Fields::Unit`].
+    pub fn iter(&self) -> impl Iterator<Item = (Member, &Field)> {
+        let fields = match self {
+            Fields::Named(FieldsNamed { fields, .. })
+            | Fields::Unnamed(FieldsUnnamed { fields, .. }) => Some(fields),
+            Fields::Unit => None,
+        };
+        fields
+            .into_iter()
+            .flat_map(|fields| fields.iter())
+            .enumerate()
+            .map(|(i, field)| {
+                let member = match field.ident {
+                    Some(ref ident) => Member::Named(ident.clone()),
+                    None => Member::Unnamed(Index::from(i)),
+                };
+                (member, field)
+            })
+    }
+
+    /// Iterate mutably over fields
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Field> {
+        let fields = match self {
+            Fields::Named(FieldsNamed { fields, .. })
+            | Fields::Unnamed(FieldsUnnamed { fields, .. }) => Some(fields),
+            Fields::Unit => None,
+        };
+        fields.into_iter().flat_map(|fields| fields.iter_mut())
+    }
+
+    /// Retain only fields for which `f` returns true
+    ///
+    /// Note that removing an unnamed field changes the index of following fields.
+    ///
+    /// ```
+    /// # use impl_tools_lib::Scope;
+    /// # use quote::ToTokens;
+    /// let mut scope: Scope = syn::parse_quote! {
+    ///     struct S(u8, #[skip] f32, char);
+    /// };
+    /// scope.fields_mut().unwrap().retain(|field| field.attrs.is_empty());
+    /// assert_eq!(
+    ///     scope.to_token_stream().to_string(),
+    ///     quote::quote! { struct S(u8, char); }.to_string(),
+    /// );
+    /// ```
+    pub fn retain(&mut self, mut f: impl FnMut(&Field) -> bool) {
+        let fields = match self {
+            Fields::Named(FieldsNamed { fields, .. })
+            | Fields::Unnamed(FieldsUnnamed { fields, .. }) => fields,
+            Fields::Unit => return,
+        };
+        *fields = std::mem::take(fields)
+            .into_pairs()
+            .filter(|pair| f(pair.value()))
+            .collect();
+    }
+}
+
+impl Field {
+    /// Construct a named field (without attributes or initializer)
+    pub fn new_named(vis: Visibility, ident: Ident, ty: Type) -> Self {
+        Field {
+            attrs: vec![],
+            vis,
+            ident: Some(ident),
+            colon_token: Some(Default::default()),
+            ty,
+            assign: None,
+        }
+    }
+
+    /// Construct an unnamed field (without attributes or initializer)
+    pub fn new_unnamed(vis: Visibility, ty: Type) -> Self {
+        Field {
+            attrs: vec![],
+            vis,
+            ident: None,
+            colon_token: None,
+            ty,
+            assign: None,
+        }
+    }
+}
+
+impl Field {
+    /// Get the field initializer, if any
+    pub fn initializer(&self) -> Option<&Expr> {
+        self.assign.as_ref().map(|assign| &assign.1)
+    }
+
+    /// Find a helper attribute matching `path`
+    ///
+    /// Helper attributes are attributes on fields which are read by a
+    /// [`ScopeAttr`](crate::ScopeAttr) rule, e.g. `#[impl_default(..)]`.
+    /// These are removed from the output by [`Scope::apply_attrs`](crate::Scope::apply_attrs)
+    /// when the path matches a rule.
+    pub fn helper_attr(&self, path: &SimplePath) -> Option<&Attribute> {
+        self.attrs.iter().find(|attr| path.matches(attr.path()))
+    }
+
+    /// Get the `#[cfg(..)]` attributes of the field
+    ///
+    /// Generated code referring to the field (e.g. in a struct expression)
+    /// should be subject to the same attributes. Since configuring out a tuple
+    /// field changes the indices of following fields, `#[cfg]` is an error
+    /// where `member` is unnamed.
+    pub fn cfg_attrs(&self, member: &Member) -> Result<Vec<&Attribute>> {
+        let cfgs = cfg_attrs(&self.attrs);
+        check_cfgs(member, &cfgs)?;
+        Ok(cfgs)
+    }
+}
+
+/// Get the `#[cfg(..)]` attributes among `attrs`
+pub(crate) fn cfg_attrs(attrs: &[Attribute]) -> Vec<&Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .collect()
+}
+
+/// Check the `#[cfg(..)]` attributes `cfgs` of the field `member`
+///
+/// This is an error where `member` is unnamed (see [`Field::cfg_attrs`]).
+pub(crate) fn check_cfgs(member: &Member, cfgs: &[&Attribute]) -> Result<()> {
+    match (member, cfgs.first()) {
+        (Member::Unnamed(_), Some(attr)) => Err(syn::Error::new_spanned(
+            attr,
+            "`#[cfg]` is not supported on tuple fields",
+        )),
+        _ => Ok(()),
+    }
+}
+
 // Copied from syn, modified
 pub(crate) mod parsing {
     use super::*;
+    use proc_macro2::TokenTree;
     use syn::ext::IdentExt;
-    use syn::{braced, parenthesized, WhereClause};
+    use syn::parse::discouraged::Speculative;
+    use syn::{braced, parenthesized, Error, WhereClause};
 
     impl Parse for FieldsNamed {
         fn parse(input: ParseStream) -> Result<Self> {
@@ -93,7 +259,7 @@ pub(crate) mod parsing {
             let brace_token = braced!(content in input);
             Ok(FieldsNamed {
                 brace_token,
-                fields: content.parse_terminated(Field::parse_named, Token![,])?,
+                fields: parse_fields(&content, Field::parse_named)?,
             })
         }
     }
@@ -104,7 +270,7 @@ pub(crate) mod parsing {
             let paren_token = parenthesized!(content in input);
             Ok(FieldsUnnamed {
                 paren_token,
-                fields: content.parse_terminated(Field::parse_unnamed, Token![,])?,
+                fields: parse_fields(&content, Field::parse_unnamed)?,
             })
         }
     }
@@ -147,6 +313,79 @@ pub(crate) mod parsing {
         }
     }
 
+    /// Parse a comma-separated list of fields
+    ///
+    /// Unlike `ParseBuffer::parse_terminated`, parsing does not stop at the
+    /// first malformed field: the remainder of that field is skipped and
+    /// errors from all fields are reported together.
+    fn parse_fields(
+        input: ParseStream,
+        parser: fn(ParseStream) -> Result<Field>,
+    ) -> Result<Punctuated<Field, Token![,]>> {
+        let mut fields = Punctuated::new();
+        let mut error: Option<Error> = None;
+        while !input.is_empty() {
+            let fork = input.fork();
+            match parser(&fork).and_then(|field| {
+                if !fork.is_empty() && !fork.peek(Token![,]) {
+                    return Err(fork.error("expected `,`"));
+                }
+                Ok(field)
+            }) {
+                Ok(field) => {
+                    input.advance_to(&fork);
+                    fields.push_value(field);
+                }
+                Err(err) => {
+                    match error.as_mut() {
+                        Some(error) => error.combine(err),
+                        None => error = Some(err),
+                    }
+                    skip_field(input)?;
+                }
+            }
+            if input.is_empty() {
+                break;
+            }
+            let comma: Token![,] = input.parse()?;
+            if !fields.empty_or_trailing() {
+                fields.push_punct(comma);
+            }
+        }
+        match error {
+            Some(error) => Err(error),
+            None => Ok(fields),
+        }
+    }
+
+    /// Skip tokens up to the next top-level `,` (which is not consumed)
+    ///
+    /// Commas within generic arguments (e.g. `HashMap<K, V>`) do not end a
+    /// field; `<` and `>` are assumed to be balanced.
+    fn skip_field(input: ParseStream) -> Result<()> {
+        let mut depth = 0usize;
+        let mut prev_joint = false;
+        while !input.is_empty() {
+            if depth == 0 && input.peek(Token![,]) {
+                break;
+            }
+            let tt: TokenTree = input.parse()?;
+            if let TokenTree::Punct(punct) = &tt {
+                match punct.as_char() {
+                    '<' => depth += 1,
+                    // Ignore the `>` of `->` and `=>`
+                    '>' if !prev_joint => depth = depth.saturating_sub(1),
+                    _ => (),
+                }
+                prev_joint = matches!(punct.as_char(), '-' | '=')
+                    && punct.spacing() == proc_macro2::Spacing::Joint;
+            } else {
+                prev_joint = false;
+            }
+        }
+        Ok(())
+    }
+
     pub(crate) fn data_struct(
         input: ParseStream,
     ) -> Result<(Option<WhereClause>, Fields, Option<Token![;]>)> {
@@ -186,7 +425,7 @@ pub(crate) mod parsing {
     fn parse_braced(input: ParseStream) -> Result<FieldsNamed> {
         let content;
         let brace_token = braced!(content in input);
-        let fields = content.parse_terminated(Field::parse_named, Token![,])?;
+        let fields = parse_fields(&content, Field::parse_named)?;
         Ok(FieldsNamed {
             brace_token,
             fields,
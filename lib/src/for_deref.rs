@@ -6,47 +6,31 @@
 //! Implementation of the `#[autoimpl]` attribute
 
 use crate::generics::{GenericParam, Generics, TypeParamBound, WherePredicate};
-use proc_macro2::{Span, TokenStream};
+use proc_macro2::{Span, TokenStream, TokenTree};
 use proc_macro_error::{emit_call_site_error, emit_error};
 use quote::{quote, ToTokens, TokenStreamExt};
-use std::{iter, slice};
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::token::{Comma, Eq, PathSep};
-use syn::{parse_quote, Attribute, FnArg, Ident, Item, Token, TraitItem, Type, TypePath};
+use syn::{parse_quote, FnArg, Ident, Item, Token, TraitItem, Type, TypePath};
 
 /// Autoimpl for types supporting `Deref`
 pub struct ForDeref {
     generics: Generics,
     definitive: Ident,
     targets: Punctuated<Type, Comma>,
-}
-
-// Copied from syn
-trait FilterAttrs<'a> {
-    type Ret: Iterator<Item = &'a Attribute>;
-
-    fn outer(self) -> Self::Ret;
-}
-
-impl<'a> FilterAttrs<'a> for &'a [Attribute] {
-    type Ret = iter::Filter<slice::Iter<'a, Attribute>, fn(&&Attribute) -> bool>;
-
-    fn outer(self) -> Self::Ret {
-        fn is_outer(attr: &&Attribute) -> bool {
-            match attr.style {
-                syn::AttrStyle::Outer => true,
-                syn::AttrStyle::Inner(_) => false,
-            }
-        }
-        self.iter().filter(is_outer)
-    }
+    report: bool,
 }
 
 mod parsing {
     use super::*;
     use syn::parse::{Error, Parse, ParseStream, Result};
 
+    mod kw {
+        syn::custom_keyword!(report);
+        syn::custom_keyword!(with);
+    }
+
     impl Parse for ForDeref {
         fn parse(input: ParseStream) -> Result<Self> {
             let _ = input.parse::<Token![for]>()?;
@@ -54,7 +38,14 @@ mod parsing {
 
             let targets = Punctuated::parse_separated_nonempty(input)?;
 
+            let mut report = false;
             let mut lookahead = input.lookahead1();
+            if lookahead.peek(kw::with) {
+                let _: kw::with = input.parse()?;
+                let _: kw::report = input.parse()?;
+                report = true;
+                lookahead = input.lookahead1();
+            }
             if lookahead.peek(Token![where]) {
                 generics.where_clause = Some(input.parse()?);
                 lookahead = input.lookahead1();
@@ -109,11 +100,167 @@ mod parsing {
                 generics,
                 definitive,
                 targets,
+                report,
             })
         }
     }
 }
 
+/// True if `ty` is known to support `Deref` but not `DerefMut`
+///
+/// This matches `&T`, `Rc<T>` and `Arc<T>` (by last path segment).
+fn is_read_only(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(rf) => rf.mutability.is_none(),
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|seg| seg.ident == "Rc" || seg.ident == "Arc")
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Generate documentation listing forwarded and skipped methods
+///
+/// Methods are forwarded where `is_forwarded` returns true. Methods with bound
+/// `Self: Sized` are listed again from `sized`, a tuple of these method names,
+/// the definitive type and whether that is sized (if so, these are forwarded).
+fn report_doc(
+    methods: &[(String, Option<bool>)],
+    is_forwarded: impl Fn(Option<bool>) -> bool,
+    sized: (&[String], &Ident, bool),
+) -> String {
+    let (sized, definitive, definitive_sized) = sized;
+    let list = |forwarded: bool| {
+        let names: Vec<_> = methods
+            .iter()
+            .filter(|(_, is_mut)| is_forwarded(*is_mut) == forwarded)
+            .map(|(name, _)| format!("`{name}`"))
+            .collect();
+        if names.is_empty() {
+            "(none)".to_string()
+        } else {
+            names.join(", ")
+        }
+    };
+    let mut doc = format!(
+        "Forwarded methods: {}.\n\nMethods using the default implementation: {}.",
+        list(true),
+        list(false)
+    );
+    if !sized.is_empty() {
+        let names: Vec<_> = sized.iter().map(|name| format!("`{name}`")).collect();
+        let reason = if definitive_sized {
+            format!("forwarded since `{definitive}` is sized")
+        } else {
+            format!("using the default implementation since `{definitive}` may be unsized")
+        };
+        doc.push_str(&format!(
+            "\n\nMethods with bound `Self: Sized` ({reason}): {}.",
+            names.join(", ")
+        ));
+    }
+    doc
+}
+
+/// True if the only bounds on `Self` in the where clause of `gen` are `Sized`
+fn is_sized_bound_on_self(gen: &syn::Generics) -> bool {
+    let mut found = false;
+    if let Some(ref clause) = gen.where_clause {
+        for pred in clause.predicates.iter() {
+            if let syn::WherePredicate::Type(ref ty) = pred {
+                if let Type::Path(ref bounded) = ty.bounded_ty {
+                    if bounded.qself.is_none() && bounded.path.is_ident("Self") {
+                        for bound in ty.bounds.iter() {
+                            match bound {
+                                syn::TypeParamBound::Trait(tb)
+                                    if tb.path.is_ident("Sized")
+                                        && matches!(tb.modifier, syn::TraitBoundModifier::None) =>
+                                {
+                                    found = true
+                                }
+                                syn::TypeParamBound::Trait(_) => return false,
+                                _ => (),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    found
+}
+
+/// True if a method with bound `Self: Sized` may be forwarded
+///
+/// This requires that `sig` takes `self` by reference and that `Self` does
+/// not appear elsewhere in the signature (other than in the bound itself).
+fn is_forwardable_sized(sig: &syn::Signature) -> bool {
+    fn contains_self(tokens: TokenStream) -> bool {
+        tokens.into_iter().any(|tt| match tt {
+            TokenTree::Ident(ident) => ident == "Self",
+            TokenTree::Group(group) => contains_self(group.stream()),
+            _ => false,
+        })
+    }
+
+    let mut inputs = sig.inputs.iter();
+    match inputs.next() {
+        Some(FnArg::Receiver(rec)) if rec.reference.is_some() => (),
+        _ => return false,
+    }
+    if inputs.any(|arg| contains_self(arg.to_token_stream()))
+        || contains_self(sig.output.to_token_stream())
+        || contains_self(sig.generics.params.to_token_stream())
+    {
+        return false;
+    }
+    if let Some(ref clause) = sig.generics.where_clause {
+        for pred in clause.predicates.iter() {
+            if let syn::WherePredicate::Type(ref ty) = pred {
+                if let Type::Path(ref bounded) = ty.bounded_ty {
+                    if bounded.qself.is_none() && bounded.path.is_ident("Self") {
+                        continue;
+                    }
+                }
+            }
+            if contains_self(pred.to_token_stream()) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// True if `generics` has a `?Sized` bound on `param`
+fn is_maybe_unsized(generics: &Generics, param: &Ident) -> bool {
+    let is_maybe = |bound: &TypeParamBound| {
+        matches!(bound, TypeParamBound::Other(syn::TypeParamBound::Trait(tb))
+            if matches!(tb.modifier, syn::TraitBoundModifier::Maybe(_)))
+    };
+    for gp in &generics.params {
+        if let GenericParam::Type(gp) = gp {
+            if gp.ident == *param && gp.bounds.iter().any(is_maybe) {
+                return true;
+            }
+        }
+    }
+    if let Some(clause) = generics.where_clause.as_ref() {
+        for pred in &clause.predicates {
+            if let WherePredicate::Type(pred) = pred {
+                if let Type::Path(TypePath { qself: None, path }) = &pred.bounded_ty {
+                    if path.is_ident(param) && pred.bounds.iter().any(is_maybe) {
+                        return true;
+                    }
+                }
+            }
+        }
+    }
+    false
+}
+
 fn has_bound_on_self(gen: &syn::Generics) -> bool {
     if let Some(ref clause) = gen.where_clause {
         for pred in clause.predicates.iter() {
@@ -144,6 +291,26 @@ impl ForDeref {
     ///
     /// This attribute does not modify the item.
     /// The caller should append the result to `item` tokens.
+    ///
+    /// With `with report`, the documentation of each impl lists methods with
+    /// bound `Self: Sized` taking `self` by reference and whether these are
+    /// forwarded:
+    /// ```
+    /// # use impl_tools_lib::ForDeref;
+    /// # use quote::quote;
+    /// let attr: ForDeref = syn::parse_quote! { for<T: trait + ?Sized> &T, Box<T> with report };
+    /// let item = quote! {
+    ///     trait Shape {
+    ///         fn area(&self) -> f64;
+    ///         fn doubled(&self) -> f64 where Self: Sized {
+    ///             2.0 * self.area()
+    ///         }
+    ///     }
+    /// };
+    /// let toks = attr.expand(item).to_string();
+    /// assert!(toks.contains("Methods with bound `Self: Sized` (using the default \
+    ///     implementation since `T` may be unsized): `doubled`."));
+    /// ```
     pub fn expand(self, item: TokenStream) -> TokenStream {
         let trait_def = match syn::parse2::<Item>(item) {
             Ok(Item::Trait(item)) => item,
@@ -161,10 +328,21 @@ impl ForDeref {
             ErrorEmitted,
         }
         let mut bound = Bound::None;
+        // Bound required by read-only targets (those not supporting `DerefMut`)
+        let mut bound_ro = Bound::None;
+        // Methods taking `&mut self` without a default implementation
+        let mut mut_methods = vec![];
+        // Method names, with `None` if skipped or `Some(is_mut)` if forwarded
+        let mut methods: Vec<(String, Option<bool>)> = vec![];
+        // Names of methods whose only bound on `Self` is `Self: Sized` and which
+        // may be forwarded when the definitive type is sized
+        let mut sized_methods: Vec<String> = vec![];
 
         let trait_ident = &trait_def.ident;
         let (_, trait_generics, _) = trait_def.generics.split_for_impl();
         let trait_ty = quote! { #trait_ident #trait_generics };
+        // Methods with bound `Self: Sized` may be forwarded to a sized type
+        let definitive_sized = !is_maybe_unsized(&self.generics, &self.definitive);
         let ty_generics = self.generics.ty_generics(&trait_def.generics);
         let (impl_generics, where_clause) =
             self.generics.impl_generics(&trait_def.generics, &trait_ty);
@@ -174,8 +352,12 @@ impl ForDeref {
 
         // Tokenize, like ToTokens impls for syn::TraitItem*, but for definition
         let mut impl_items = TokenStream::new();
-        let tokens = &mut impl_items;
+        // As impl_items, less methods taking `&mut self`
+        let mut impl_items_ro = TokenStream::new();
         for item in &trait_def.items {
+            let mut item_toks = TokenStream::new();
+            let tokens = &mut item_toks;
+            let mut is_mut_method = false;
             match item {
                 TraitItem::Const(item) => {
                     for attr in item.attrs.iter() {
@@ -203,24 +385,39 @@ impl ForDeref {
                         }
                     }
 
-                    if has_bound_on_self(&item.sig.generics) {
+                    let sized_only = is_sized_bound_on_self(&item.sig.generics)
+                        && is_forwardable_sized(&item.sig);
+                    if sized_only {
+                        sized_methods.push(item.sig.ident.to_string());
+                    }
+                    if has_bound_on_self(&item.sig.generics) && !(sized_only && definitive_sized) {
                         // If the method has a bound on Self, we cannot use a dereferencing
                         // implementation since the definitive type is not guaranteed to match
-                        // the bound (we also cannot add a bound).
+                        // the bound (we also cannot add a bound). Exception: `Self: Sized`
+                        // is satisfied by a sized definitive type, provided that the method
+                        // takes `self` by reference and does not otherwise mention `Self`.
 
                         if item.default.is_none() {
-                            emit_call_site_error!(
-                                "cannot autoimpl trait with Deref";
-                                note = item.span() => "method has a bound on Self and no default implementation";
-                            );
+                            if sized_only {
+                                emit_call_site_error!(
+                                    "cannot autoimpl trait with Deref";
+                                    note = item.span() => "method has bound `Self: Sized` and no default implementation, but definitive type `{}` may be unsized", definitive_ty;
+                                );
+                            } else {
+                                emit_call_site_error!(
+                                    "cannot autoimpl trait with Deref";
+                                    note = item.span() => "method has a bound on Self and no default implementation";
+                                );
+                            }
                         }
 
+                        methods.push((item.sig.ident.to_string(), None));
                         continue;
                     }
 
                     item.sig.to_tokens(tokens);
 
-                    bound = bound.max(match item.sig.inputs.first() {
+                    let item_bound = match item.sig.inputs.first() {
                         Some(FnArg::Receiver(rec)) => {
                             if rec.reference.is_some() {
                                 Bound::Deref(rec.mutability.is_some())
@@ -239,7 +436,18 @@ impl ForDeref {
                             _ => Bound::None,
                         },
                         _ => Bound::None,
-                    });
+                    };
+                    bound = bound.max(item_bound);
+                    if item_bound == Bound::Deref(true) {
+                        is_mut_method = true;
+                        if item.default.is_none() {
+                            mut_methods.push(item);
+                        }
+                    } else {
+                        bound_ro = bound_ro.max(item_bound);
+                    }
+
+                    methods.push((item.sig.ident.to_string(), Some(is_mut_method)));
 
                     let ident = &item.sig.ident;
                     let params = item.sig.inputs.iter().map(|arg| match arg {
@@ -293,46 +501,135 @@ impl ForDeref {
                 */
                 _ => (),
             }
+
+            impl_items.append_all(item_toks.clone());
+            if !is_mut_method {
+                impl_items_ro.append_all(item_toks);
+            }
         }
 
-        let mut toks = TokenStream::new();
-        match bound {
-            Bound::None => (),
-            Bound::Deref(is_mut) => {
-                // Emit a bound to improve error messages (see issue 27)
-                let bound = match is_mut {
-                    false => quote! { ::core::ops::Deref },
-                    true => quote! { ::core::ops::DerefMut },
-                };
-
-                let target_impls = self.targets.iter().map(|target| {
-                    quote! {
-                        impl #impl_generics TargetMustImplDeref #ty_generics for #target
-                        #where_clause {}
-                    }
-                });
+        let (targets_ro, targets): (Vec<_>, Vec<_>) =
+            self.targets.into_iter().partition(is_read_only);
 
-                toks.append_all(quote! {
-                    #[automatically_derived]
-                    const _: () = {
-                        trait TargetMustImplDeref #impl_generics: #bound<Target = #definitive_ty>
-                        #where_clause {}
+        if !targets_ro.is_empty() {
+            for method in mut_methods {
+                emit_error!(
+                    method.sig.span(),
+                    "method taking `&mut self` requires a default implementation for read-only targets";
+                    note = targets_ro[0].span() => "read-only target (does not support `DerefMut`)";
+                );
+            }
+        }
 
-                        #(#target_impls)*
+        let mut toks = TokenStream::new();
+        for (bound, targets) in [(bound, &targets), (bound_ro, &targets_ro)] {
+            if targets.is_empty() {
+                continue;
+            }
+            match bound {
+                Bound::None => (),
+                Bound::Deref(is_mut) => {
+                    // Emit a bound to improve error messages (see issue 27)
+                    let bound = match is_mut {
+                        false => quote! { ::core::ops::Deref },
+                        true => quote! { ::core::ops::DerefMut },
                     };
-                });
+
+                    let target_impls = targets.iter().map(|target| {
+                        quote! {
+                            impl #impl_generics TargetMustImplDeref #ty_generics for #target
+                            #where_clause {}
+                        }
+                    });
+
+                    toks.append_all(quote! {
+                        #[automatically_derived]
+                        const _: () = {
+                            trait TargetMustImplDeref #impl_generics: #bound<Target = #definitive_ty>
+                            #where_clause {}
+
+                            #(#target_impls)*
+                        };
+                    });
+                }
+                Bound::ErrorEmitted => return toks,
             }
-            Bound::ErrorEmitted => return toks,
         }
 
-        for target in self.targets {
+        let (doc, doc_ro) = if self.report {
+            let sized = (&sized_methods[..], &definitive_ty, definitive_sized);
+            let doc = report_doc(&methods, |is_mut| is_mut.is_some(), sized);
+            let doc_ro = report_doc(&methods, |is_mut| is_mut == Some(false), sized);
+            (quote! { #[doc = #doc] }, quote! { #[doc = #doc_ro] })
+        } else {
+            (TokenStream::new(), TokenStream::new())
+        };
+
+        for target in targets {
             toks.append_all(quote! {
+                #doc
                 #[automatically_derived]
                 impl #impl_generics #trait_ty for #target #where_clause {
                     #impl_items
                 }
             });
         }
+        for target in targets_ro {
+            toks.append_all(quote! {
+                #doc_ro
+                #[automatically_derived]
+                impl #impl_generics #trait_ty for #target #where_clause {
+                    #impl_items_ro
+                }
+            });
+        }
         toks
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn report() {
+        let methods = vec![
+            ("value".to_string(), Some(false)),
+            ("reset".to_string(), Some(true)),
+            ("name".to_string(), None),
+        ];
+        let sized = vec!["bounded".to_string()];
+        let ident = Ident::new("T", Span::call_site());
+
+        let doc = report_doc(&methods, |is_mut| is_mut.is_some(), (&sized, &ident, true));
+        assert_eq!(
+            doc,
+            "Forwarded methods: `value`, `reset`.\n\n\
+            Methods using the default implementation: `name`.\n\n\
+            Methods with bound `Self: Sized` (forwarded since `T` is sized): `bounded`."
+        );
+
+        let doc = report_doc(
+            &methods,
+            |is_mut| is_mut == Some(false),
+            (&sized, &ident, false),
+        );
+        assert_eq!(
+            doc,
+            "Forwarded methods: `value`.\n\n\
+            Methods using the default implementation: `reset`, `name`.\n\n\
+            Methods with bound `Self: Sized` (using the default implementation \
+            since `T` may be unsized): `bounded`."
+        );
+
+        let doc = report_doc(
+            &methods[..1],
+            |is_mut| is_mut.is_some(),
+            (&[], &ident, true),
+        );
+        assert_eq!(
+            doc,
+            "Forwarded methods: `value`.\n\nMethods using the default implementation: (none)."
+        );
+    }
+}
@@ -12,12 +12,13 @@ use syn::punctuated::{Pair, Punctuated};
 use syn::token;
 use syn::{Attribute, ConstParam, LifetimeParam, PredicateLifetime};
 use syn::{BoundLifetimes, Ident, Lifetime, Token, Type};
+use syn::{ImplGenerics, TypeGenerics};
 
 /// Lifetimes and type parameters attached an item
 ///
 /// This is a custom variant of [`syn::Generics`]
 /// which supports `trait` as a parameter bound.
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct Generics {
     /// `<`
     pub lt_token: Option<Token![<]>,
@@ -29,17 +30,6 @@ pub struct Generics {
     pub where_clause: Option<WhereClause>,
 }
 
-impl Default for Generics {
-    fn default() -> Self {
-        Generics {
-            lt_token: None,
-            params: Punctuated::new(),
-            gt_token: None,
-            where_clause: None,
-        }
-    }
-}
-
 /// A generic type parameter, lifetime, or const generic
 ///
 /// This is a custom variant of [`syn::GenericParam`]
@@ -78,7 +68,7 @@ pub struct TypeParam {
 /// A trait or lifetime used as a bound on a type parameter.
 ///
 /// This is a superset of [`syn::TypeParamBound`].
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum TypeParamBound {
     /// `trait` used as a bound (substituted for the trait name by [`ToTokensSubst`])
     TraitSubst(Token![trait]),
@@ -90,7 +80,7 @@ pub enum TypeParamBound {
 ///
 /// This is a custom variant of [`syn::WhereClause`]
 /// which supports `trait` as a parameter bound.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct WhereClause {
     /// `where`
     pub where_token: Token![where],
@@ -103,7 +93,7 @@ pub struct WhereClause {
 /// This is a custom variant of [`syn::WherePredicate`]
 /// which supports `trait` as a parameter bound.
 #[allow(clippy::large_enum_variant)]
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum WherePredicate {
     /// A type predicate in a `where` clause: `for<'c> Foo<'c>: Trait<'c>`.
     Type(PredicateType),
@@ -116,7 +106,7 @@ pub enum WherePredicate {
 ///
 /// This is a custom variant of [`syn::PredicateType`]
 /// which supports `trait` as a parameter bound.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct PredicateType {
     /// Any lifetimes from a `for` binding
     pub lifetimes: Option<BoundLifetimes>,
@@ -620,10 +610,20 @@ impl Generics {
 ///
 /// This merges a [`WhereClause`] with a [`syn::WhereClause`], replacing any
 /// instance of `trait` as a parameter bound in `wc` with `subst`.
+///
+/// See also [`ImplGenericsBuilder`].
 pub fn clause_to_toks(
     wc: &Option<WhereClause>,
     item_wc: Option<&syn::WhereClause>,
     subst: &TokenStream,
+) -> TokenStream {
+    merge_clauses(wc.as_ref(), item_wc, subst)
+}
+
+fn merge_clauses(
+    wc: Option<&WhereClause>,
+    item_wc: Option<&syn::WhereClause>,
+    subst: &TokenStream,
 ) -> TokenStream {
     match (wc, item_wc) {
         (None, None) => quote! {},
@@ -643,3 +643,93 @@ pub fn clause_to_toks(
         }
     }
 }
+
+/// Builder for the generics of a trait implementation
+///
+/// This merges the generics (and where clause) of an item with an optional
+/// attribute-supplied [`WhereClause`], replacing any instance of `trait` as a
+/// parameter bound with the path of the trait being implemented. This is the
+/// same logic used by `#[autoimpl]`, thus extensions using this builder
+/// generate impls with consistent bounds.
+///
+/// # Example
+///
+/// ```
+/// # use impl_tools_lib::generics::{ImplGenericsBuilder, WhereClause};
+/// # use quote::quote;
+/// let item: syn::ItemStruct = syn::parse_quote! {
+///     struct Pair<T>(T, T) where T: Copy;
+/// };
+/// let clause: WhereClause = syn::parse_quote! { where T: trait };
+///
+/// let toks = ImplGenericsBuilder::new(&item.generics)
+///     .clause(Some(&clause))
+///     .impl_block(&item.ident, &quote! { MyTrait }, &quote! {});
+/// assert_eq!(
+///     toks.to_string(),
+///     quote! {
+///         #[automatically_derived]
+///         impl<T> MyTrait for Pair<T> where T: Copy, T: MyTrait {}
+///     }.to_string(),
+/// );
+/// ```
+pub struct ImplGenericsBuilder<'a> {
+    item_generics: &'a syn::Generics,
+    clause: Option<&'a WhereClause>,
+}
+
+impl<'a> ImplGenericsBuilder<'a> {
+    /// Construct, using the generics of the item being implemented
+    pub fn new(item_generics: &'a syn::Generics) -> Self {
+        ImplGenericsBuilder {
+            item_generics,
+            clause: None,
+        }
+    }
+
+    /// Set an additional `where` clause (usually supplied by an attribute)
+    ///
+    /// Predicates are appended to those of the item.
+    pub fn clause(mut self, clause: Option<&'a WhereClause>) -> Self {
+        self.clause = clause;
+        self
+    }
+
+    /// Generate (`impl_generics`, `ty_generics`, `where_clause`)
+    ///
+    /// This is the equivalent of [`syn::Generics::split_for_impl`], except
+    /// that the where clause is merged and any instance of `trait` as a
+    /// parameter bound is replaced by `subst`.
+    pub fn split_for_impl(
+        &self,
+        subst: &TokenStream,
+    ) -> (ImplGenerics<'a>, TypeGenerics<'a>, TokenStream) {
+        let (impl_generics, ty_generics, item_wc) = self.item_generics.split_for_impl();
+        let wc = merge_clauses(self.clause, item_wc, subst);
+        (impl_generics, ty_generics, wc)
+    }
+
+    /// Generate a complete trait implementation
+    ///
+    /// Output has the form:
+    /// ```ignore
+    /// #[automatically_derived]
+    /// impl #impl_generics #trait_path for #type_ident #ty_generics #where_clause {
+    ///     #items
+    /// }
+    /// ```
+    pub fn impl_block(
+        &self,
+        type_ident: &Ident,
+        trait_path: &TokenStream,
+        items: &TokenStream,
+    ) -> TokenStream {
+        let (impl_generics, ty_generics, wc) = self.split_for_impl(trait_path);
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics #trait_path for #type_ident #ty_generics #wc {
+                #items
+            }
+        }
+    }
+}
@@ -15,19 +15,72 @@
 #![allow(clippy::style)]
 
 pub mod autoimpl;
+mod constructor;
 mod default;
+mod dispatch;
+mod display;
 pub mod fields;
 mod for_deref;
 pub mod generics;
 mod scope;
 mod singleton;
 
-pub use default::{find_attr_impl_default, AttrImplDefault, ImplDefault};
+pub use constructor::{find_attr_impl_new, AttrImplNew};
+pub use default::{
+    find_attr_impl_default, find_attr_impl_try_default, AttrImplDefault, AttrImplTryDefault,
+    ImplDefault,
+};
+pub use dispatch::Dispatch;
+pub use display::{find_attr_impl_debug_display, AttrImplDebugDisplay};
 pub use for_deref::ForDeref;
 use proc_macro2::Span;
 pub use scope::{Scope, ScopeAttr, ScopeItem};
 pub use singleton::{Singleton, SingletonField, SingletonScope};
-use syn::Ident;
+use syn::{Attribute, Ident};
+
+/// Run an expansion, returning its output as formatted source code
+///
+/// The output of `expand` (for example [`autoimpl::ImplTraits::expand`] or
+/// the tokens of a [`Scope`]) is parsed as a sequence of items and formatted
+/// by [`prettyplease`](https://docs.rs/prettyplease). This is intended for
+/// documentation examples, snapshot tests and debugging. Output which does
+/// not parse as items is reported as an error.
+///
+/// Requires feature `pretty`.
+///
+/// ```
+/// # use impl_tools_lib::{autoimpl, expand_pretty};
+/// let attr = match syn::parse_quote! { Clone } {
+///     autoimpl::Attr::ImplTraits(attr) => attr,
+///     _ => unreachable!(),
+/// };
+/// let item = quote::quote! { struct Foo; };
+/// let find_impl = |path: &syn::Path| {
+///     autoimpl::STD_IMPLS
+///         .iter()
+///         .cloned()
+///         .find(|impl_| impl_.path().matches_ident_or_path(path))
+/// };
+///
+/// let source = expand_pretty(|| attr.expand(item, find_impl)).unwrap();
+/// assert!(source.starts_with("#[automatically_derived]\nimpl ::core::clone::Clone for Foo {\n"));
+/// ```
+#[cfg(feature = "pretty")]
+pub fn expand_pretty(expand: impl FnOnce() -> proc_macro2::TokenStream) -> syn::Result<String> {
+    let file: syn::File = syn::parse2(expand())?;
+    Ok(prettyplease::unparse(&file))
+}
+
+/// True if `attrs` includes `#[non_exhaustive]`
+///
+/// Generators should not emit a constructor taking field values (e.g. an impl
+/// of `From<FieldType>`) for such an item, since this would allow construction
+/// outside of the defining crate.
+pub fn is_non_exhaustive(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("non_exhaustive"))
+}
 
 /// Tool to make a formatted [`Ident`]
 pub struct IdentFormatter(String);
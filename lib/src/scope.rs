@@ -3,7 +3,8 @@
 // You may obtain a copy of the License in the LICENSE-APACHE file or at:
 //     https://www.apache.org/licenses/LICENSE-2.0
 
-use crate::{fields::Fields, SimplePath};
+use crate::fields::{Field, Fields, FieldsNamed, FieldsUnnamed};
+use crate::SimplePath;
 use proc_macro2::{Span, TokenStream};
 use proc_macro_error::emit_error;
 use quote::{ToTokens, TokenStreamExt};
@@ -11,8 +12,8 @@ use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::token::{Brace, Comma, Semi};
 use syn::{
-    parse_quote, Attribute, FieldsNamed, GenericParam, Generics, Ident, ItemImpl, Path, Result,
-    Token, Type, Variant, Visibility,
+    parse_quote, Attribute, GenericParam, Generics, Ident, ItemImpl, Path, Result, Token, Type,
+    Variant, Visibility,
 };
 
 /// Attribute rule for [`Scope`]
@@ -40,6 +41,39 @@ pub trait ScopeAttr {
         false
     }
 
+    /// Application priority
+    ///
+    /// Rules are applied in order of descending priority; rules of equal
+    /// priority are applied in the order their attributes appear on the item.
+    ///
+    /// Default implementation: `0`. Rules which modify the scope item should
+    /// usually have a higher priority than rules which read it.
+    fn priority(&self) -> i32 {
+        0
+    }
+
+    /// Paths of rules which may not be used together with this one
+    ///
+    /// Conflicts are symmetric: it is sufficient for only one of two rules to
+    /// declare the conflict. Usage of conflicting rules on the same item
+    /// results in an error at both attributes, and the latter is not applied.
+    ///
+    /// Default implementation: no conflicts.
+    fn conflicts_with(&self) -> Vec<SimplePath> {
+        Vec::new()
+    }
+
+    /// Whether this rule reads field initializers
+    ///
+    /// Field initializers (`name: Type = expr`) are not legal output. If this
+    /// is true (default: false), initializers are considered used and are
+    /// removed by [`Scope::apply_attrs`] once all rules have been applied.
+    /// Rules should therefore read initializers without removing them, since
+    /// other rules may also use them.
+    fn uses_field_initializers(&self) -> bool {
+        false
+    }
+
     /// Function type of [`ScopeAttr`] rule
     ///
     /// Input arguments:
@@ -48,7 +82,9 @@ pub trait ScopeAttr {
     ///     using [`Attribute::parse_args`] or [`Attribute::parse_args_with`].
     /// -   `scope`: mutable reference to the implementation scope. Usually
     ///     an attribute rule function will read data from the scope and append its
-    ///     output to [`Scope::generated`].
+    ///     output to [`Scope::generated`]. Rules may also modify the item,
+    ///     for example adding fields via [`Scope::push_field`] or removing
+    ///     fields via [`Fields::retain`]; the modified item is emitted.
     fn apply(&self, attr: Attribute, scope: &mut Scope) -> Result<()>;
 }
 
@@ -87,7 +123,7 @@ pub enum ScopeItem {
         /// `union`
         token: Token![union],
         /// Fields of union
-        fields: FieldsNamed,
+        fields: syn::FieldsNamed,
     },
 }
 
@@ -101,6 +137,59 @@ impl ScopeItem {
             ScopeItem::Union { token, .. } => token.span,
         }
     }
+
+    /// Get the fields of a struct
+    ///
+    /// Returns `None` if the item is not a struct.
+    pub fn fields(&self) -> Option<&Fields> {
+        match self {
+            ScopeItem::Struct { fields, .. } => Some(fields),
+            _ => None,
+        }
+    }
+
+    /// Get the fields of a struct, mutably
+    ///
+    /// Returns `None` if the item is not a struct.
+    pub fn fields_mut(&mut self) -> Option<&mut Fields> {
+        match self {
+            ScopeItem::Struct { fields, .. } => Some(fields),
+            _ => None,
+        }
+    }
+
+    /// Get the variants of an enum
+    ///
+    /// Returns `None` if the item is not an enum.
+    pub fn variants(&self) -> Option<&Punctuated<Variant, Comma>> {
+        match self {
+            ScopeItem::Enum { variants, .. } => Some(variants),
+            _ => None,
+        }
+    }
+
+    /// Get the variants of an enum, mutably
+    ///
+    /// Returns `None` if the item is not an enum.
+    pub fn variants_mut(&mut self) -> Option<&mut Punctuated<Variant, Comma>> {
+        match self {
+            ScopeItem::Enum { variants, .. } => Some(variants),
+            _ => None,
+        }
+    }
+
+    /// Get the `{ ... }` token of an enum, regular struct or union
+    pub fn brace_token(&self) -> Option<&Brace> {
+        match self {
+            ScopeItem::Enum { brace, .. } => Some(brace),
+            ScopeItem::Struct {
+                fields: Fields::Named(fields),
+                ..
+            } => Some(&fields.brace_token),
+            ScopeItem::Union { fields, .. } => Some(&fields.brace_token),
+            _ => None,
+        }
+    }
 }
 
 /// Contents of `impl_scope!`
@@ -144,42 +233,267 @@ pub struct Scope {
     pub generated: Vec<TokenStream>,
 }
 
+/// Limit on rounds of [`Scope::apply_attrs`]
+const MAX_ROUNDS: usize = 16;
+
 impl Scope {
     /// Apply attribute rules
     ///
-    /// The supplied `rules` are applied in the order of definition, and their
-    /// attributes removed from the item.
+    /// The supplied `rules` are applied in order of [priority](ScopeAttr::priority),
+    /// then in the order of definition, and their attributes removed from the
+    /// item. Repeated and [conflicting](ScopeAttr::conflicts_with) rules are
+    /// reported as errors.
+    ///
+    /// All rules see the same fields. Once all rules have been applied, field
+    /// attributes matching a rule (helper attributes, see
+    /// [`Field::helper_attr`](crate::fields::Field::helper_attr)) are removed,
+    /// as are field initializers if any applied rule
+    /// [uses them](ScopeAttr::uses_field_initializers).
+    ///
+    /// Rules may add further attributes to the scope, in which case these are
+    /// applied in a subsequent round. To guard against rules which re-add
+    /// their own attribute indefinitely, the number of rounds is limited;
+    /// attributes remaining after the limit are reported as errors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use impl_tools_lib::{Scope, ScopeAttr};
+    /// # use impl_tools_lib::SimplePath;
+    /// # use quote::quote;
+    /// struct Rule(&'static [&'static str], i32);
+    /// impl ScopeAttr for Rule {
+    ///     fn path(&self) -> SimplePath {
+    ///         SimplePath::new(self.0)
+    ///     }
+    ///     fn priority(&self) -> i32 {
+    ///         self.1
+    ///     }
+    ///     fn apply(&self, _: syn::Attribute, scope: &mut Scope) -> syn::Result<()> {
+    ///         let name = self.0[0];
+    ///         scope.generated.push(quote! { #name });
+    ///         Ok(())
+    ///     }
+    /// }
+    /// static RULES: [Rule; 3] = [Rule(&["a"], 0), Rule(&["b"], 1), Rule(&["c"], 0)];
+    ///
+    /// let mut scope: Scope = syn::parse_quote! {
+    ///     #[c] #[a] #[b]
+    ///     struct S;
+    /// };
+    /// scope.apply_attrs(|path| {
+    ///     RULES.iter().find(|rule| rule.path().matches(path)).map(|rule| rule as _)
+    /// });
+    /// // `b` has the highest priority; `c` and `a` retain input order
+    /// let order: Vec<_> = scope.generated.iter().map(|toks| toks.to_string()).collect();
+    /// assert_eq!(order, ["\"b\"", "\"c\"", "\"a\""]);
+    /// ```
+    ///
+    /// Usage of [conflicting](ScopeAttr::conflicts_with) rules is an error
+    /// (here, emitting the error panics since we are not in a proc-macro):
+    ///
+    /// ```should_panic
+    /// # use impl_tools_lib::{Scope, ScopeAttr};
+    /// # use impl_tools_lib::SimplePath;
+    /// struct Rule(&'static [&'static str], &'static [&'static str]);
+    /// impl ScopeAttr for Rule {
+    ///     fn path(&self) -> SimplePath {
+    ///         SimplePath::new(self.0)
+    ///     }
+    ///     fn conflicts_with(&self) -> Vec<SimplePath> {
+    ///         vec![SimplePath::new(self.1)]
+    ///     }
+    ///     fn apply(&self, _: syn::Attribute, _: &mut Scope) -> syn::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    /// static RULES: [Rule; 2] = [Rule(&["a"], &["b"]), Rule(&["b"], &["x"])];
+    ///
+    /// let mut scope: Scope = syn::parse_quote! {
+    ///     #[a] #[b]
+    ///     struct S;
+    /// };
+    /// scope.apply_attrs(|path| {
+    ///     RULES.iter().find(|rule| rule.path().matches(path)).map(|rule| rule as _)
+    /// });
+    /// ```
     pub fn apply_attrs(&mut self, find_rule: impl Fn(&Path) -> Option<&'static dyn ScopeAttr>) {
-        let mut applied: Vec<(Span, *const dyn ScopeAttr)> = Vec::new();
+        let mut applied: Vec<(Span, &'static dyn ScopeAttr)> = Vec::new();
+        let mut used_initializers = false;
+
+        // Rules may add attributes to the scope, hence we loop until none remain
+        for round in 0.. {
+            let mut rules = Vec::new();
+            let mut i = 0;
+            while i < self.attrs.len() {
+                if let Some(rule) = find_rule(self.attrs[i].path()) {
+                    rules.push((rule, self.attrs.remove(i)));
+                } else {
+                    i += 1;
+                }
+            }
+            if rules.is_empty() {
+                break;
+            } else if round == MAX_ROUNDS {
+                for (_, attr) in rules {
+                    emit_error!(attr, "attribute rules added attributes too many times");
+                }
+                break;
+            }
+
+            // This sort is stable: rules of equal priority retain input order
+            rules.sort_by_key(|(rule, _)| std::cmp::Reverse(rule.priority()));
 
-        let mut i = 0;
-        while i < self.attrs.len() {
-            if let Some(rule) = find_rule(&self.attrs[i].path()) {
-                let attr = self.attrs.remove(i);
+            for (rule, attr) in rules {
+                let span = attr.span();
 
                 if !rule.support_repetition() {
                     // We compare the fat pointer (including vtable address;
                     // the data may be zero-sized and thus not unique).
                     // We consider two rules the same when data pointers and
                     // vtables both compare equal.
-                    let span = attr.span();
-                    let ptr = rule as *const dyn ScopeAttr;
-                    #[allow(clippy::vtable_address_comparisons)]
-                    if let Some(first) = applied.iter().find(|(_, p)| std::ptr::eq(*p, ptr)) {
+                    #[allow(unknown_lints, ambiguous_wide_pointer_comparisons)]
+                    if let Some(first) = applied.iter().find(|(_, r)| std::ptr::eq(*r, rule)) {
                         emit_error!(span, "repeated use of attribute not allowed");
                         emit_error!(first.0, "first usage here");
                         continue;
                     }
-                    applied.push((span, ptr));
                 }
 
+                let path = rule.path();
+                let conflicts = rule.conflicts_with();
+                if let Some(other) = applied.iter().find(|(_, other)| {
+                    let other_path = other.path();
+                    conflicts.contains(&other_path) || other.conflicts_with().contains(&path)
+                }) {
+                    emit_error!(
+                        span,
+                        "attribute `{}` conflicts with `{}`",
+                        path,
+                        other.1.path()
+                    );
+                    emit_error!(other.0, "conflicting attribute here");
+                    continue;
+                }
+
+                applied.push((span, rule));
+                used_initializers |= rule.uses_field_initializers();
                 if let Err(err) = rule.apply(attr, self) {
                     emit_error!(err.span(), "{}", err);
                 }
-                continue;
             }
+        }
 
-            i += 1;
+        if let ScopeItem::Struct { fields, .. } = &mut self.item {
+            for field in fields.iter_mut() {
+                field.attrs.retain(|attr| find_rule(attr.path()).is_none());
+                if used_initializers {
+                    field.assign = None;
+                }
+            }
+        }
+    }
+
+    /// Get the fields of a struct
+    ///
+    /// Returns `None` if the item is not a struct. See also [`Scope::item`].
+    pub fn fields(&self) -> Option<&Fields> {
+        self.item.fields()
+    }
+
+    /// Get the fields of a struct, mutably
+    ///
+    /// Returns `None` if the item is not a struct.
+    pub fn fields_mut(&mut self) -> Option<&mut Fields> {
+        self.item.fields_mut()
+    }
+
+    /// Get the item's generics
+    pub fn generics(&self) -> &Generics {
+        &self.generics
+    }
+
+    /// Get the trailing semicolon (type alias, unit struct and tuple struct only)
+    pub fn semi_token(&self) -> Option<&Semi> {
+        self.semi.as_ref()
+    }
+
+    /// Add a field to the struct
+    ///
+    /// This allows a [`ScopeAttr`] rule to inject fields into the emitted item.
+    /// A unit struct is converted to a regular struct or tuple struct
+    /// according to whether `field` is named. Rules applied later (see
+    /// [`ScopeAttr::priority`]) observe the new field.
+    ///
+    /// Fails if the item is not a struct or if `field` is named but the
+    /// struct is a tuple struct (or vice-versa).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use impl_tools_lib::{fields::Field, Scope};
+    /// # use quote::ToTokens;
+    /// let mut scope: Scope = syn::parse_quote! { struct Unit; };
+    /// let field = Field::new_named(syn::parse_quote! { pub }, syn::parse_quote! { x }, syn::parse_quote! { f32 });
+    /// scope.push_field(field).unwrap();
+    /// assert_eq!(
+    ///     scope.to_token_stream().to_string(),
+    ///     quote::quote! { struct Unit { pub x: f32 } }.to_string(),
+    /// );
+    ///
+    /// // An unnamed field makes a unit struct a tuple struct
+    /// let mut scope: Scope = syn::parse_quote! { struct Unit; };
+    /// scope.push_field(Field::new_unnamed(syn::Visibility::Inherited, syn::parse_quote! { u8 })).unwrap();
+    /// assert_eq!(
+    ///     scope.to_token_stream().to_string(),
+    ///     quote::quote! { struct Unit(u8); }.to_string(),
+    /// );
+    ///
+    /// // Named and unnamed fields may not be mixed
+    /// let field = Field::new_unnamed(syn::Visibility::Inherited, syn::parse_quote! { u8 });
+    /// let mut scope: Scope = syn::parse_quote! { struct S { x: f32 } };
+    /// assert!(scope.push_field(field).is_err());
+    /// ```
+    pub fn push_field(&mut self, field: Field) -> Result<()> {
+        let fields = match &mut self.item {
+            ScopeItem::Struct { fields, .. } => fields,
+            item => {
+                return Err(syn::Error::new(
+                    item.token_span(),
+                    "cannot add a field: item is not a struct",
+                ))
+            }
+        };
+
+        if let Fields::Unit = fields {
+            if field.ident.is_some() {
+                *fields = Fields::Named(FieldsNamed {
+                    brace_token: Default::default(),
+                    fields: Punctuated::new(),
+                });
+                self.semi = None;
+            } else {
+                *fields = Fields::Unnamed(FieldsUnnamed {
+                    paren_token: Default::default(),
+                    fields: Punctuated::new(),
+                });
+            }
+        }
+
+        match (fields, field.ident.is_some()) {
+            (Fields::Named(FieldsNamed { fields, .. }), true)
+            | (Fields::Unnamed(FieldsUnnamed { fields, .. }), false) => {
+                fields.push(field);
+                Ok(())
+            }
+            (_, true) => Err(syn::Error::new(
+                field.ident.span(),
+                "cannot add a named field to a tuple struct",
+            )),
+            (_, false) => Err(syn::Error::new(
+                field.ty.span(),
+                "cannot add an unnamed field to a regular struct",
+            )),
         }
     }
 
@@ -189,11 +503,16 @@ impl Scope {
     /// by a [`ScopeAttr`] if required. Calling multiple times is harmless.
     pub fn expand_impl_self(&mut self) {
         for impl_ in self.impls.iter_mut() {
-            if impl_.self_ty == parse_quote! { Self } {
+            // Support `Self`, `&Self` and `&mut Self` (with optional lifetime)
+            let self_ty = match &mut *impl_.self_ty {
+                Type::Reference(ty) => &mut *ty.elem,
+                ty => ty,
+            };
+            if *self_ty == parse_quote! { Self } {
                 let mut ident = self.ident.clone();
-                ident.set_span(impl_.self_ty.span());
+                ident.set_span(self_ty.span());
                 let (_, ty_generics, _) = self.generics.split_for_impl();
-                impl_.self_ty = parse_quote! { #ident #ty_generics };
+                *self_ty = parse_quote! { #ident #ty_generics };
                 extend_generics(&mut impl_.generics, &self.generics);
             }
         }
@@ -215,11 +534,16 @@ mod parsing {
     use crate::fields::parsing::data_struct;
     use syn::parse::{Parse, ParseStream};
     use syn::spanned::Spanned;
-    use syn::{braced, Error, Field, Lifetime, Path, TypePath, WhereClause};
+    use syn::{braced, AttrStyle, Error, Field, Lifetime, Path, TypePath, WhereClause};
 
     impl Parse for Scope {
         fn parse(input: ParseStream) -> Result<Self> {
-            let attrs = input.call(Attribute::parse_outer)?;
+            // Inner attributes apply to the scope's item; we emit them as outer
+            let mut attrs = input.call(Attribute::parse_inner)?;
+            for attr in &mut attrs {
+                attr.style = AttrStyle::Outer;
+            }
+            attrs.extend(input.call(Attribute::parse_outer)?);
             let vis = input.parse::<Visibility>()?;
 
             enum Token {
@@ -321,7 +645,7 @@ mod parsing {
         };
 
         let mut first_ty: Type = input.parse()?;
-        let self_ty: Type;
+        let mut self_ty: Type;
         let trait_;
 
         let is_impl_for = input.peek(Token![for]);
@@ -351,8 +675,25 @@ mod parsing {
 
         generics.where_clause = input.parse()?;
 
-        if self_ty != parse_quote! { Self }
-            && !matches!(self_ty, Type::Path(TypePath {
+        // Remove invisible groups (from macro_rules! fragments like `$ty:ty`)
+        fn ungroup(ty: &mut Type) {
+            while let Type::Group(group) = ty {
+                *ty = std::mem::replace(&mut *group.elem, Type::Verbatim(TokenStream::new()));
+            }
+            if let Type::Reference(reference) = ty {
+                ungroup(&mut reference.elem);
+            }
+        }
+        ungroup(&mut self_ty);
+
+        let mut target_ty = &self_ty;
+        if let Type::Reference(ty) = target_ty {
+            if trait_.is_some() {
+                target_ty = &ty.elem;
+            }
+        }
+        if *target_ty != parse_quote! { Self }
+            && !matches!(target_ty, Type::Path(TypePath {
                 qself: None,
                 path: Path {
                     leading_colon: None,
@@ -363,7 +704,7 @@ mod parsing {
             return Err(Error::new(
                 self_ty.span(),
                 format!(
-                    "expected `Self` or `{0}` or `{0}<...>` or `Trait for Self`, etc",
+                    "expected `Self` or `{0}` or `{0}<...>` or `Trait for Self` or `Trait for &Self`, etc",
                     in_ident
                 ),
             ));
@@ -403,17 +744,17 @@ mod parsing {
         Ok((where_clause, brace, variants))
     }
 
-    pub fn data_union(input: ParseStream) -> Result<(Option<WhereClause>, FieldsNamed)> {
+    pub fn data_union(input: ParseStream) -> Result<(Option<WhereClause>, syn::FieldsNamed)> {
         let where_clause = input.parse()?;
         let fields = parse_braced(input)?;
         Ok((where_clause, fields))
     }
 
-    pub(crate) fn parse_braced(input: ParseStream) -> Result<FieldsNamed> {
+    pub(crate) fn parse_braced(input: ParseStream) -> Result<syn::FieldsNamed> {
         let content;
         let brace_token = braced!(content in input);
         let named = content.parse_terminated(Field::parse_named, Token![,])?;
-        Ok(FieldsNamed { brace_token, named })
+        Ok(syn::FieldsNamed { brace_token, named })
     }
 }
 
@@ -7,6 +7,7 @@ use crate::fields::{Field, Fields, FieldsNamed, FieldsUnnamed, StructStyle};
 use crate::{IdentFormatter, Scope, ScopeItem};
 use proc_macro2::{Span, TokenStream};
 use quote::{quote, ToTokens, TokenStreamExt};
+use syn::ext::IdentExt;
 use syn::token::{Brace, Colon, Comma, Eq, Paren, Semi};
 use syn::{parse_quote, punctuated::Punctuated, spanned::Spanned};
 use syn::{Attribute, GenericParam, Generics, Ident, ItemImpl, Member, Token, Type, TypePath};
@@ -88,7 +89,7 @@ impl Singleton {
             let ty_name = match ident {
                 None => format!("_Field{}", index),
                 Some(ref id) => {
-                    let ident = id.to_string();
+                    let ident = id.unraw().to_string();
                     let mut buf = "_Field".to_string();
                     buf.reserve(ident.len());
                     let mut next_upper = true;
@@ -181,10 +182,12 @@ impl Singleton {
                 }
             };
 
-            if let Some((_, ref value)) = field.assignment {
+            if let Some((_, mut value)) = field.assignment {
+                // The value is constructed outside of any impl, thus `Self` must be resolved
+                syn::visit_mut::visit_expr_mut(&mut ReplaceSelf, &mut value);
                 field_val_toks.append_all(quote! { #mem: #value, });
             } else {
-                field_val_toks.append_all(quote! { #mem: Default::default(), });
+                field_val_toks.append_all(quote! { #mem: ::core::default::Default::default(), });
             }
 
             fields.push_value(Field {
@@ -236,6 +239,21 @@ impl Singleton {
     }
 }
 
+/// Replace `Self` with `_Singleton` in paths (excluding nested items)
+struct ReplaceSelf;
+impl syn::visit_mut::VisitMut for ReplaceSelf {
+    fn visit_item_mut(&mut self, _: &mut syn::Item) {}
+
+    fn visit_path_mut(&mut self, path: &mut syn::Path) {
+        if let Some(seg) = path.segments.first_mut() {
+            if path.leading_colon.is_none() && seg.ident == "Self" {
+                seg.ident = Ident::new("_Singleton", seg.ident.span());
+            }
+        }
+        syn::visit_mut::visit_path_mut(self, path);
+    }
+}
+
 /// A [`Scope`] plus field values
 ///
 /// This supports dereference to a [`Scope`], allowing macro expansion via
@@ -344,9 +362,15 @@ mod parsing {
 
         generics.where_clause = input.parse()?;
 
-        if self_ty != parse_quote! { Self } {
+        let mut target_ty = &self_ty;
+        if let Type::Reference(ty) = target_ty {
+            if trait_.is_some() {
+                target_ty = &ty.elem;
+            }
+        }
+        if *target_ty != parse_quote! { Self } {
             if let Some(ident) = in_ident {
-                if !matches!(self_ty, Type::Path(TypePath {
+                if !matches!(target_ty, Type::Path(TypePath {
                     qself: None,
                     path: syn::Path {
                         leading_colon: None,
@@ -363,7 +387,10 @@ mod parsing {
                     );
                 }
             } else {
-                abort!(self_ty.span(), "expected `Self` or `Trait for Self`");
+                abort!(
+                    self_ty.span(),
+                    "expected `Self` or `Trait for Self` or `Trait for &Self`"
+                );
             }
         }
 
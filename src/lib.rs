@@ -33,6 +33,30 @@
 //! User-extensions to both `#[autoimpl]` and `impl_scope!` are possible, by
 //! writing your own proc-macro crate depending on
 //! [impl-tools-lib](https://crates.io/crates/impl-tools-lib).
+//!
+//! Generated code never refers to items from `impl-tools` itself, thus there
+//! is no runtime dependency and the macros work unchanged when re-exported or
+//! when the dependency is renamed. Generated code does refer to external crates
+//! via absolute paths: usually `::core` (e.g. `::core::fmt::Debug`), but
+//! `::std` for some targets (`Error`, `BufRead` and `Arbitrary` on enums) and
+//! the optional crates listed below for feature-gated targets. Where these
+//! crates are not available under their usual names,
+//! `#[autoimpl(.., crate = PATH)]` may be used to resolve these paths through
+//! `PATH`.
+//!
+//! Feature `warnings` enables checks for soft issues in `#[autoimpl]` usage,
+//! for example a duplicated `ignore` entry or a redundant `using` clause.
+//! These are reported as warnings by nightly compilers only; on stable they
+//! are silent.
+//!
+//! Feature `serde` enables `#[autoimpl(Serialize, Deserialize)]`. Generated
+//! code refers to `::serde`, thus the using crate must depend on `serde`.
+//!
+//! Feature `zeroize` enables `#[autoimpl(Zeroize, ZeroizeOnDrop)]`. Generated
+//! code refers to `::zeroize`, thus the using crate must depend on `zeroize`.
+//!
+//! Feature `arbitrary` enables `#[autoimpl(Arbitrary)]`. Generated code refers
+//! to `::arbitrary`, thus the using crate must depend on `arbitrary`.
 
 #[cfg(doctest)]
 doc_comment::doctest!("../README.md");
@@ -93,7 +117,60 @@ use impl_tools_lib::{self as lib, autoimpl};
 /// }
 /// ```
 ///
+/// Fields without a sensible default value may be marked with
+/// `#[impl_default(skip)]`. In this case `Default` is not implemented; instead
+/// an inherent method `fn new_with(..) -> Self` is generated, taking a
+/// parameter for each skipped field (in order):
+///
+/// ```
+/// # use impl_tools::impl_scope;
+/// impl_scope! {
+///     #[impl_default]
+///     struct Connection {
+///         #[impl_default(skip)]
+///         address: String,
+///         retries: u32 = 3,
+///     }
+/// }
+///
+/// let conn = Connection::new_with("localhost".to_string());
+/// assert_eq!(conn.retries, 3);
+/// ```
+///
+/// Since skipped fields have no default value, `#[impl_new]` and
+/// `#[impl_try_default]` report an error on these:
+///
+/// ```compile_fail
+/// # use impl_tools::impl_scope;
+/// impl_scope! {
+///     #[impl_new]
+///     struct Conn {
+///         #[impl_default(skip)]
+///         fd: i32,
+///     }
+/// }
+/// ```
+///
 /// A where clause is optional: `#[impl_default(where BOUNDS)]`.
+///
+/// ### Validation
+///
+/// A validation function may be given: `#[impl_default(validate = PATH)]` or
+/// `#[impl_default(EXPR, validate = PATH)]`. This is called as
+/// `PATH(&value) -> Result<(), E>` where `E: Debug`; on error, `default()`
+/// panics with a message including the error.
+///
+/// ```
+/// # use impl_tools::impl_default;
+/// #[impl_default(Range(0, 10), validate = Range::check)]
+/// struct Range(u32, u32);
+///
+/// impl Range {
+///     fn check(&self) -> Result<(), &'static str> {
+///         if self.0 <= self.1 { Ok(()) } else { Err("start > end") }
+///     }
+/// }
+/// ```
 #[proc_macro_attribute]
 #[proc_macro_error]
 pub fn impl_default(args: TokenStream, item: TokenStream) -> TokenStream {
@@ -126,21 +203,50 @@ pub fn impl_default(args: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// | Path | *ignore* | *using* | *notes* |
 /// |----- |--- |--- |--- |
-/// | [`::core::borrow::Borrow<T>`] | - | borrow target | `T` is type of target field |
-/// | [`::core::borrow::BorrowMut<T>`] | - | borrow target | `T` is type of target field |
-/// | [`::core::clone::Clone`] | yes | - | ignored fields use `Default::default()` |
-/// | [`::core::cmp::Eq`] | * | - | *allowed with `PartialEq` |
-/// | [`::core::cmp::Ord`] | yes | - | |
-/// | [`::core::cmp::PartialEq`] | yes | - | |
-/// | [`::core::cmp::PartialOrd`] | yes | - | |
-/// | [`::core::convert::AsRef<T>`] | - | ref target | `T` is type of target field |
-/// | [`::core::convert::AsMut<T>`] | - | ref target | `T` is type of target field |
-/// | [`::core::default::Default`] | - | - | [`macro@impl_default`] is a more flexible alternative |
-/// | [`::core::fmt::Debug`] | yes | - | |
-/// | [`::core::hash::Hash`] | yes | - | |
-/// | [`::core::marker::Copy`] | * | - | *allowed with `Clone` |
+/// | [`::core::borrow::Borrow<T>`] | - | borrow target | `T` is type of target field unless given, e.g. `Borrow<str>` |
+/// | [`::core::borrow::BorrowMut<T>`] | - | borrow target | `T` is type of target field unless given, e.g. `BorrowMut<str>` |
+/// | [`::core::clone::Clone`] | yes | - | ignored fields use the given value or `Default::default()`; `clone_from` reuses resources of each field; option `copy` |
+/// | [`::core::cmp::Eq`] | yes | optional: key field | ignored fields do not require bounds, as for `PartialEq` |
+/// | [`::core::cmp::Ord`] | yes | optional: key field | `using` compares only the key field; enum variants compare in declaration order, then by fields |
+/// | [`::core::cmp::PartialEq<T>`] | yes | optional: key field | `using` compares only the key field; `T` is `Self` unless given, e.g. `PartialEq<str> using self.name` compares field `name` with `str` |
+/// | [`::core::cmp::PartialOrd`] | yes | optional: key field | as `Ord` |
+/// | [`::core::convert::AsRef<T>`] | - | ref target | `T` is type of target field unless given, e.g. `AsRef<str>` |
+/// | [`::core::convert::AsMut<T>`] | - | ref target | `T` is type of target field unless given, e.g. `AsMut<str>` |
+/// | [`::core::convert::From<T>`] | - | source field | `T` is type of source field; other fields use `Default::default()` |
+/// | [`::core::convert::TryFrom<S>`] | - | target field | `S` is required; converts `S` to the type of target field; other fields use `Default::default()`; on fieldless enums with `#[repr(INT)]`, converts to `INT` and matches discriminants, returning other values as the error |
+/// | [`::core::convert::Into<T>`] | - | target field | implements `From<Self> for T` where `T` is type of target field; on fieldless enums with `#[repr(INT)]`, `T` is required (e.g. `Into<u32>`) and the discriminant is converted via `From<INT>` (thus may not narrow) |
+/// | [`::core::default::Default`] | - | - | [`macro@impl_default`] is a more flexible alternative; for enums, constructs the variant marked `#[default]` with default fields |
+/// | [`::core::fmt::Debug`] | yes | - | option `type_name`; supports `redact` and `fmt` |
+/// | [`::core::fmt::Display`] | - | display target | on enums, formats each variant via `fmt Self::VARIANT(..) => "FORMAT"` |
+/// | [`::core::fmt::LowerHex`], [`UpperHex`](::core::fmt::UpperHex), [`Octal`](::core::fmt::Octal), [`Binary`](::core::fmt::Binary), [`Pointer`](::core::fmt::Pointer) | - | format target | as `Display` |
+/// | [`::core::fmt::Write`] | - | inner writer | forwards `write_str`, `write_char` and `write_fmt` |
+/// | [`::core::future::Future`] | - | inner future | the field is structurally pinned; also implements `Unpin` (where the field is `Unpin`) and forbids `Drop` and `#[repr(packed)]` |
+/// | [`::core::hash::Hash`] | yes | optional: hash key | `using` hashes only the key field |
+/// | [`::core::hash::Hasher`] | - | inner hasher | forwards `finish`, `write` and all stable `write_*` methods |
+/// | [`::core::iter::Extend<A>`] | - | collection | for each `A` supported by the field |
+/// | [`::core::iter::FromIterator<A>`] | - | collection | for each `A` supported by the field; other fields use `Default::default()` |
+/// | [`::core::iter::IntoIterator`] | - | collection | for `Self`, `&Self` and `&mut Self`; options `by_value`, `by_ref`, `by_mut` select a subset |
+/// | [`::core::iter::Iterator`] | - | inner iterator | forwards `next` and `size_hint` |
+/// | [`::core::marker::Copy`] | - | - | |
+/// | [`::core::marker::Send`], [`Sync`](::core::marker::Sync) | - | - | must be written `unsafe Send` / `unsafe Sync`; see [unsafe traits](#unsafe-traits) |
+/// | [`::core::ops::Add`], [`Sub`](::core::ops::Sub), [`Mul`](::core::ops::Mul), [`Div`](::core::ops::Div), [`Rem`](::core::ops::Rem) | yes | - | field-wise with `rhs: Self`; ignored fields keep the value of `self` |
+/// | [`::core::ops::AddAssign`], [`SubAssign`](::core::ops::SubAssign), [`MulAssign`](::core::ops::MulAssign), [`DivAssign`](::core::ops::DivAssign), [`RemAssign`](::core::ops::RemAssign) | yes | - | field-wise with `rhs: Self`; ignored fields are unchanged |
+/// | [`::core::ops::BitAnd`], [`BitOr`](::core::ops::BitOr), [`BitXor`](::core::ops::BitXor), [`Shl`](::core::ops::Shl), [`Shr`](::core::ops::Shr) | yes | - | as `Add` |
+/// | [`::core::ops::BitAndAssign`], [`BitOrAssign`](::core::ops::BitOrAssign), [`BitXorAssign`](::core::ops::BitXorAssign), [`ShlAssign`](::core::ops::ShlAssign), [`ShrAssign`](::core::ops::ShrAssign) | yes | - | as `AddAssign` |
 /// | [`::core::ops::Deref`] | - | deref target | See [`Deref::Target` type](#dereftarget-type) below |
 /// | [`::core::ops::DerefMut`] | - | deref target | |
+/// | [`::core::ops::Index<I>`] | - | indexed field | `I` is optional (if omitted, all index types of the field are supported) |
+/// | [`::core::ops::IndexMut<I>`] | - | indexed field | as `Index` |
+/// | [`::core::ops::RangeBounds<T>`] | - | inner range | `T` is optional (if omitted, all bound types of the field are supported) |
+/// | [`::std::error::Error`] | - | source | `using` field (or `Option` of it; `Box<dyn Error + ..>` is supported) is returned by `source` |
+/// | [`::std::io::BufRead`] | - | inner reader | forwards `fill_buf`, `consume`, `read_until` and `read_line`; requires `Read` |
+/// | `::arbitrary::Arbitrary` | yes | - | requires feature `arbitrary`; ignored fields use `Default::default()`; recursive enums fail on exhausted input (enums require `std`); `trait` in a `where` clause means `Arbitrary<'arbitrary>` |
+/// | `::serde::Deserialize` | yes | optional: deserialize as field | requires feature `serde`; other fields use `Default::default()` |
+/// | `::serde::Serialize` | yes | optional: serialize as field | requires feature `serde`; `ignore` skips fields |
+/// | `::zeroize::Zeroize` | yes | - | requires feature `zeroize`; zeroizes each field not ignored |
+/// | `::zeroize::ZeroizeOnDrop` | yes | - | requires feature `zeroize`; implements `Drop`, zeroizing each field not ignored; `trait` in a `where` clause means `Zeroize` |
+/// | `Inner` | - | inner field | See [inner-access methods](#inner-access-methods) below |
+/// | `StrNewtype` | - | optional: string field | See [string newtypes](#string-newtypes) below |
 ///
 /// Traits are matched from the path, as follows:
 ///
@@ -150,32 +256,225 @@ pub fn impl_default(args: TokenStream, item: TokenStream) -> TokenStream {
 /// -   The full path with/without leading `::`, using `std` instead of `core` or `alloc`,
 ///     e.g. `#[autoimpl(std::clone::Clone)]`
 ///
+/// Unions support only `Copy`, `Clone` (implemented by copy, thus requiring
+/// `Copy`), `Eq`, `unsafe Send` and `unsafe Sync`.
+///
+/// *Integer conversions:* on fieldless enums, `Into` and `TryFrom` require an
+/// integer representation and do not truncate discriminants:
+/// ```compile_fail
+/// # use impl_tools::autoimpl;
+/// #[autoimpl(Into<u8>)]
+/// #[repr(u16)]
+/// enum Wide {
+///     A = 1,
+///     B = 257,
+/// }
+/// ```
+///
 /// *Ignore:* some trait implementations supports ignoring listed fields.
 /// For example, `#[autoimpl(PartialEq ignore self.foo)]` will implement
 /// `PartialEq`, comparing all fields except `foo`.
-/// Note: `Copy` and `Eq` do not *use* `ignore`, but tolerate its usage by a
-/// companion trait (e.g. `#[autoimpl(PartialEq, Eq ignore self.a)]`).
+/// Note: `Eq` uses `ignore` only to omit bounds (as does `PartialEq`).
+/// `Clone` initializes ignored fields with `Default::default()` or with a
+/// given value: `#[autoimpl(Clone ignore self.handle = Handle::invalid())]`.
+/// Since a copy must equal the result of `clone()`, `ignore` is an error
+/// together with `Copy`; other targets may ignore fields using per-target
+/// arguments, e.g. `#[autoimpl(Copy, Clone, Debug(ignore self.x))]`:
+/// ```compile_fail
+/// # use impl_tools::autoimpl;
+/// #[autoimpl(Copy, Clone ignore self.x)]
+/// struct Pair {
+///     x: u8,
+///     y: u8,
+/// }
+/// ```
+/// The complement, `only self.a, self.b`, ignores all fields except those
+/// listed, e.g. `#[autoimpl(Debug, Hash only self.id)]`.
+/// For enums, `PartialEq`, `Eq`, `PartialOrd`, `Ord` and `Hash` support `ignore`: `ignore self.FIELD`
+/// (or `self.0`) ignores that field within each variant having such a field.
+/// `Eq` and `Hash` report an error on fields of type `f32` or `f64` (including
+/// arrays and tuples of these) unless such fields are ignored.
+/// Since values comparing equal must have equal hashes, it is an error for
+/// `Hash` to use a field ignored by `PartialEq`, including where these are
+/// implemented by separate attributes or with their own arguments:
+/// ```compile_fail
+/// # use impl_tools::autoimpl;
+/// #[autoimpl(PartialEq ignore self.note)]
+/// #[autoimpl(Hash)]
+/// struct Record {
+///     id: u32,
+///     note: &'static str,
+/// }
+/// ```
+/// ```compile_fail
+/// # use impl_tools::autoimpl;
+/// #[autoimpl(PartialEq(ignore self.note), Hash)]
+/// struct Record {
+///     id: u32,
+///     note: &'static str,
+/// }
+/// ```
+///
+/// *Raw identifiers:* a field such as `r#type` may be listed as
+/// `self.r#type` wherever a field is expected (`ignore`, `using`, etc.);
+/// a field `foo` may be named as either `self.foo` or `self.r#foo`.
+/// Generated names (e.g. in `Debug` output) omit the `r#` prefix.
+///
+/// *Redact:* `Debug` supports redacting listed fields, e.g.
+/// `#[autoimpl(Debug redact self.password)]` prints `password: ***` instead
+/// of the value of `password`.
+///
+/// *Fmt:* `Debug` supports overriding the value printed for listed fields,
+/// e.g. `#[autoimpl(Debug fmt self.addr as format_args!("{:#x}", self.addr))]`.
+/// The expression may be any value implementing `Debug`.
+/// On enums, `Display` takes a format string per variant, e.g.
+/// `#[autoimpl(Display fmt Self::Io(e) => "I/O error: {e}", Self::Eof => "end of file")]`.
+/// Bindings from each pattern may be used in the format string. Patterns may
+/// be combined with `|`; each variant must be matched by some pattern.
 ///
 /// *Using:* some trait implementations require a named field to "use".
 /// For example, `#[autoimpl(Deref using self.foo)]` implements [`Deref`] to
 /// return a reference to field `self.foo`.
+/// Tuple fields are selected by index, e.g. `using self.1`; `using self.(last)`
+/// selects the last field of any struct.
+/// Alternatively, the field attribute `#[autoimpl(using)]` designates the
+/// field used by all targets requiring `using` where this is not given.
+/// Otherwise, for a `#[repr(transparent)]` struct, `using` may be omitted: such targets
+/// then use the only field not assumed to be zero-sized (fields of type
+/// `PhantomData<T>`, `()` and `[T; 0]` are assumed zero-sized).
+///
+/// *Key field:* comparison traits (`PartialEq`, `Eq`, `PartialOrd`, `Ord`, `Hash`)
+/// support comparing by a single key field, e.g.
+/// `#[autoimpl(PartialEq, Eq, Hash, Ord, PartialOrd using self.id)]` compares
+/// and hashes only `id`, regardless of other (payload) fields.
+///
+/// *By:* comparison traits (`PartialEq`, `Eq`, `PartialOrd`, `Ord`, `Hash`)
+/// support comparing by a list of key expressions over `self` instead of by
+/// fields. For example, `#[autoimpl(PartialEq, Eq, PartialOrd, Ord by self.priority, self.name.len())]`
+/// compares `priority`, then the length of `name`.
+///
+/// *Options:* some trait implementations support options, enabled via
+/// `with NAME`. For example, `#[autoimpl(Debug with type_name)]` prints the
+/// type name with generic arguments (e.g. `Foo<i32>` instead of `Foo`).
+/// It is an error if no listed trait supports an option.
+///
+/// `Clone` copies fields of primitive type (and shared references) directly
+/// instead of calling `clone()`. With `#[autoimpl(Copy, Clone with copy)]`,
+/// `clone` simply returns `*self`. This requires `Self: Copy`; for generic
+/// types use an explicit bound, e.g. `where T: Copy`.
+///
+/// *Per-target arguments:* a target may be followed by its own arguments in
+/// parentheses, e.g. `#[autoimpl(Clone(ignore self.x), Debug(ignore self.y), Default)]`.
+/// These apply only to that target, while arguments following the list of
+/// targets apply only to targets without parentheses (thus are an error if
+/// every target has parentheses).
+///
+/// *Conflicts:* it is an error to list a target twice (the repeat is
+/// reported and ignored), or to list a target which is also listed by a
+/// following `#[autoimpl]` attribute or `#[derive]` on the same item (e.g.
+/// `Clone`). In the latter case the error is reported on the earlier target
+/// and that impl is not generated. Targets with different
+/// path arguments (e.g. `PartialEq<str>`) do not conflict, nor do targets of
+/// two `#[autoimpl]` attributes with different `cfg(PRED)` (or `test_only`)
+/// predicates, which are assumed to be exclusive. A `#[derive]`
+/// preceding `#[autoimpl]` is not visible to the macro, thus is not checked.
+/// ```compile_fail
+/// # use impl_tools::autoimpl;
+/// #[autoimpl(Clone, Debug, Clone)]
+/// struct Twice(u8);
+/// ```
+/// ```compile_fail
+/// # use impl_tools::autoimpl;
+/// #[autoimpl(Clone, Debug)]
+/// #[derive(Clone)]
+/// struct Derived(u8);
+/// ```
+///
+/// ### Unsafe traits
+///
+/// `Send` and `Sync` are unsafe to implement, thus these targets must be
+/// written with the `unsafe` keyword, e.g. `#[autoimpl(unsafe Send, unsafe Sync)]`,
+/// asserting that the implementation is sound. This is useful for wrappers
+/// over raw pointers (e.g. FFI handles).
+///
+/// Unlike other targets, when no `where` clause is given each type parameter
+/// is bounded by the trait being implemented, e.g. `T: Send`. With an explicit
+/// `where` clause, only the given bounds apply.
 ///
 /// ### Parameter syntax
 ///
 /// > _ParamsMulti_ :\
-/// > &nbsp;&nbsp; ( _Trait_ ),+ _Using_? _Ignores_? _WhereClause_?
+/// > &nbsp;&nbsp; ( _Target_ | _Crate_ | _Cfg_ | _TestOnly_ | `const` ),+ _Args_
+/// >
+/// > _Target_ :\
+/// > &nbsp;&nbsp; _Visibility_? `unsafe`? _Trait_ ( `(` _Args_ `)` )?
+/// >
+/// > _Args_ :\
+/// > &nbsp;&nbsp; _Using_? ( _Ignores_ | _Only_ )? _Redacts_? _Fmts_? _Options_? _By_? _BoundFields_? _WhereClause_?
+/// >
+/// > _Crate_ :\
+/// > &nbsp;&nbsp; `crate` `=` _SimplePath_
+/// >
+/// > _Cfg_ :\
+/// > &nbsp;&nbsp; `cfg` `(` _ConfigurationPredicate_ `)`
+/// >
+/// > _TestOnly_ :\
+/// > &nbsp;&nbsp; `test_only` ( `=` _StringLiteral_ )?
 /// >
 /// > _Using_ :\
-/// > &nbsp;&nbsp; `using` `self` `.` _Member_
+/// > &nbsp;&nbsp; `using` `self` `.` ( _Member_ | `(` `last` `)` )
 /// >
 /// > _Ignores_ :\
-/// > &nbsp;&nbsp; `ignore` ( `self` `.` _Member_ ),+
+/// > &nbsp;&nbsp; `ignore` ( `self` `.` _Member_ ( `=` _Expression_ )? ),+
+/// >
+/// > _Only_ :\
+/// > &nbsp;&nbsp; `only` ( `self` `.` _Member_ ),+
+/// >
+/// > _Redacts_ :\
+/// > &nbsp;&nbsp; `redact` ( `self` `.` _Member_ ),+
+/// >
+/// > _Fmts_ :\
+/// > &nbsp;&nbsp; `fmt` ( ( `self` `.` _Member_ `as` _Expression_ ),+ | ( _Pattern_ `=>` _StringLiteral_ ),+ )
+/// >
+/// > _Options_ :\
+/// > &nbsp;&nbsp; `with` ( _Identifier_ ),+
+/// >
+/// > _By_ :\
+/// > &nbsp;&nbsp; `by` ( _Expression_ ),+
+/// >
+/// > _BoundFields_ :\
+/// > &nbsp;&nbsp; ( `bound` `(` ( `fields` | `used` ) `)` )*
 /// >
 /// > _WhereClause_ :\
-/// > &nbsp;&nbsp; `where` ( _WherePredicate_ ),*
+/// > &nbsp;&nbsp; `where` ( _WherePredicate_ ),* \
+/// > &nbsp;&nbsp; | `bound` `=` ( _StringLiteral_ | ( _WherePredicate_ ),* )
 ///
 /// **Targets:** each *Trait* listed is implemented for the annotated type.
 ///
+/// **Crate path:** with `crate = PATH`, generated impls refer to `PATH::core`,
+/// `PATH::std` and the optional crates `PATH::serde`, `PATH::zeroize` and
+/// `PATH::arbitrary` in place of the absolute paths `::core` etc. Paths written by the user (e.g. in field types or `where`
+/// clauses) are emitted unchanged.
+/// This allows a crate re-exporting these macros to direct the expansion
+/// through its own re-exports, e.g.
+/// `#[autoimpl(Clone, Debug, crate = my_facade::__impl_tools)]`.
+///
+/// **Const block:** with `const`, all generated impls are emitted inside an
+/// anonymous `const _: () = { .. };` block (as done by `serde`), keeping any
+/// helper items and imports out of the surrounding module. When combined with
+/// `crate = PATH`, the path is imported locally within this block.
+///
+/// **Conditional impls:** with `cfg(PRED)`, generated impls are only included
+/// when `PRED` holds, e.g. `#[autoimpl(Debug, cfg(feature = "debug-impls"))]`.
+/// As with `const`, impls are emitted within a const block, here annotated
+/// with `#[cfg(PRED)]`.
+///
+/// **Test-only impls:** `test_only` is equivalent to `cfg(test)`, e.g. for
+/// `PartialEq` impls used only by assertions. With `test_only = "FEATURE"`
+/// impls are also enabled by the given feature (as for
+/// `cfg(any(test, feature = "FEATURE"))`), e.g. to make these available to
+/// tests of dependent crates. If `cfg(PRED)` is also given, both must hold.
+///
 /// ### Generics and where clause
 ///
 /// Type generics are inherited from the type definition. Bounds defined by the
@@ -186,6 +485,100 @@ pub fn impl_default(args: TokenStream, item: TokenStream) -> TokenStream {
 /// A special bound syntax, `T: trait`, indicates that `T` must support the
 /// trait being implemented.
 ///
+/// The `trait` bound may be combined with other bounds and may be applied to
+/// any type, including associated types. Predicates using `trait` may be mixed
+/// with literal predicates, e.g. `where K: trait, V: Clone + Debug`, in which
+/// case each target substitutes its own path for `trait` while literal bounds
+/// apply to every target. For example:
+///
+/// ```
+/// # use impl_tools::autoimpl;
+/// #[autoimpl(Clone, Debug where I: trait + Send + 'static, I::Item: trait)]
+/// struct Peekable<I: Iterator> {
+///     iter: I,
+///     peeked: Option<I::Item>,
+/// }
+/// ```
+///
+/// With `bound(used)`, a `T: trait` bound is dropped for any type parameter
+/// `T` used only by ignored fields or by fields of type `PhantomData<..>`
+/// (explicit bounds like `T: Clone` are kept, and `unsafe` targets like `Send`
+/// are not affected). Note that `Clone` still constructs ignored fields with
+/// `Default::default()`, which may require bounds of its own.
+/// For example, the following implements `Clone` for `Cached<T, C>` without
+/// requiring `C: Clone`:
+///
+/// ```
+/// # use impl_tools::autoimpl;
+/// #[autoimpl(Clone, Debug ignore self.cache bound(used) where T: trait, C: trait)]
+/// struct Cached<T, C> {
+///     value: T,
+///     cache: Option<C>,
+/// }
+/// ```
+///
+/// Fields of type `PhantomData<..>` are omitted from `Debug` output and
+/// constructed directly by `Clone` and `Default`. Only the paths
+/// `PhantomData`, `core::marker::PhantomData` and `std::marker::PhantomData`
+/// are recognised.
+///
+/// Since `Rc<T>` and `Arc<T>` are `Clone` for any `T`, with `bound(used)`
+/// `Clone` also drops a `T: trait` bound where `T` is used only within
+/// `Rc<..>`, `Arc<..>` or `Weak<..>`, written either alone or as a path in
+/// `std` or `alloc` such as `std::sync::Arc`. (This does not apply to
+/// `Box<T>`, which requires `T: Clone`, nor to other types named `Rc`.)
+///
+/// In place of a `where` clause, `bound = "PREDICATES"` (or unquoted,
+/// `bound = PREDICATES`) gives the bounds of the generated impl exactly,
+/// replacing any inferred bounds (for example the `T: Send` bounds inferred by
+/// `unsafe Send`, or the dropping of `T: trait` bounds described above).
+/// Like a `where` clause, this must come last. For example,
+/// `#[autoimpl(Clone bound = "I: Clone, I::Item: Clone")]`.
+///
+/// With `bound(fields)`, bounds are inferred from field types: for each field
+/// whose type uses a type parameter, the bound `FieldTy: trait` is added
+/// (fields which are ignored, not used due to `using`, of type
+/// `PhantomData<..>` or whose type refers to the item itself, such as
+/// `Vec<Self>`, are skipped).
+/// This avoids over-constrained impls where, for example, `Rc<T>` is `Clone`
+/// for any `T`. This is supported by `Clone`, `Copy`, `Debug`, `Default`,
+/// `PartialEq`, `Eq`, `PartialOrd`, `Ord` and `Hash`; a `where` clause may
+/// add further bounds.
+///
+/// ```
+/// # use impl_tools::autoimpl;
+/// # use std::rc::Rc;
+/// #[autoimpl(Clone bound(fields))]
+/// struct Shared<T> {
+///     value: Rc<T>,
+/// }
+///
+/// struct NotClone;
+/// let a = Shared { value: Rc::new(NotClone) };
+/// let _b = a.clone();
+/// ```
+///
+/// ### Configurable fields
+///
+/// Named fields may have `#[cfg(..)]` attributes. Generated code referring
+/// to such a field (struct expressions, patterns and statements) is subject
+/// to the same attributes. This is supported by `Clone`, `Copy`, `Debug`,
+/// `Default`, `PartialEq`, `Eq`, `PartialOrd`, `Ord`, `Hash`, `Send` and
+/// `Sync`; targets using a single field (e.g. `Deref using self.x`) only
+/// require that field to be unconfigured. Since removing a tuple field
+/// changes the indices of following fields, `#[cfg]` on tuple fields is an
+/// error.
+///
+/// ```
+/// # use impl_tools::autoimpl;
+/// #[autoimpl(Clone, Debug, Default)]
+/// struct Options {
+///     verbose: bool,
+///     #[cfg(feature = "color")]
+///     color: bool,
+/// }
+/// ```
+///
 /// ### `Deref::Target` type
 ///
 /// The [`Deref`] trait has two members:
@@ -207,6 +600,143 @@ pub fn impl_default(args: TokenStream, item: TokenStream) -> TokenStream {
 /// struct MyBoxingWrapper<T: ?Sized>(Box<T>);
 /// ```
 ///
+/// ### Inner-access methods
+///
+/// `Inner` is not a trait: `#[autoimpl(Inner using self.x)]` generates an
+/// inherent `impl` block with the following methods, where field `x` has type `X`:
+///
+/// - `pub fn into_inner(self) -> X`
+/// - `pub fn as_inner(&self) -> &X`
+/// - `pub fn as_inner_mut(&mut self) -> &mut X`
+///
+/// A different visibility may be given before the target, e.g.
+/// `#[autoimpl(pub(crate) Inner using self.x)]` or (for private methods)
+/// `#[autoimpl(pub(self) Inner using self.x)]`.
+///
+/// If field `x` is `#[deprecated]`, these methods are also deprecated.
+///
+/// This is usually combined with other targets `using` the same field:
+///
+/// ```
+/// # use impl_tools::autoimpl;
+/// #[autoimpl(Deref, DerefMut, Inner using self.0)]
+/// struct Meters(f64);
+///
+/// let mut m = Meters(1.5);
+/// *m.as_inner_mut() += 1.0;
+/// assert_eq!(m.into_inner(), 2.5);
+/// ```
+///
+/// ### String newtypes
+///
+/// `StrNewtype` is not a trait: it implements a bundle of traits for a struct
+/// wrapping a string field of type `S` (e.g. `String` or `Box<str>`):
+///
+/// - `Deref<Target = str>`, `AsRef<str>` and `Borrow<str>`
+/// - `Display` (forwarding to `str`)
+/// - `From<S>` (other fields, if any, use `Default::default()`), except on
+///   `#[non_exhaustive]` structs
+/// - `PartialEq<str>`
+///
+/// The field is specified via `using`, which may be omitted if the struct has
+/// only one field:
+///
+/// ```
+/// # use impl_tools::autoimpl;
+/// #[autoimpl(StrNewtype)]
+/// struct UserName(String);
+///
+/// let name = UserName::from("alice".to_string());
+/// assert_eq!(name.len(), 5);
+/// assert!(*name == *"alice");
+/// assert_eq!(format!("<{}>", name), "<alice>");
+/// ```
+///
+/// ### Field attributes
+///
+/// A field attribute `#[autoimpl(rename = "name")]` changes the name of that
+/// field as it appears in the output of `#[autoimpl(Debug)]` (and, where
+/// enabled, the keys of `Serialize` and `Deserialize`). It does not affect
+/// other generated code, for example names available to the format string of
+/// `#[impl_debug_display]`:
+///
+/// ```
+/// # use impl_tools::autoimpl;
+/// #[autoimpl(Debug)]
+/// struct Point {
+///     #[autoimpl(rename = "x")]
+///     x_coord: i32,
+///     y: i32,
+/// }
+///
+/// assert_eq!(format!("{:?}", Point { x_coord: 1, y: 2 }), "Point { x: 1, y: 2 }");
+/// ```
+///
+/// A field attribute `#[autoimpl(hidden)]` marks an internal field: it is
+/// omitted from `Debug` output and from documentation (`#[doc(hidden)]`), but
+/// is otherwise treated normally (e.g. by `Clone` and `#[impl_default]`):
+///
+/// ```
+/// # use impl_tools::{autoimpl, impl_scope};
+/// impl_scope! {
+///     #[impl_default]
+///     #[autoimpl(Clone, Debug)]
+///     pub struct Buffer {
+///         pub data: Vec<u8>,
+///         #[autoimpl(hidden)]
+///         pub revision: u64 = 1,
+///     }
+/// }
+///
+/// assert_eq!(format!("{:?}", Buffer::default()), "Buffer { data: [], .. }");
+/// ```
+///
+/// Field attributes `#[autoimpl(clone = EXPR)]`, `#[autoimpl(debug = EXPR)]`
+/// and `#[autoimpl(default = EXPR)]` supply a custom expression for that field,
+/// used respectively by `Clone` (instead of `self.FIELD.clone()`), `Debug`
+/// (the value printed) and `Default` (instead of `Default::default()`).
+/// Expressions for `clone` and `debug` may use `self`, thus are an error on
+/// fields of enum variants; `default` also applies to fields of the variant
+/// marked `#[default]`. With a `clone` expression, `Clone::clone_from` uses
+/// its default implementation, and `Copy` is an error (since `Clone` must be
+/// equivalent to a copy):
+///
+/// ```compile_fail
+/// # use impl_tools::autoimpl;
+/// #[autoimpl(Clone, Copy)]
+/// struct Counter {
+///     #[autoimpl(clone = self.n + 1)]
+///     n: u32,
+/// }
+/// ```
+///
+/// ```
+/// # use impl_tools::autoimpl;
+/// #[autoimpl(Clone, Debug, Default)]
+/// struct Connection {
+///     #[autoimpl(clone = self.id + 1, default = 1)]
+///     id: u32,
+///     #[autoimpl(debug = self.buffer.len())]
+///     buffer: Vec<u8>,
+/// }
+///
+/// let conn = Connection::default();
+/// assert_eq!(format!("{:?}", conn.clone()), "Connection { id: 2, buffer: 0 }");
+/// ```
+///
+/// A field attribute `#[autoimpl(using)]` designates the field used by targets
+/// requiring `using` (see above), keeping this next to the field definition:
+///
+/// ```
+/// # use impl_tools::autoimpl;
+/// #[autoimpl(Deref, DerefMut, AsRef)]
+/// struct Inventory {
+///     owner: String,
+///     #[autoimpl(using)]
+///     items: Vec<u32>,
+/// }
+/// ```
+///
 /// ### Examples
 ///
 /// Implement `std::fmt::Debug`, ignoring the last field:
@@ -227,6 +757,21 @@ pub fn impl_default(args: TokenStream, item: TokenStream) -> TokenStream {
 /// struct Wrapper<T>(pub T);
 /// ```
 ///
+/// Implement `PartialEq`, comparing all fields except a cache:
+/// ```
+/// # use impl_tools::autoimpl;
+/// # use std::cell::Cell;
+/// #[autoimpl(PartialEq ignore self.cache)]
+/// struct Text {
+///     text: String,
+///     cache: Cell<Option<usize>>,
+/// }
+///
+/// let a = Text { text: "a".into(), cache: Cell::new(None) };
+/// let b = Text { text: "a".into(), cache: Cell::new(Some(1)) };
+/// assert!(a == b);
+/// ```
+///
 /// Implement `Deref` and `DerefMut`, dereferencing to the given field:
 /// ```
 /// # use impl_tools::autoimpl;
@@ -242,7 +787,10 @@ pub fn impl_default(args: TokenStream, item: TokenStream) -> TokenStream {
 /// ### Parameter syntax
 ///
 /// > _ParamsTrait_ :\
-/// > &nbsp;&nbsp; `for` _Generics_ ( _Type_ ),+ _WhereClause_?
+/// > &nbsp;&nbsp; `for` _Generics_ ( _Type_ ),+ _Report_? _WhereClause_?
+/// >
+/// > _Report_ :\
+/// > &nbsp;&nbsp; `with` `report`
 ///
 /// **Targets:** the annotated trait is implemented for each *Type* listed.
 ///
@@ -261,10 +809,28 @@ pub fn impl_default(args: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// Generics and where clauses on types and methods are supported.
 ///
+/// Read-only targets (`&T`, `Rc<T>` and `Arc<T>`) do not support `DerefMut`.
+/// For these targets, methods taking `&mut self` are *not* forwarded: they use
+/// the trait's default implementation, even where `T` overrides the method.
+/// It is an error if no default implementation exists. Use `with report` to
+/// list such methods in the documentation of each impl. Targets using interior
+/// mutability (e.g. `Rc<RefCell<T>>`) are not supported, since a method
+/// returning a reference cannot outlive the borrow guard.
+///
 /// Items with a where clause with a type bound on `Self` are not supported
 /// since the item is not guaranteed to exist on the definitive type.
 /// Exception: methods with a default implementation (in this case the item is
-/// skipped).
+/// skipped). Methods whose only bound on `Self` is `Self: Sized` are forwarded
+/// when the definitive type is sized (i.e. does not have bound `?Sized`),
+/// provided they take `self` by reference and do not otherwise mention `Self`
+/// in their signature, and are otherwise skipped as above. This is decided for all targets together, not
+/// per target: though `Box<T>` is sized, forwarding calls `T::foo(..)`, which
+/// requires `T: Sized`.
+///
+/// With `with report`, each generated impl is documented with a list of
+/// forwarded methods and of methods using their default implementation, and
+/// of methods with bound `Self: Sized` (if any). This is visible in `rustdoc`
+/// output for the trait's implementors.
 ///
 /// ### Examples
 ///
@@ -282,13 +848,54 @@ pub fn impl_default(args: TokenStream, item: TokenStream) -> TokenStream {
 /// Note further: if the trait uses generic parameters itself, these must be
 /// introduced explicitly in the `for<..>` parameter list.
 ///
+/// ### Enum dispatch
+///
+/// > _DispatchTrait_ :\
+/// > &nbsp;&nbsp; `dispatch` _Generics_ _Type_ `{` ( _Variant_ ),+ `}` _WhereClause_?
+///
+/// The annotated trait is implemented for the enum *Type* by dispatch: each
+/// method matches over the listed variants, forwarding the call to the payload.
+/// Each listed variant must be a tuple variant with a single field whose type
+/// implements the trait. (The attribute is placed on the trait since a macro
+/// on the enum cannot see the trait's methods.)
+///
+/// Only methods with a receiver (`self`, `&self` or `&mut self`) are
+/// supported; other argument types and the return type may not mention `Self`.
+/// Associated types and constants are not supported.
+///
+/// ```
+/// # use impl_tools::autoimpl;
+/// #[autoimpl(dispatch Shape { Circle, Square })]
+/// trait Area {
+///     fn area(&self) -> f64;
+/// }
+///
+/// struct Circle(f64);
+/// impl Area for Circle {
+///     fn area(&self) -> f64 { 3.0 * self.0 * self.0 }
+/// }
+///
+/// struct Square(f64);
+/// impl Area for Square {
+///     fn area(&self) -> f64 { self.0 * self.0 }
+/// }
+///
+/// enum Shape {
+///     Circle(Circle),
+///     Square(Square),
+/// }
+///
+/// assert_eq!(Shape::Square(Square(2.0)).area(), 4.0);
+/// ```
+///
 /// [`Deref`]: std::ops::Deref
 #[proc_macro_attribute]
 #[proc_macro_error]
 pub fn autoimpl(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let mut toks = item.clone();
+    let mut toks = TokenStream::from(autoimpl::remove_field_attrs(item.clone().into()));
     match syn::parse::<autoimpl::Attr>(attr) {
         Ok(autoimpl::Attr::ForDeref(ai)) => toks.extend(TokenStream::from(ai.expand(item.into()))),
+        Ok(autoimpl::Attr::Dispatch(ai)) => toks.extend(TokenStream::from(ai.expand(item.into()))),
         Ok(autoimpl::Attr::ImplTraits(ai)) => {
             // We could use lazy_static to construct a HashMap for fast lookups,
             // but given the small number of impls a "linear map" is fine.
@@ -326,6 +933,50 @@ pub fn autoimpl(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// -   [`#[impl_default]`](macro@impl_default): implement [`Default`] using
 ///     field initializers (which are not legal syntax outside of `impl_scope!`)
+/// -   `#[impl_try_default(E)]`: generate an inherent method
+///     `fn try_default() -> Result<Self, E>` using field initializers, which
+///     may use the `?` operator (fields without an initializer use
+///     `Default::default()`); an optional `where` clause is supported, as is a
+///     validation function: `#[impl_try_default(E, validate = PATH)]` where
+///     `PATH(&value)` returns a `Result` with an error convertible to `E`
+/// -   `#[impl_new]`: generate an inherent method `fn new() -> Self` using
+///     field initializers (other fields use `Default::default()`). This is a
+///     `const fn` when all initializers are literals or tuples, arrays and
+///     integer operators over literals (and type parameters are unbounded).
+///     `#[impl_new(const)]` always generates a `const fn`, requiring an
+///     initializer on every field. An optional `where` clause is supported,
+///     as is a validation function: `#[impl_new(validate = PATH)]` (as for
+///     `#[impl_default]`; `new` is then not a `const fn`).
+///     The method is `#[must_use]`.
+/// -   `#[impl_debug_display("FORMAT", ARGS...)]`: implement
+///     [`Display`](core::fmt::Display) via `write!(f, "FORMAT", ARGS...)` and
+///     [`Debug`](core::fmt::Debug) structurally (as `#[autoimpl(Debug)]`,
+///     including the field attributes `rename`, `hidden` and `debug`).
+///     Named fields are available to the format string by name (e.g.
+///     `"{x}, {y}"`), unnamed fields as `_0`, `_1`, etc. Trailing arguments
+///     `redact self.FIELD, ...` redact fields from `Debug` output. An optional
+///     `where` clause applies to both impls.
+///
+/// Generated inherent methods have the visibility of the type by default. A
+/// visibility may be given as the first argument, e.g. `#[impl_new(pub(crate))]`
+/// or `#[impl_try_default(pub E)]`. Visibility is set per attribute, not per
+/// field: each generated method concerns the whole item.
+///
+/// As with `#[autoimpl]`, `#[cfg(..)]` attributes on named fields apply to
+/// generated initializers and parameters referring to those fields.
+///
+/// Malformed `#[autoimpl(..)]` field attributes are an error, also with
+/// `#[impl_debug_display]`:
+///
+/// ```compile_fail
+/// impl_tools::impl_scope! {
+///     #[impl_debug_display("{name}")]
+///     struct Named {
+///         #[autoimpl(rename)]
+///         name: &'static str,
+///     }
+/// }
+/// ```
 ///
 /// Note: matching these macros within `impl_scope!` does not use path
 /// resolution. Using `#[impl_tools::impl_default]` would resolve the variant
@@ -334,20 +985,25 @@ pub fn autoimpl(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// ## Syntax
 ///
 /// > _ImplScope_ :\
-/// > &nbsp;&nbsp; `impl_scope!` `{` _ScopeItem_ _ItemImpl_ * `}`
+/// > &nbsp;&nbsp; `impl_scope!` `{` _InnerAttribute_ * _ScopeItem_ _ItemImpl_ * `}`
 /// >
 /// > _ScopeItem_ :\
 /// > &nbsp;&nbsp; _ItemEnum_ | _ItemStruct_ | _ItemType_ | _ItemUnion_
 ///
 /// That is, one type definition followed by a set of implementations.
+/// Inner attributes (e.g. `#![impl_default]`) at the start of the scope are
+/// applied to the type definition, as if they were outer attributes on it.
 /// Impls must take one of two forms:
 ///
 /// -   `impl Self { ... }` — generic parameters and bounds of the type are used
 /// -   `impl MyType { ... }` where `MyType` matches the name of the defined type
 ///
+/// Trait impls take the same forms: `impl Trait for Self { ... }`. References
+/// to the type are also supported, e.g. `impl<'a> IntoIterator for &'a Self`.
+///
 /// Generic parameters from the type are included implicitly with the first form.
 /// Additional generic parameters and where clauses are supported (parameters
-/// and bounds are merged).
+/// and bounds are merged), e.g. `impl<'a> From<&'a str> for Self`.
 ///
 /// ## Example
 ///
@@ -373,10 +1029,18 @@ pub fn autoimpl(attr: TokenStream, item: TokenStream) -> TokenStream {
 #[proc_macro]
 pub fn impl_scope(input: TokenStream) -> TokenStream {
     let mut scope = parse_macro_input!(input as lib::Scope);
-    scope.apply_attrs(lib::find_attr_impl_default);
+    scope.apply_attrs(find_scope_attr);
     scope.expand().into()
 }
 
+/// Find a [`lib::ScopeAttr`] rule supported by `impl_scope!` and `singleton!`
+fn find_scope_attr(path: &syn::Path) -> Option<&'static dyn lib::ScopeAttr> {
+    lib::find_attr_impl_default(path)
+        .or_else(|| lib::find_attr_impl_try_default(path))
+        .or_else(|| lib::find_attr_impl_new(path))
+        .or_else(|| lib::find_attr_impl_debug_display(path))
+}
+
 /// Construct a single-instance struct
 ///
 /// Rust doesn't currently support [`impl Trait { ... }` expressions](https://github.com/canndrew/rfcs/blob/impl-trait-expressions/text/0000-impl-trait-expressions.md)
@@ -413,6 +1077,9 @@ pub fn impl_scope(input: TokenStream) -> TokenStream {
 ///
 /// The field name, `ident`, may be `_` (anonymous field).
 ///
+/// The field value, `value`, may refer to the singleton's type as `Self`, e.g.
+/// to use associated constants or functions defined by an `impl Self` block.
+///
 /// The field type, `ty`, may be or may contain inferred types (`_`) and/or
 /// `impl Trait` type expressions. These are substituted with generics on the
 /// type.
@@ -422,6 +1089,6 @@ pub fn impl_scope(input: TokenStream) -> TokenStream {
 #[proc_macro]
 pub fn singleton(input: TokenStream) -> TokenStream {
     let mut scope = parse_macro_input!(input as lib::Singleton).into_scope();
-    scope.apply_attrs(lib::find_attr_impl_default);
+    scope.apply_attrs(find_scope_attr);
     scope.expand().into()
 }
@@ -24,7 +24,7 @@ fn unit() {
     assert_eq!(format!("{:?}", Unit), "Unit");
 }
 
-#[autoimpl(Copy, Clone, Debug ignore self.1 where T: trait)]
+#[autoimpl(Copy, Clone, Debug(ignore self.1 where T: trait) where T: trait)]
 struct Wrapper<T>(pub T, ());
 
 #[test]
@@ -66,6 +66,656 @@ fn x() {
     test_has_clone(MaybeX::Some(x));
 }
 
+#[autoimpl(Clone, Debug where I: trait + Send + 'static, I::Item: trait)]
+struct Peekable<I: Iterator> {
+    iter: I,
+    peeked: Option<I::Item>,
+}
+
+#[autoimpl(Clone where <T as IntoIterator>::IntoIter: trait, T: 'static + trait)]
+struct IterPair<T: IntoIterator>(T, T::IntoIter);
+
+#[autoimpl(Clone, Debug where K: trait, V: Clone + Debug)]
+struct Entry<K, V>(K, V);
+
+#[test]
+fn trait_subst() {
+    let mut p = Peekable {
+        iter: [1, 2].into_iter(),
+        peeked: None,
+    };
+    p.peeked = p.iter.next();
+    let q = p.clone();
+    assert_eq!(
+        format!("{q:?}"),
+        "Peekable { iter: IntoIter([2]), peeked: Some(1) }"
+    );
+
+    let v = alloc::vec![1u8, 2];
+    let pair = IterPair(v.clone(), v.into_iter());
+    test_has_clone(pair);
+
+    let entry = Entry("k", 1u8).clone();
+    assert_eq!(format!("{entry:?}"), "Entry(\"k\", 1)");
+}
+
+#[autoimpl(Clone, Debug, PartialEq ignore self.0 bound(used) where T: trait, U: trait)]
+struct Marked<T, U>(PhantomData<T>, U);
+
+struct NotClone;
+
+#[test]
+fn marked() {
+    let a: Marked<NotClone, u32> = Marked(PhantomData, 7);
+    let b = a.clone();
+    assert_eq!(format!("{:?}", b), "Marked(_, 7)");
+    assert!(a == b);
+}
+
+#[autoimpl(Clone, Debug, Default bound(used) where T: trait, U: trait)]
+#[allow(unused)]
+struct Typed<T, U> {
+    id: U,
+    _type: PhantomData<T>,
+}
+
+#[autoimpl(Clone, Debug bound(used) where T: trait)]
+#[allow(unused)]
+enum TypedRef<T> {
+    Id(u32, PhantomData<T>),
+    Named {
+        name: &'static str,
+        _type: PhantomData<T>,
+    },
+}
+
+#[test]
+fn phantom() {
+    let a: Typed<NotClone, u32> = Default::default();
+    let b = Clone::clone(&Typed { id: 3, ..a });
+    assert_eq!(format!("{b:?}"), "Typed { id: 3 }");
+
+    let c = TypedRef::<NotClone>::Id(1, PhantomData).clone();
+    assert_eq!(format!("{c:?}"), "TypedRef::Id(1)");
+    let d = TypedRef::<NotClone>::Named {
+        name: "d",
+        _type: PhantomData,
+    };
+    assert_eq!(
+        format!("{:?}", d.clone()),
+        "TypedRef::Named { name: \"d\" }"
+    );
+}
+
+mod m {
+    // Not the real `PhantomData` or `Rc`: require `T: Clone` and `T: Debug`
+    #[derive(Clone, Debug)]
+    pub struct PhantomData<T>(pub T);
+    #[derive(Clone, Debug)]
+    pub struct Rc<T>(pub T);
+}
+
+#[autoimpl(Clone, Debug bound(used) where T: trait)]
+struct NotPhantom<T>(m::PhantomData<T>);
+
+#[test]
+fn not_phantom() {
+    let a = NotPhantom(m::PhantomData(1u8)).clone();
+    assert_eq!(format!("{a:?}"), "NotPhantom(PhantomData(1))");
+}
+
+#[autoimpl(Clone, Debug bound(used) where T: trait, U: trait)]
+struct SharedPair<T: ?Sized, U> {
+    left: alloc::sync::Arc<T>,
+    right: U,
+}
+
+#[autoimpl(Clone bound(used) where T: trait)]
+struct NotShared<T>(m::Rc<T>);
+
+#[test]
+fn shared_pointers() {
+    let a = SharedPair {
+        left: alloc::sync::Arc::new(NotClone),
+        right: 1u8,
+    };
+    let b = a.clone();
+    assert!(alloc::sync::Arc::ptr_eq(&a.left, &b.left));
+    assert_eq!(b.right, 1);
+
+    let c: SharedPair<str, ()> = SharedPair {
+        left: "c".into(),
+        right: (),
+    };
+    assert_eq!(
+        format!("{:?}", c.clone()),
+        "SharedPair { left: \"c\", right: () }"
+    );
+
+    let d = NotShared(m::Rc(2u8)).clone();
+    assert_eq!((d.0).0, 2);
+}
+
+#[autoimpl(PartialEq, Eq, Hash ignore self.0 bound(used) where T: trait, U: trait)]
+struct EqMarked<T, U>(PhantomData<T>, U);
+
+#[test]
+fn eq_marked() {
+    let a: EqMarked<f32, u8> = EqMarked(PhantomData, 1);
+    assert!(a == EqMarked(PhantomData, 1));
+    test_has_eq(a);
+}
+
+#[autoimpl(Debug with type_name where T: trait)]
+struct Named<T>(T);
+
+#[autoimpl(Debug with type_name where A: trait, B: trait)]
+enum Either<A, B> {
+    Left(A),
+    #[allow(unused)]
+    Right(B),
+}
+
+#[test]
+fn debug_type_name() {
+    // The format of `core::any::type_name` is unspecified
+    let s = format!("{:?}", Named(1i32));
+    assert!(s.starts_with("Named<") && s.ends_with(">(1)"), "{s}");
+    assert!(s.contains("i32"), "{s}");
+
+    let s = format!("{:?}", Named(Named(true)));
+    assert!(s.starts_with("Named<") && s.ends_with(">(true))"), "{s}");
+    assert!(s.contains("bool") && s.contains("(Named<"), "{s}");
+
+    let e: Either<u8, ()> = Either::Left(2);
+    let s = format!("{:?}", e);
+    assert!(s.starts_with("Either<") && s.ends_with(">::Left(2)"), "{s}");
+    assert!(s.contains("u8"), "{s}");
+}
+
+#[autoimpl(Hash using self.id)]
+struct Interned<'a> {
+    id: u32,
+    #[allow(unused)]
+    text: &'a str,
+}
+
+#[test]
+fn hash_using() {
+    let a = Interned { id: 3, text: "a" };
+    let b = Interned { id: 3, text: "b" };
+    assert_eq!(xx_hash_64_0(&a), xx_hash_64_0(&b));
+    assert_eq!(xx_hash_64_0(&a), xx_hash_64_0(3u32));
+}
+
+struct Handle(i32);
+impl Handle {
+    fn invalid() -> Self {
+        Handle(-1)
+    }
+}
+
+#[autoimpl(Clone ignore self.3 = Handle::invalid(), self.1 = 7)]
+struct Resource(&'static str, u8, Option<u8>, Handle);
+
+#[autoimpl(Clone ignore self.handle = Handle::invalid())]
+struct Session {
+    name: &'static str,
+    handle: Handle,
+}
+
+#[test]
+fn ignore_value() {
+    let s = Session {
+        name: "s",
+        handle: Handle(3),
+    };
+    let t = s.clone();
+    assert_eq!(
+        (s.handle.0, t.name, t.handle.0),
+        (3, "s", Handle::invalid().0)
+    );
+
+    let r = Resource("r", 1, Some(2), Handle(4)).clone();
+    assert_eq!((r.0, r.1, r.2, r.3 .0), ("r", 7, Some(2), -1));
+}
+
+#[autoimpl(PartialEq, Eq ignore self.metadata)]
+#[autoimpl(Hash ignore self.metadata, self.note)]
+struct Record {
+    id: u32,
+    note: &'static str,
+    #[allow(unused)]
+    metadata: f64,
+}
+
+#[autoimpl(Hash, PartialEq, Eq)]
+enum Key {
+    Name { name: alloc::string::String },
+}
+
+#[test]
+fn hash_ignore() {
+    let a = Record {
+        id: 1,
+        note: "a",
+        metadata: 0.5,
+    };
+    let b = Record {
+        id: 1,
+        note: "b",
+        metadata: 1.5,
+    };
+    assert!(a != b);
+    assert_eq!(xx_hash_64_0(&a), xx_hash_64_0(&b));
+    assert_eq!(xx_hash_64_0(&a), xx_hash_64_0(1u32));
+
+    let k = Key::Name { name: "k".into() };
+    assert_eq!(
+        xx_hash_64_0(&k),
+        xx_hash_64_0(Key::Name { name: "k".into() })
+    );
+}
+
+#[autoimpl(PartialEq, Eq, Hash ignore self.weight)]
+struct Weighted {
+    key: u32,
+    #[allow(unused)]
+    weight: f32,
+}
+
+#[test]
+fn float_ignored() {
+    let a = Weighted {
+        key: 1,
+        weight: 0.5,
+    };
+    let b = Weighted {
+        key: 1,
+        weight: 2.0,
+    };
+    test_has_eq(&a);
+    assert!(a == b);
+    assert_eq!(xx_hash_64_0(&a), xx_hash_64_0(&b));
+}
+
+#[autoimpl(Deref, DerefMut, AsRef, Inner)]
+#[repr(transparent)]
+struct Meters<T>(PhantomData<T>, f64);
+
+#[test]
+fn transparent() {
+    let mut m: Meters<()> = Meters(PhantomData, 2.0);
+    *m += 1.0;
+    let r: &f64 = m.as_ref();
+    assert_eq!(*r, 3.0);
+    assert_eq!(m.into_inner(), 3.0);
+}
+
+#[autoimpl(Deref, Borrow, Inner using self.(last))]
+#[allow(unused)]
+struct Labelled(&'static str, u32, alloc::string::String);
+
+#[test]
+fn using_last() {
+    use core::borrow::Borrow;
+    let l = Labelled("id", 1, "text".into());
+    assert_eq!(l.len(), 4);
+    let s: &alloc::string::String = l.borrow();
+    assert_eq!(s, "text");
+    assert_eq!(l.into_inner(), "text");
+}
+
+#[autoimpl(Clone, Deref, DerefMut, AsRef)]
+struct Inventory {
+    owner: &'static str,
+    #[autoimpl(using)]
+    items: alloc::vec::Vec<u32>,
+}
+
+#[test]
+fn using_field_attr() {
+    let mut inv = Inventory {
+        owner: "shop",
+        items: alloc::vec![1, 2],
+    };
+    inv.push(3);
+    assert_eq!(inv.len(), 3);
+    let r: &alloc::vec::Vec<u32> = inv.as_ref();
+    assert_eq!(r[2], 3);
+    assert_eq!(inv.owner, "shop");
+}
+
+// With feature "warnings", the first `using` is reported as redundant but not
+// the second, which changes what `PartialEq` compares
+#[autoimpl(Deref, AsRef using self.items)]
+#[autoimpl(PartialEq using self.items)]
+struct Stock {
+    #[allow(unused)]
+    owner: &'static str,
+    #[autoimpl(using)]
+    items: alloc::vec::Vec<u32>,
+}
+
+#[test]
+fn using_redundant() {
+    let a = Stock {
+        owner: "a",
+        items: alloc::vec![1],
+    };
+    let b = Stock {
+        owner: "b",
+        items: alloc::vec![1],
+    };
+    assert!(a == b);
+    let r: &alloc::vec::Vec<u32> = a.as_ref();
+    assert_eq!(r.len(), a.len());
+}
+
+#[autoimpl(Clone, Debug, Default)]
+struct Login {
+    user: &'static str,
+    #[autoimpl(clone = self.generation + 1, default = 1)]
+    generation: u32,
+    #[autoimpl(debug = self.token.len(), clone = alloc::string::String::new())]
+    token: alloc::string::String,
+}
+
+#[test]
+fn field_overrides() {
+    let session = Login {
+        user: "ann",
+        generation: 3,
+        token: "secret".into(),
+    };
+    let copy = session.clone();
+    assert_eq!(copy.generation, 4);
+    assert!(copy.token.is_empty());
+    assert_eq!(
+        format!("{session:?}"),
+        "Login { user: \"ann\", generation: 3, token: 6 }"
+    );
+    let default = Login::default();
+    assert_eq!((default.user, default.generation), ("", 1));
+}
+
+#[autoimpl(Debug, PartialEq, Eq, Hash only self.id, self.name)]
+#[allow(unused)]
+struct Reading {
+    id: u32,
+    name: &'static str,
+    raw: [u8; 4],
+    scale: f32,
+}
+
+#[test]
+fn only() {
+    let a = Reading {
+        id: 1,
+        name: "temp",
+        raw: [0; 4],
+        scale: 0.5,
+    };
+    let b = Reading {
+        id: 1,
+        name: "temp",
+        raw: [1; 4],
+        scale: 2.0,
+    };
+    assert!(a == b);
+    assert_eq!(xx_hash_64_0(&a), xx_hash_64_0(&b));
+    assert_eq!(format!("{a:?}"), "Reading { id: 1, name: \"temp\", .. }");
+}
+
+#[autoimpl(StrNewtype using self.name)]
+struct Tag {
+    name: alloc::string::String,
+    uses: u32,
+}
+
+#[test]
+fn str_newtype() {
+    use alloc::string::ToString;
+    use core::borrow::Borrow;
+
+    let mut tag = Tag::from("tools".to_string());
+    tag.uses += 1;
+    assert_eq!(tag.uses, 1);
+    assert!(tag.starts_with("to"));
+    let r: &str = tag.as_ref();
+    assert_eq!(r, "tools");
+    let b: &str = tag.borrow();
+    assert_eq!(b, "tools");
+    assert!(tag == *"tools");
+    assert_eq!(tag.to_string(), "tools");
+}
+
+#[autoimpl(PartialEq, Eq, PartialOrd, Ord, Hash by self.priority, self.name.len())]
+struct Job {
+    priority: u8,
+    name: &'static str,
+    #[allow(unused)]
+    cost: f64,
+}
+
+#[test]
+fn compare_by() {
+    let a = Job {
+        priority: 1,
+        name: "abc",
+        cost: 1.0,
+    };
+    let b = Job {
+        priority: 1,
+        name: "xyz",
+        cost: 2.0,
+    };
+    let c = Job {
+        priority: 1,
+        name: "long",
+        cost: 0.5,
+    };
+    let d = Job {
+        priority: 0,
+        name: "longest",
+        cost: 0.5,
+    };
+    assert!(a == b);
+    assert_eq!(xx_hash_64_0(&a), xx_hash_64_0(&b));
+    assert!(a < c);
+    assert_eq!(d.cmp(&a), core::cmp::Ordering::Less);
+}
+
+mod restricted {
+    use impl_tools::autoimpl;
+
+    #[autoimpl(pub(crate) Inner, Deref using self.0)]
+    pub struct Id(pub u32);
+}
+
+#[test]
+fn inner_visibility() {
+    let id = restricted::Id(7);
+    assert_eq!(*id.as_inner(), 7);
+    assert_eq!(*id, 7);
+}
+
+#[autoimpl(Inner using self.0)]
+struct Legacy(#[deprecated(note = "use a newer type")] u32);
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_inner() {
+    let mut legacy = Legacy(3);
+    *legacy.as_inner_mut() += 1;
+    assert_eq!(legacy.into_inner(), 4);
+}
+
+#[autoimpl(Clone)]
+#[autoimpl(Debug ignore self.secret)]
+struct Account {
+    #[autoimpl(rename = "user")]
+    login: &'static str,
+    #[allow(unused)]
+    secret: u64,
+}
+
+#[autoimpl(Debug)]
+#[allow(unused)]
+enum Shape {
+    Circle {
+        #[autoimpl(rename = "r")]
+        radius: u32,
+    },
+}
+
+// Field attributes may use the same paths as item attributes
+#[impl_tools::autoimpl(Debug)]
+#[impl_tools::autoimpl(Clone)]
+struct Qualified {
+    #[impl_tools::autoimpl(rename = "q")]
+    #[allow(unused)]
+    value: u8,
+}
+
+#[test]
+fn rename() {
+    let a = Account {
+        login: "ann",
+        secret: 1,
+    };
+    assert_eq!(a.login, "ann");
+    assert_eq!(format!("{:?}", a.clone()), "Account { user: \"ann\", .. }");
+    let c = Shape::Circle { radius: 2 };
+    assert_eq!(format!("{:?}", c), "Shape::Circle { r: 2 }");
+    let q = Qualified { value: 3 };
+    assert_eq!(format!("{:?}", q.clone()), "Qualified { q: 3 }");
+}
+
+#[autoimpl(Copy, Clone with copy where T: Copy)]
+struct Point<T> {
+    x: T,
+    y: T,
+}
+
+#[autoimpl(Clone where T: trait)]
+#[derive(Debug, PartialEq)]
+struct Sample<'a, T>(f32, &'a str, [(u8, char); 2], T);
+
+#[autoimpl(Clone)]
+#[derive(Debug, PartialEq)]
+enum Shared<'a> {
+    Value(u32, alloc::string::String),
+    Ref { r: &'a str },
+}
+
+#[test]
+fn clone_copy() {
+    let p = Point { x: 1, y: 2 };
+    let q = Clone::clone(&p);
+    test_has_copy(p);
+    assert_eq!((q.x, q.y), (1, 2));
+
+    let s = Sample(1.5, "a", [(1, 'b'); 2], alloc::vec![3]);
+    assert_eq!(s.clone(), s);
+
+    let v = Shared::Value(4, "v".into());
+    assert_eq!(v.clone(), v);
+    let r = Shared::Ref { r: "r" };
+    assert_eq!(r.clone(), r);
+}
+
+#[autoimpl(PartialEq ignore self.cache)]
+struct Cached {
+    words: alloc::vec::Vec<&'static str>,
+    cache: core::cell::Cell<Option<usize>>,
+}
+
+impl Cached {
+    fn len(&self) -> usize {
+        if let Some(len) = self.cache.get() {
+            return len;
+        }
+        let len = self.words.iter().map(|w| w.len()).sum();
+        self.cache.set(Some(len));
+        len
+    }
+}
+
+#[test]
+fn partial_eq_ignore_cache() {
+    let a = Cached {
+        words: alloc::vec!["a", "bc"],
+        cache: Default::default(),
+    };
+    let b = Cached {
+        words: alloc::vec!["a", "bc"],
+        cache: Default::default(),
+    };
+    assert_eq!(a.len(), 3);
+    assert!(a == b);
+    let c = Cached {
+        words: alloc::vec!["abc"],
+        cache: Default::default(),
+    };
+    assert_eq!(c.len(), 3);
+    assert!(a != c);
+}
+
+#[autoimpl(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct EmptyBraced {}
+
+#[autoimpl(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct EmptyTuple();
+
+#[test]
+fn zero_fields() {
+    use core::cmp::Ordering;
+
+    let a = EmptyBraced::default();
+    test_has_copy(a);
+    assert_eq!(format!("{:?}", a), "EmptyBraced");
+    assert!(a == EmptyBraced {});
+    assert_eq!(a.cmp(&a.clone()), Ordering::Equal);
+    assert_eq!(xx_hash_64_0(a), xx_hash_64_0(()));
+
+    let b = EmptyTuple::default();
+    test_has_copy(b);
+    assert_eq!(format!("{:?}", b), "EmptyTuple");
+    assert_eq!(b.partial_cmp(&EmptyTuple()), Some(Ordering::Equal));
+    assert_eq!(xx_hash_64_0(b), xx_hash_64_0(()));
+}
+
+#[autoimpl(StrNewtype, Clone)]
+#[non_exhaustive]
+struct Reserved(alloc::string::String);
+
+#[test]
+fn non_exhaustive() {
+    // From<String> is not implemented
+    let r = Reserved("id".into());
+    assert_eq!(r.clone().len(), 2);
+    assert_eq!(format!("{}", r), "id");
+}
+
+#[autoimpl(Clone, Debug ignore self.cache bound(used) where T: trait, C: trait)]
+struct Memo<T, C> {
+    value: T,
+    cache: Option<C>,
+}
+
+#[test]
+fn bound_used() {
+    let a: Memo<u8, NotClone> = Memo {
+        value: 3,
+        cache: Some(NotClone),
+    };
+    let b = a.clone();
+    assert_eq!(b.value, 3);
+    assert!(b.cache.is_none());
+    assert_eq!(format!("{b:?}"), "Memo { value: 3, .. }");
+}
+
 #[autoimpl(Deref, DerefMut using self.t)]
 #[autoimpl(Borrow, BorrowMut using self.t)]
 #[autoimpl(AsRef, AsMut using self.t)]
@@ -92,6 +742,19 @@ fn y() {
     assert_eq!(y.as_ref(), &12);
 }
 
+#[autoimpl(Deref, DerefMut, Inner using self.1)]
+struct Tagged<T>(&'static str, T);
+
+#[test]
+fn tagged() {
+    let mut x = Tagged("len", 3usize);
+    assert_eq!(*x.as_inner(), 3);
+    *x.as_inner_mut() += 1;
+    assert_eq!(*x, 4);
+    assert_eq!(x.0, "len");
+    assert_eq!(x.into_inner(), 4);
+}
+
 #[autoimpl(PartialEq, PartialOrd)]
 #[derive(Clone, Copy, Debug)]
 struct Pair(f32, f32);
@@ -191,3 +854,1085 @@ fn foo() {
         ptr,
     });
 }
+
+#[autoimpl(Display using self.value where T: trait)]
+struct Counted<T> {
+    _label: &'static str,
+    value: T,
+}
+
+#[autoimpl(Debug, Display)]
+#[repr(transparent)]
+struct Distance(f32);
+
+#[test]
+fn display() {
+    let l = Counted {
+        _label: "count",
+        value: 7,
+    };
+    assert_eq!(format!("{}", l), "7");
+    assert_eq!(format!("{:>3}", l), "  7");
+    assert_eq!(format!("{:.1}", Distance(2.25)), "2.2");
+}
+
+#[autoimpl(Display, LowerHex, UpperHex, Octal, Binary using self.0)]
+struct Flags(u8);
+
+#[autoimpl(Pointer using self.0)]
+struct ByteRef(&'static u8);
+
+#[test]
+fn fmt_traits() {
+    let f = Flags(42);
+    assert_eq!(
+        format!("{} {:x} {:#X} {:o} {:08b}", f, f, f, f, f),
+        "42 2a 0x2A 52 00101010"
+    );
+
+    static BYTE: u8 = 0;
+    let h = ByteRef(&BYTE);
+    assert_eq!(format!("{:p}", h), format!("{:p}", &BYTE));
+}
+
+#[autoimpl(From, Into using self.0)]
+#[derive(Debug, PartialEq)]
+struct Id(u64);
+
+#[autoimpl(From using self.value)]
+#[derive(Debug, PartialEq)]
+struct Versioned<T> {
+    version: u32,
+    value: T,
+}
+
+#[test]
+fn from() {
+    assert_eq!(Id::from(3), Id(3));
+    assert_eq!(u64::from(Id(4)), 4);
+    let v: Versioned<&str> = "a".into();
+    assert_eq!(
+        v,
+        Versioned {
+            version: 0,
+            value: "a"
+        }
+    );
+}
+
+#[autoimpl(AsRef<str>, AsMut<str> using self.name)]
+#[autoimpl(AsRef using self.tags)]
+struct Person {
+    name: alloc::string::String,
+    tags: alloc::vec::Vec<u8>,
+}
+
+#[test]
+fn as_ref_target() {
+    let mut p = Person {
+        name: "ann".into(),
+        tags: alloc::vec![1, 2],
+    };
+    AsMut::<str>::as_mut(&mut p).make_ascii_uppercase();
+    assert_eq!(AsRef::<str>::as_ref(&p), "ANN");
+    assert_eq!(AsRef::<alloc::vec::Vec<u8>>::as_ref(&p).len(), 2);
+}
+
+#[autoimpl(Borrow<str> using self.0)]
+#[autoimpl(PartialEq, Eq, PartialOrd, Ord)]
+struct MapKey(alloc::string::String);
+
+#[test]
+fn borrow_target() {
+    let mut map = alloc::collections::BTreeMap::new();
+    map.insert(MapKey("a".into()), 1);
+    assert_eq!(map.get("a"), Some(&1));
+}
+
+#[autoimpl(Iterator using self.iter where I: trait)]
+struct TaggedIter<I> {
+    iter: I,
+    _label: &'static str,
+}
+
+#[test]
+fn iterator() {
+    let mut iter = TaggedIter {
+        iter: 1..4,
+        _label: "range",
+    };
+    assert_eq!(iter.size_hint(), (3, Some(3)));
+    assert_eq!(iter.next(), Some(1));
+    assert_eq!(iter.sum::<i32>(), 5);
+}
+
+#[autoimpl(IntoIterator using self.items)]
+struct Bag<T> {
+    items: alloc::vec::Vec<T>,
+}
+
+#[autoimpl(IntoIterator using self.0 with by_ref)]
+struct RefOnly(alloc::vec::Vec<u8>);
+
+// Each impl is bounded on the field type (or a reference to it)
+#[autoimpl(IntoIterator using self.0)]
+struct Source<I>(I);
+
+#[test]
+fn into_iterator() {
+    let mut bag = Bag {
+        items: alloc::vec![1, 2, 3],
+    };
+    for x in &mut bag {
+        *x *= 2;
+    }
+    assert_eq!((&bag).into_iter().sum::<i32>(), 12);
+    assert_eq!(bag.into_iter().collect::<alloc::vec::Vec<_>>(), [2, 4, 6]);
+
+    let r = RefOnly(alloc::vec![5]);
+    assert_eq!((&r).into_iter().next(), Some(&5));
+
+    let s = Source(alloc::vec![1u8, 2]);
+    assert_eq!((&s).into_iter().count(), 2);
+    assert_eq!(s.into_iter().sum::<u8>(), 3);
+    assert_eq!(Source(0..4).into_iter().sum::<i32>(), 6);
+}
+
+#[autoimpl(FromIterator, Extend using self.items)]
+#[derive(Debug, PartialEq)]
+struct Log<T> {
+    items: alloc::vec::Vec<T>,
+    dirty: bool,
+}
+
+#[test]
+fn from_iterator_extend() {
+    let mut log: Log<u8> = (1..3).collect();
+    assert_eq!(
+        log,
+        Log {
+            items: alloc::vec![1, 2],
+            dirty: false
+        }
+    );
+    log.extend([3, 4]);
+    log.extend(&[5]);
+    assert_eq!(log.items, [1, 2, 3, 4, 5]);
+}
+
+#[autoimpl(Index<usize>, IndexMut<usize> using self.data)]
+struct Row {
+    data: [u8; 4],
+}
+
+#[autoimpl(Index, IndexMut using self.0)]
+struct Slots<T>(alloc::vec::Vec<T>);
+
+#[test]
+fn index() {
+    let mut row = Row { data: [0; 4] };
+    row[1] = 3;
+    assert_eq!(row[1], 3);
+
+    let mut slots = Slots(alloc::vec![1, 2, 3]);
+    slots[0] = 7;
+    assert_eq!(&slots[..2], &[7, 2]);
+}
+
+// The generated parameter must precede const parameters
+#[autoimpl(Extend, Index, IndexMut using self.0)]
+struct Chunk<T, const N: usize>(alloc::vec::Vec<T>);
+
+#[autoimpl(RangeBounds using self.0)]
+struct Stride<const N: usize>(core::ops::Range<u32>);
+
+#[test]
+fn const_generic_item_param() {
+    use core::ops::RangeBounds;
+
+    let mut chunk = Chunk::<u8, 4>(alloc::vec![1]);
+    chunk.extend([2, 3]);
+    chunk[0] = 5;
+    assert_eq!(&chunk[..], &[5, 2, 3]);
+
+    let stride = Stride::<2>(1..3);
+    assert!(stride.contains(&2));
+    assert!(!stride.contains(&3));
+}
+
+#[autoimpl(Add, Sub, Mul, Div, Rem)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pair2(i32, i32);
+
+#[autoimpl(Add where T: core::ops::Add<Output = T>)]
+#[derive(Debug, PartialEq)]
+struct Sum<T>(T);
+
+#[autoimpl(Add, Sub, AddAssign, MulAssign ignore self.id)]
+#[derive(Debug, PartialEq)]
+struct Tally {
+    id: u32,
+    count: i64,
+}
+
+#[test]
+fn arithmetic() {
+    let (a, b) = (Pair2(6, 9), Pair2(4, 2));
+    assert_eq!(a + b, Pair2(10, 11));
+    assert_eq!(a - b, Pair2(2, 7));
+    assert_eq!(a * b, Pair2(24, 18));
+    assert_eq!(a / b, Pair2(1, 4));
+    assert_eq!(a % b, Pair2(2, 1));
+    assert_eq!(Sum(1.5) + Sum(2.0), Sum(3.5));
+
+    let t = Tally { id: 1, count: 5 } - Tally { id: 2, count: 7 };
+    assert_eq!(t, Tally { id: 1, count: -2 });
+
+    let mut t = t;
+    t += Tally { id: 3, count: 6 };
+    t *= Tally { id: 4, count: 3 };
+    assert_eq!(t, Tally { id: 1, count: 12 });
+}
+
+#[autoimpl(BitAnd, BitOr, BitXor, Shl, Shr)]
+#[autoimpl(BitAndAssign, BitOrAssign, BitXorAssign, ShlAssign, ShrAssign)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Mask {
+    read: u8,
+    write: u8,
+}
+
+#[test]
+fn bitwise() {
+    let a = Mask {
+        read: 0b1100,
+        write: 0b0011,
+    };
+    let b = Mask {
+        read: 0b1010,
+        write: 1,
+    };
+    assert_eq!(
+        a & b,
+        Mask {
+            read: 0b1000,
+            write: 1
+        }
+    );
+    assert_eq!(
+        a | b,
+        Mask {
+            read: 0b1110,
+            write: 0b0011
+        }
+    );
+    assert_eq!(
+        a ^ b,
+        Mask {
+            read: 0b0110,
+            write: 0b0010
+        }
+    );
+    let shift = Mask { read: 2, write: 1 };
+    assert_eq!(
+        a << shift,
+        Mask {
+            read: 0b110000,
+            write: 0b0110
+        }
+    );
+    assert_eq!(
+        a >> shift,
+        Mask {
+            read: 0b0011,
+            write: 0b0001
+        }
+    );
+
+    let mut c = a;
+    c &= b;
+    c |= Mask { read: 1, write: 0 };
+    c ^= Mask { read: 0, write: 1 };
+    c <<= Mask { read: 1, write: 0 };
+    c >>= Mask { read: 0, write: 0 };
+    assert_eq!(
+        c,
+        Mask {
+            read: 0b10010,
+            write: 0
+        }
+    );
+}
+
+#[autoimpl(TryFrom<i64>, TryFrom<u32> using self.value)]
+#[derive(Debug, PartialEq)]
+struct Small {
+    value: u8,
+    _note: (),
+}
+
+#[autoimpl(TryFrom<&[u8]> using self.0)]
+struct Code([u8; 2]);
+
+#[test]
+fn try_from() {
+    use core::convert::TryFrom;
+
+    assert_eq!(
+        Small::try_from(7i64),
+        Ok(Small {
+            value: 7,
+            _note: ()
+        })
+    );
+    assert!(Small::try_from(300u32).is_err());
+    assert_eq!(
+        Code::try_from(&b"ab"[..]).ok().map(|code| code.0),
+        Some(*b"ab")
+    );
+    assert!(Code::try_from(&b"abc"[..]).is_err());
+}
+
+#[autoimpl(core::fmt::Write using self.buf)]
+struct Builder {
+    buf: alloc::string::String,
+}
+
+#[test]
+fn fmt_write() {
+    use core::fmt::Write;
+
+    let mut b = Builder {
+        buf: alloc::string::String::new(),
+    };
+    b.write_str("a").unwrap();
+    b.write_char('b').unwrap();
+    write!(b, "{}", 3).unwrap();
+    assert_eq!(b.buf, "ab3");
+}
+
+#[autoimpl(unsafe Send, unsafe Sync)]
+struct RawBox<T> {
+    ptr: *mut T,
+}
+
+#[autoimpl(unsafe Send where T: Copy + Send)]
+enum RawSlot<T> {
+    Empty,
+    Full(*const T),
+}
+
+#[test]
+fn unsafe_send_sync() {
+    fn require_send<T: Send>(_: &T) {}
+    fn require_sync<T: Sync>(_: &T) {}
+
+    let b = RawBox::<u8> {
+        ptr: core::ptr::null_mut(),
+    };
+    require_send(&b);
+    require_sync(&b);
+    assert!(b.ptr.is_null());
+
+    let slots = [RawSlot::<u8>::Empty, RawSlot::Full(core::ptr::null())];
+    require_send(&slots);
+    assert!(matches!(slots[0], RawSlot::Empty));
+    assert!(matches!(slots[1], RawSlot::Full(p) if p.is_null()));
+}
+
+#[autoimpl(Clone bound = "T: Clone, T::Item: Clone")]
+#[autoimpl(Debug bound = T::Item: Debug, T: Debug)]
+struct Buffered<T: Iterator> {
+    source: T,
+    items: alloc::vec::Vec<T::Item>,
+}
+
+#[autoimpl(unsafe Send bound = "")]
+struct AnyPtr<T> {
+    ptr: *const T,
+}
+
+#[test]
+fn bound_override() {
+    fn require_send<T: Send>(_: &T) {}
+
+    let b = Buffered {
+        source: 0..2u8,
+        items: alloc::vec![7u8],
+    };
+    let c = b.clone();
+    assert_eq!(c.items, [7]);
+    assert_eq!(c.source, 0..2);
+    assert_eq!(format!("{c:?}"), "Buffered { source: 0..2, items: [7] }");
+
+    // Send regardless of T, unlike the default bound `T: Send`
+    let p = AnyPtr::<*const u8> {
+        ptr: core::ptr::null(),
+    };
+    require_send(&p);
+    assert!(p.ptr.is_null());
+}
+
+#[autoimpl(Clone, Debug, PartialEq bound(fields))]
+struct RcHandle<T> {
+    value: alloc::rc::Rc<T>,
+    marker: PhantomData<T>,
+}
+
+#[autoimpl(Clone, PartialEq bound(fields) where T: Copy)]
+enum RcOrByte<T> {
+    Left(alloc::rc::Rc<T>),
+    Right(u8),
+}
+
+#[autoimpl(Clone, Debug, PartialEq bound(fields))]
+struct Tree<T> {
+    value: T,
+    children: alloc::vec::Vec<Tree<T>>,
+    parent: Option<alloc::rc::Rc<Self>>,
+}
+
+#[test]
+fn bound_fields() {
+    // Neither Clone nor Debug nor PartialEq
+    struct Opaque;
+
+    let a = RcHandle {
+        value: alloc::rc::Rc::new(Opaque),
+        marker: PhantomData,
+    };
+    let b = a.clone();
+    assert_eq!(alloc::rc::Rc::strong_count(&b.value), 2);
+
+    let c = RcHandle {
+        value: alloc::rc::Rc::new(1),
+        marker: PhantomData,
+    };
+    assert_eq!(format!("{c:?}"), "RcHandle { value: 1 }");
+    assert!(c == c.clone());
+
+    let e = RcOrByte::<u8>::Left(alloc::rc::Rc::new(2));
+    assert!(e == e.clone());
+    assert!(RcOrByte::<u8>::Right(1) != e);
+
+    let leaf = Tree {
+        value: 2,
+        children: alloc::vec![],
+        parent: None,
+    };
+    let tree = Tree {
+        value: 1,
+        children: alloc::vec![leaf.clone()],
+        parent: None,
+    };
+    assert!(tree == tree.clone());
+    assert_eq!(
+        format!("{leaf:?}"),
+        "Tree { value: 2, children: [], parent: None }"
+    );
+}
+
+#[autoimpl(PartialEq<str>, PartialEq<alloc::string::String> using self.name)]
+#[autoimpl(PartialEq<u32> using self.id)]
+struct Label {
+    id: u32,
+    name: alloc::string::String,
+}
+
+#[repr(transparent)]
+#[autoimpl(PartialEq<[u8]>, PartialEq)]
+struct Bytes(alloc::vec::Vec<u8>);
+
+#[test]
+fn partial_eq_other() {
+    let tag = Label {
+        id: 3,
+        name: "red".into(),
+    };
+    assert!(tag == *"red");
+    assert!(tag != *"blue");
+    let name = alloc::string::String::from("red");
+    assert!(tag == name);
+    assert!(tag == 3);
+    assert!(tag != 4);
+
+    let bytes = Bytes(alloc::vec![1, 2]);
+    assert!(bytes == [1u8, 2][..]);
+    assert!(bytes == Bytes(alloc::vec![1, 2]));
+}
+
+#[autoimpl(PartialEq, Eq, PartialOrd, Ord, Hash using self.id)]
+struct Ticket {
+    id: u32,
+    weight: f32,
+    name: &'static str,
+}
+
+#[test]
+fn compare_using_key() {
+    use core::cmp::Ordering;
+
+    let a = Ticket {
+        id: 1,
+        weight: 0.5,
+        name: "a",
+    };
+    let b = Ticket {
+        id: 1,
+        weight: 2.0,
+        name: "b",
+    };
+    let c = Ticket {
+        id: 2,
+        weight: 0.0,
+        name: "a",
+    };
+    assert!(a == b);
+    assert!(a != c);
+    assert_eq!(a.cmp(&b), Ordering::Equal);
+    assert_eq!(a.cmp(&c), Ordering::Less);
+    assert_eq!(c.partial_cmp(&b), Some(Ordering::Greater));
+    assert_eq!(xx_hash_64_0(&a), xx_hash_64_0(&b));
+    assert_eq!((a.weight, b.name), (0.5, "b"));
+}
+
+#[autoimpl(Clone ignore self.hits)]
+struct Scratch {
+    data: alloc::vec::Vec<u8>,
+    len: usize,
+    hits: u32,
+}
+
+#[test]
+fn clone_from() {
+    let mut a = Scratch {
+        data: alloc::vec::Vec::with_capacity(64),
+        len: 0,
+        hits: 5,
+    };
+    let b = Scratch {
+        data: alloc::vec![1, 2, 3],
+        len: 3,
+        hits: 7,
+    };
+    a.clone_from(&b);
+    assert_eq!(a.data, [1, 2, 3]);
+    assert!(a.data.capacity() >= 64);
+    assert_eq!(a.len, 3);
+    assert_eq!(a.hits, 0);
+    assert_eq!(b.hits, 7);
+}
+
+#[autoimpl(Debug redact self.password, self.token)]
+struct Credentials {
+    user: &'static str,
+    password: &'static str,
+    token: u64,
+}
+
+#[autoimpl(Debug redact self.1)]
+struct ApiKey(u32, &'static str);
+
+#[test]
+fn debug_redact() {
+    let c = Credentials {
+        user: "ann",
+        password: "hunter2",
+        token: 42,
+    };
+    assert_eq!(
+        format!("{c:?}"),
+        r#"Credentials { user: "ann", password: ***, token: *** }"#
+    );
+    assert_eq!((c.user, c.password, c.token), ("ann", "hunter2", 42));
+
+    let key = ApiKey(1, "secret");
+    assert_eq!(format!("{key:?}"), "ApiKey(1, ***)");
+    assert_eq!((key.0, key.1), (1, "secret"));
+}
+
+#[autoimpl(Debug fmt self.addr as format_args!("{:#x}", self.addr), self.flags as self.flags.count_ones())]
+#[allow(unused)]
+struct Mapping {
+    addr: usize,
+    flags: u8,
+    len: usize,
+}
+
+#[autoimpl(Debug fmt self.0 as format_args!("{:08b}", self.0) redact self.1)]
+#[allow(unused)]
+struct BitPair(u8, u8);
+
+#[test]
+fn debug_fmt() {
+    let m = Mapping {
+        addr: 0x1000,
+        flags: 0b101,
+        len: 16,
+    };
+    assert_eq!(
+        format!("{m:?}"),
+        "Mapping { addr: 0x1000, flags: 2, len: 16 }"
+    );
+    assert_eq!(format!("{:?}", BitPair(5, 1)), "BitPair(00000101, ***)");
+}
+
+#[autoimpl(Hasher using self.inner)]
+struct LabelledHasher {
+    _label: &'static str,
+    inner: twox_hash::XxHash64,
+}
+
+#[test]
+fn hasher() {
+    use core::hash::{Hash, Hasher};
+
+    let mut hasher = LabelledHasher {
+        _label: "xx",
+        inner: twox_hash::XxHash64::with_seed(0),
+    };
+    (1u8, -2i64, 3u128, "four").hash(&mut hasher);
+    assert_eq!(hasher.finish(), xx_hash_64_0((1u8, -2i64, 3u128, "four")));
+}
+
+#[autoimpl(RangeBounds using self.0)]
+struct Window(core::ops::Range<u32>);
+
+#[autoimpl(RangeBounds<usize> using self.range)]
+struct Limits {
+    range: core::ops::RangeInclusive<usize>,
+}
+
+#[test]
+fn range_bounds() {
+    use core::ops::{Bound, RangeBounds};
+
+    let w = Window(2..5);
+    assert_eq!(w.start_bound(), Bound::Included(&2));
+    assert_eq!(w.end_bound(), Bound::Excluded(&5));
+    assert!(w.contains(&4));
+    assert!(!w.contains(&5));
+
+    let v = [0, 1, 2, 3, 4, 5];
+    let limits = Limits { range: 1..=3 };
+    assert_eq!(
+        &v[(limits.start_bound().cloned(), limits.end_bound().cloned())],
+        [1, 2, 3]
+    );
+    assert!(limits.contains(&3));
+}
+
+#[autoimpl(Debug, Default)]
+#[autoimpl(PartialEq)]
+enum Fill {
+    None,
+    #[default]
+    Solid {
+        color: u32,
+        #[autoimpl(default = 0xff)]
+        alpha: u8,
+    },
+    Pattern(u8),
+}
+
+#[autoimpl(Default where T: trait)]
+enum Slot<T> {
+    #[allow(unused)]
+    Empty,
+    #[default]
+    Full(T),
+}
+
+#[test]
+fn default_enum() {
+    let solid = Fill::Solid {
+        color: 0,
+        alpha: 0xff,
+    };
+    assert_eq!(Fill::default(), solid);
+    assert_ne!(Fill::None, Fill::Pattern(1));
+    assert!(matches!(Slot::<u8>::default(), Slot::Full(0)));
+}
+
+#[autoimpl(PartialEq, Eq, Hash ignore self.stamp, self.1)]
+#[allow(unused)]
+enum Msg {
+    Ping { seq: u32, stamp: u64 },
+    Data(u8, u64),
+    Close { stamp: u64 },
+    Quit,
+}
+
+#[test]
+fn enum_eq_hash_ignore() {
+    let a = Msg::Ping { seq: 1, stamp: 10 };
+    let b = Msg::Ping { seq: 1, stamp: 20 };
+    assert!(a == b);
+    assert_eq!(xx_hash_64_0(&a), xx_hash_64_0(&b));
+    assert!(a != Msg::Ping { seq: 2, stamp: 10 });
+
+    assert!(Msg::Data(1, 5) == Msg::Data(1, 6));
+    assert!(Msg::Data(1, 5) != Msg::Data(2, 5));
+    assert!(Msg::Close { stamp: 1 } == Msg::Close { stamp: 2 });
+    assert!(Msg::Close { stamp: 1 } != Msg::Quit);
+    assert_ne!(
+        xx_hash_64_0(Msg::Close { stamp: 0 }),
+        xx_hash_64_0(Msg::Quit)
+    );
+}
+
+#[autoimpl(PartialEq, Eq, PartialOrd, Ord ignore self.note)]
+#[derive(Debug)]
+#[allow(unused)]
+enum Priority {
+    Low,
+    Medium(u8, i32),
+    High { level: u8, note: &'static str },
+}
+
+#[test]
+fn enum_ord() {
+    use Priority::*;
+
+    let mut list = [
+        High {
+            level: 2,
+            note: "b",
+        },
+        Medium(1, 0),
+        Low,
+        Medium(0, 5),
+        High {
+            level: 1,
+            note: "a",
+        },
+    ];
+    list.sort();
+    assert_eq!(
+        list,
+        [
+            Low,
+            Medium(0, 5),
+            Medium(1, 0),
+            High {
+                level: 1,
+                note: "a"
+            },
+            High {
+                level: 2,
+                note: "b"
+            },
+        ]
+    );
+    assert_eq!(
+        High {
+            level: 1,
+            note: "x"
+        }
+        .partial_cmp(&High {
+            level: 1,
+            note: "y"
+        }),
+        Some(core::cmp::Ordering::Equal)
+    );
+    assert!(Medium(0, 1) < Medium(0, 2));
+}
+
+#[autoimpl(Copy, Clone, Eq)]
+#[autoimpl(unsafe Send, unsafe Sync)]
+union IntOrFloat {
+    int: u32,
+    float: f32,
+}
+
+impl PartialEq for IntOrFloat {
+    fn eq(&self, other: &Self) -> bool {
+        unsafe { self.int == other.int }
+    }
+}
+
+#[test]
+fn union_markers() {
+    fn require_send_sync<T: Send + Sync>(_: &T) {}
+
+    let a = IntOrFloat { float: 1.0 };
+    let b = Clone::clone(&a);
+    let c = a;
+    require_send_sync(&c);
+    assert!(a == b && b == c);
+    assert_eq!(unsafe { b.int }, 1.0f32.to_bits());
+}
+
+mod facade {
+    pub mod core {
+        pub use ::core::{clone, cmp, convert, fmt, hash};
+    }
+}
+
+#[autoimpl(Clone, Debug, PartialEq, Hash, crate = facade)]
+struct Facaded {
+    a: u8,
+    b: Option<u8>,
+}
+
+// User paths are not resolved through `facade` (which lacks `core::option`)
+#[autoimpl(Clone, Debug, PartialEq, crate = facade)]
+#[autoimpl(From, crate = facade using self.0)]
+struct Verbatim(::core::option::Option<u8>);
+
+#[test]
+fn crate_path() {
+    let x = Facaded { a: 1, b: Some(2) };
+    let y = x.clone();
+    assert!(x == y);
+    assert_eq!(format!("{y:?}"), "Facaded { a: 1, b: Some(2) }");
+    assert_eq!(xx_hash_64_0(&x), xx_hash_64_0(&y));
+
+    let v = Verbatim::from(Some(3));
+    assert!(v == v.clone());
+    assert_eq!(format!("{v:?}"), "Verbatim(Some(3))");
+}
+
+#[autoimpl(Clone, Debug, const)]
+struct Isolated(u8);
+
+#[autoimpl(Clone, PartialEq, Hash, const, crate = facade)]
+struct IsolatedFacaded {
+    a: u8,
+}
+
+#[test]
+fn const_block() {
+    let x = Isolated(3);
+    assert_eq!(format!("{:?}", x.clone()), "Isolated(3)");
+    assert_eq!(x.0, 3);
+
+    let y = IsolatedFacaded { a: 4 };
+    let z = y.clone();
+    assert!(y == z);
+    assert_eq!(xx_hash_64_0(&y), xx_hash_64_0(&z));
+}
+
+#[autoimpl(Clone, Debug, cfg(test))]
+struct TestOnly(u8);
+
+#[autoimpl(Clone, cfg(not(test)))]
+struct NotTest;
+
+// Would conflict with the generated impl if not cfg-disabled
+impl Clone for NotTest {
+    fn clone(&self) -> Self {
+        NotTest
+    }
+}
+
+// Targets gated by different predicates do not conflict
+#[autoimpl(Debug, cfg(test))]
+#[autoimpl(Debug, cfg(not(test)) ignore self.0)]
+struct EitherCfg(u8);
+
+#[test]
+fn cfg_impls() {
+    let x = TestOnly(5);
+    assert_eq!(format!("{:?}", x.clone()), "TestOnly(5)");
+    assert_eq!(x.0, 5);
+    let _ = NotTest.clone();
+    let e = EitherCfg(7);
+    assert_eq!(format!("{e:?}"), "EitherCfg(7)");
+    assert_eq!(e.0, 7);
+}
+
+#[autoimpl(PartialEq, Debug, test_only = "serde")]
+struct Fixture {
+    a: u8,
+    b: [u8; 3],
+}
+
+#[autoimpl(Clone, Debug, test_only)]
+struct Probe(u8);
+
+#[autoimpl(Clone, test_only = "serde", cfg(not(test)))]
+struct TestOnlyNotTest;
+
+impl Clone for TestOnlyNotTest {
+    fn clone(&self) -> Self {
+        TestOnlyNotTest
+    }
+}
+
+#[test]
+fn test_only() {
+    let x = Fixture { a: 1, b: [2; 3] };
+    assert_eq!(x, Fixture { a: 1, b: [2; 3] });
+    let _ = TestOnlyNotTest.clone();
+    let probe = Probe(6).clone();
+    assert_eq!(format!("{probe:?}"), "Probe(6)");
+    assert_eq!(probe.0, 6);
+}
+
+/// Generated code must not depend on `core`, prelude traits or macros in scope
+mod shadowed {
+    #![allow(unused)]
+    use impl_tools::autoimpl;
+
+    mod core {}
+    trait Clone {}
+    trait Default {}
+    macro_rules! write {
+        ($($tt:tt)*) => {
+            compile_error!("shadowed")
+        };
+    }
+    macro_rules! format_args {
+        ($($tt:tt)*) => {
+            compile_error!("shadowed")
+        };
+    }
+
+    #[autoimpl(Clone, Debug, Default)]
+    pub struct Secret {
+        #[autoimpl(hidden)]
+        pub key: u32,
+        pub name: &'static str,
+    }
+
+    #[autoimpl(Clone, Debug)]
+    pub enum Access {
+        Read(u32),
+        Write { id: u32 },
+    }
+}
+
+#[test]
+fn shadowed() {
+    let s = shadowed::Secret { key: 1, name: "s" };
+    let mut t = Default::default();
+    Clone::clone_from(&mut t, &s);
+    assert_eq!(format!("{t:?}"), "Secret { name: \"s\", .. }");
+
+    let a = shadowed::Access::Write { id: 2 }.clone();
+    assert_eq!(format!("{a:?}"), "Access::Write { id: 2 }");
+    let r = shadowed::Access::Read(3).clone();
+    assert_eq!(format!("{r:?}"), "Access::Read(3)");
+}
+
+#[autoimpl(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Configured {
+    #[cfg(not(test))]
+    missing: Missing,
+    first: u8,
+    #[cfg(test)]
+    second: u8,
+}
+
+#[autoimpl(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Configurable {
+    #[default]
+    Pair {
+        #[cfg(not(test))]
+        missing: Missing,
+        first: u8,
+        #[cfg(test)]
+        second: u8,
+    },
+    Unit,
+}
+
+#[test]
+fn field_cfgs() {
+    let a = Configured {
+        first: 1,
+        second: 2,
+    };
+    let mut b = Configured::default();
+    assert!(b < a);
+    b.clone_from(&a);
+    assert_eq!(b, a);
+    assert_eq!(format!("{b:?}"), "Configured { first: 1, second: 2 }");
+    assert_eq!(xx_hash_64_0(&a), xx_hash_64_0(&b));
+    assert_ne!(xx_hash_64_0(&a), xx_hash_64_0(Configured::default()));
+
+    let c = Configurable::Pair {
+        first: 1,
+        second: 2,
+    };
+    assert_eq!(
+        format!("{:?}", c.clone()),
+        "Configurable::Pair { first: 1, second: 2 }"
+    );
+    assert!(Configurable::default() < c && c < Configurable::Unit);
+    assert_ne!(xx_hash_64_0(&c), xx_hash_64_0(Configurable::default()));
+}
+
+#[autoimpl(Clone, Debug, PartialEq ignore self.r#match, self.foo)]
+#[autoimpl(Deref using self.r#type)]
+#[allow(unused)]
+struct Schema {
+    r#type: u8,
+    r#match: u8,
+    r#foo: u8,
+}
+
+#[autoimpl(Debug, PartialEq)]
+enum Token {
+    r#Match { r#type: u8 },
+}
+
+#[test]
+fn raw_idents() {
+    let a = Schema {
+        r#type: 1,
+        r#match: 2,
+        foo: 3,
+    };
+    let b = Schema {
+        r#type: 1,
+        r#match: 0,
+        foo: 0,
+    };
+    assert_eq!(a, b);
+    assert_eq!(*a.clone(), 1);
+    assert_eq!(format!("{a:?}"), "Schema { type: 1, .. }");
+
+    let t = Token::Match { r#type: 4 };
+    assert_eq!(format!("{t:?}"), "Token::Match { type: 4 }");
+}
+
+#[autoimpl(Clone)]
+#[autoimpl(PartialEq<u8> using self.0)]
+#[derive(Debug, PartialEq)]
+struct Level(u8);
+
+#[test]
+fn distinct_impls() {
+    let a = Level(2);
+    assert_eq!(a, 2u8);
+    assert_eq!(a.clone(), a);
+}
+
+#[autoimpl(Clone(ignore self.cache), Debug(ignore self.secret), PartialEq(using self.id))]
+#[autoimpl(Default)]
+struct Grouped {
+    id: u32,
+    cache: u32,
+    secret: u32,
+}
+
+#[test]
+fn grouped_args() {
+    let a = Grouped {
+        id: 1,
+        cache: 2,
+        secret: 3,
+    };
+    let b = a.clone();
+    assert_eq!((b.id, b.cache, b.secret), (1, 0, 3));
+    assert_eq!(format!("{a:?}"), "Grouped { id: 1, cache: 2, .. }");
+    assert_eq!(
+        a,
+        Grouped {
+            id: 1,
+            ..Default::default()
+        }
+    );
+}
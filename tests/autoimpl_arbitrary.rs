@@ -0,0 +1,82 @@
+//! Test #[autoimpl] for arbitrary::Arbitrary
+#![cfg(feature = "arbitrary")]
+
+use arbitrary::{Arbitrary, Unstructured};
+use impl_tools::autoimpl;
+
+#[autoimpl(Arbitrary ignore self.cache)]
+#[derive(Debug, PartialEq)]
+struct Sample {
+    a: u8,
+    b: bool,
+    cache: Option<u32>,
+}
+
+#[test]
+fn construct() {
+    let mut u = Unstructured::new(&[7, 1, 0xff]);
+    let sample = Sample::arbitrary(&mut u).unwrap();
+    assert_eq!(
+        sample,
+        Sample {
+            a: 7,
+            b: true,
+            cache: None,
+        }
+    );
+}
+
+#[autoimpl(Arbitrary)]
+#[derive(Debug)]
+#[allow(unused)]
+enum Expr {
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Lit(u8),
+}
+
+fn depth(expr: &Expr) -> usize {
+    match expr {
+        Expr::Neg(a) => 1 + depth(a),
+        Expr::Add(a, b) => 1 + depth(a).max(depth(b)),
+        Expr::Lit(_) => 1,
+    }
+}
+
+#[test]
+fn recursive_enum() {
+    // The first variant is recursive and chosen on exhausted input
+    let mut u = Unstructured::new(&[]);
+    assert!(Expr::arbitrary(&mut u).is_err());
+
+    let data: Vec<u8> = (0..=255).cycle().take(4096).collect();
+    for start in 0..64 {
+        let mut u = Unstructured::new(&data[start..]);
+        if let Ok(expr) = Expr::arbitrary(&mut u) {
+            assert!(depth(&expr) <= data.len());
+        }
+    }
+}
+
+mod facade {
+    pub use ::arbitrary;
+    pub use ::core;
+    pub use ::std;
+}
+
+#[autoimpl(Arbitrary, crate = facade)]
+#[derive(Debug, PartialEq)]
+struct Facaded(u8);
+
+#[autoimpl(Arbitrary, crate = facade)]
+#[derive(Debug, PartialEq)]
+enum FacadedEnum {
+    A(u8),
+}
+
+#[test]
+fn crate_path() {
+    let mut u = Unstructured::new(&[3, 0, 0, 0, 0, 4]);
+    assert_eq!(Facaded::arbitrary(&mut u).unwrap(), Facaded(3));
+    assert_eq!(FacadedEnum::arbitrary(&mut u).unwrap(), FacadedEnum::A(4));
+}
@@ -0,0 +1,90 @@
+//! Test #[autoimpl(dispatch ...)]
+
+// Test no_std
+#![no_std]
+
+use impl_tools::autoimpl;
+
+#[autoimpl(dispatch Counter { Up, Down })]
+trait Count {
+    fn get(&self) -> i32;
+    fn step(&mut self, n: i32);
+    fn into_value(self) -> i32
+    where
+        Self: Sized;
+    fn describe(&self) -> &'static str {
+        "counter"
+    }
+}
+
+struct Up(i32);
+impl Count for Up {
+    fn get(&self) -> i32 {
+        self.0
+    }
+    fn step(&mut self, n: i32) {
+        self.0 += n;
+    }
+    fn into_value(self) -> i32 {
+        self.0
+    }
+    fn describe(&self) -> &'static str {
+        "up"
+    }
+}
+
+struct Down(i32);
+impl Count for Down {
+    fn get(&self) -> i32 {
+        self.0
+    }
+    fn step(&mut self, n: i32) {
+        self.0 -= n;
+    }
+    fn into_value(self) -> i32 {
+        self.0
+    }
+}
+
+enum Counter {
+    Up(Up),
+    Down(Down),
+}
+
+#[test]
+fn dispatch() {
+    let mut up = Counter::Up(Up(1));
+    let mut down = Counter::Down(Down(1));
+    up.step(2);
+    down.step(2);
+    assert_eq!(up.get(), 3);
+    assert_eq!(down.get(), -1);
+    assert_eq!(up.describe(), "up");
+    assert_eq!(down.describe(), "counter");
+    assert_eq!(up.into_value(), 3);
+}
+
+#[autoimpl(dispatch<T: trait> Either<T> { Left, Right })]
+trait Scale {
+    fn scale(&self, factor: u32) -> u32;
+}
+
+struct Double;
+impl Scale for Double {
+    fn scale(&self, factor: u32) -> u32 {
+        2 * factor
+    }
+}
+
+enum Either<T> {
+    Left(T),
+    Right(Double),
+}
+
+#[test]
+fn dispatch_generic() {
+    let left: Either<Double> = Either::Left(Double);
+    let right: Either<Double> = Either::Right(Double);
+    assert_eq!(left.scale(3), 6);
+    assert_eq!(right.scale(4), 8);
+}
@@ -10,6 +10,7 @@ use impl_tools::{autoimpl, impl_default};
 fn test_has_clone(_: impl Clone) {}
 fn test_has_copy(_: impl Copy) {}
 
+#[allow(unused)]
 #[autoimpl(std::clone::Clone, core::fmt::Debug)]
 #[autoimpl(std::cmp::PartialEq, std::cmp::Eq, core::hash::Hash)]
 enum Void {}
@@ -57,3 +58,69 @@ fn my_option() {
     test_has_copy(MyOption::Some(1));
     assert_eq!(format!("{:?}", MyOption::Some(1)), "MyOption::Some(1)");
 }
+
+#[autoimpl(Debug where T: trait)]
+#[autoimpl(Display fmt
+    Self::Io(e) => "I/O error: {e}",
+    Self::Parse { line, col } => "parse error at {line}:{col}",
+    Self::Empty | Self::Eof => "unexpected end of input",
+    where T: trait
+)]
+enum ReadError<T> {
+    Io(T),
+    Parse { line: u32, col: u32 },
+    Empty,
+    Eof,
+}
+
+#[test]
+fn display_variants() {
+    let io: ReadError<&str> = ReadError::Io("denied");
+    assert_eq!(format!("{io}"), "I/O error: denied");
+    let parse: ReadError<&str> = ReadError::Parse { line: 3, col: 7 };
+    assert_eq!(format!("{parse}"), "parse error at 3:7");
+    assert_eq!(
+        format!("{}", ReadError::<&str>::Empty),
+        "unexpected end of input"
+    );
+    assert_eq!(
+        format!("{}", ReadError::<&str>::Eof),
+        "unexpected end of input"
+    );
+}
+
+#[autoimpl(Into<u8>, TryFrom<u8>)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
+enum Opcode {
+    Nop = 0,
+    Load = 0x10,
+    Store = 0x11,
+    Halt = 0xff,
+}
+
+#[test]
+fn integer_conversions() {
+    use core::convert::TryFrom;
+
+    assert_eq!(u8::from(Opcode::Load), 0x10);
+    let halt: u8 = Opcode::Halt.into();
+    assert_eq!(halt, 0xff);
+    assert_eq!(Opcode::try_from(0), Ok(Opcode::Nop));
+    assert_eq!(Opcode::try_from(0x11), Ok(Opcode::Store));
+    assert_eq!(Opcode::try_from(0x12), Err(0x12));
+
+    // Values are compared in the representation type, without truncation
+    assert_eq!(u32::from(Wide::High), 257);
+    assert_eq!(Wide::try_from(1u8), Ok(Wide::Low));
+    assert_eq!(Wide::try_from(257u32), Ok(Wide::High));
+    assert_eq!(Wide::try_from(0x1_0001u32), Err(0x1_0001));
+}
+
+#[autoimpl(Into<u32>, TryFrom<u8>, TryFrom<u32>)]
+#[derive(Debug, PartialEq)]
+#[repr(u16)]
+enum Wide {
+    High = 257,
+    Low = 1,
+}
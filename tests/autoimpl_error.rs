@@ -0,0 +1,61 @@
+//! Test #[autoimpl(Error)]
+
+use impl_tools::autoimpl;
+use std::error::Error;
+use std::fmt;
+
+#[autoimpl(Debug)]
+struct Leaf;
+
+impl fmt::Display for Leaf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("leaf")
+    }
+}
+
+impl Error for Leaf {}
+
+#[autoimpl(Debug)]
+#[autoimpl(Display using self.msg)]
+#[autoimpl(Error using self.source)]
+struct Wrapped {
+    msg: &'static str,
+    source: Leaf,
+}
+
+#[autoimpl(Debug)]
+#[autoimpl(Display using self.0)]
+#[autoimpl(Error using self.1)]
+struct Maybe(&'static str, Option<Wrapped>);
+
+#[autoimpl(Debug)]
+#[autoimpl(Display using self.0)]
+#[autoimpl(Error using self.1)]
+struct Boxed(&'static str, Option<Box<dyn Error + Send + Sync>>);
+
+#[autoimpl(Debug)]
+#[autoimpl(Display using self.0)]
+#[autoimpl(Error using self.1)]
+struct AlwaysBoxed(&'static str, std::boxed::Box<dyn Error + Send + Sync>);
+
+#[test]
+fn source() {
+    let err = Wrapped {
+        msg: "wrapped",
+        source: Leaf,
+    };
+    assert_eq!(err.to_string(), "wrapped");
+    assert_eq!(err.source().unwrap().to_string(), "leaf");
+    assert!(err.source().unwrap().source().is_none());
+
+    let err = Maybe("maybe", Some(err));
+    assert_eq!(err.source().unwrap().to_string(), "wrapped");
+    assert!(Maybe("none", None).source().is_none());
+
+    let err = Boxed("boxed", Some(Box::new(Leaf)));
+    assert_eq!(err.source().unwrap().to_string(), "leaf");
+    assert!(Boxed("none", None).source().is_none());
+
+    let err = AlwaysBoxed("always", "message".into());
+    assert_eq!(err.source().unwrap().to_string(), "message");
+}
@@ -0,0 +1,64 @@
+//! Test #[autoimpl(Future)]
+
+// Test no_std
+#![no_std]
+
+use core::future::Future;
+use core::marker::PhantomPinned;
+use core::pin::Pin;
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use impl_tools::autoimpl;
+
+fn noop_waker() -> Waker {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(core::ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+    unsafe { Waker::from_raw(clone(core::ptr::null())) }
+}
+
+/// Ready on the second poll
+struct Twice(bool, PhantomPinned);
+
+impl Future for Twice {
+    type Output = u32;
+    fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<u32> {
+        let this = unsafe { self.get_unchecked_mut() };
+        if this.0 {
+            Poll::Ready(2)
+        } else {
+            this.0 = true;
+            Poll::Pending
+        }
+    }
+}
+
+#[autoimpl(Future using self.inner where F: trait)]
+struct Labelled<F> {
+    _label: &'static str,
+    inner: F,
+}
+
+fn is_unpin<T: Unpin>(_: &T) {}
+
+#[test]
+fn future() {
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    let mut ready = Labelled {
+        _label: "ready",
+        inner: core::future::ready(1),
+    };
+    is_unpin(&ready);
+    assert_eq!(Pin::new(&mut ready).poll(&mut cx), Poll::Ready(1));
+
+    let mut twice = Labelled {
+        _label: "twice",
+        inner: Twice(false, PhantomPinned),
+    };
+    let mut twice = unsafe { Pin::new_unchecked(&mut twice) };
+    assert_eq!(twice.as_mut().poll(&mut cx), Poll::Pending);
+    assert_eq!(twice.as_mut().poll(&mut cx), Poll::Ready(2));
+}
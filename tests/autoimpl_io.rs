@@ -0,0 +1,32 @@
+//! Test #[autoimpl] for std::io traits
+
+use impl_tools::autoimpl;
+use std::io::{self, BufRead, Read};
+
+#[autoimpl(BufRead using self.inner where R: trait)]
+struct Counting<R> {
+    inner: R,
+    reads: usize,
+}
+
+impl<R: Read> Read for Counting<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reads += 1;
+        self.inner.read(buf)
+    }
+}
+
+#[test]
+fn buf_read() {
+    let mut reader = Counting {
+        inner: io::Cursor::new("one\ntwo\n"),
+        reads: 0,
+    };
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    assert_eq!(line, "one\n");
+    assert_eq!(reader.fill_buf().unwrap(), b"two\n");
+    reader.consume(1);
+    let lines: Vec<_> = reader.lines().map(Result::unwrap).collect();
+    assert_eq!(lines, ["wo"]);
+}
@@ -0,0 +1,138 @@
+//! Test #[autoimpl] for serde traits
+#![cfg(feature = "serde")]
+
+use impl_tools::autoimpl;
+use serde_test::{
+    assert_de_tokens, assert_de_tokens_error, assert_ser_tokens, assert_tokens, Token,
+};
+
+#[autoimpl(Serialize, Deserialize ignore self.cache)]
+#[derive(Debug, PartialEq)]
+struct Config {
+    name: String,
+    #[autoimpl(rename = "level")]
+    depth: u8,
+    cache: Option<u32>,
+}
+
+#[test]
+fn named() {
+    let config = Config {
+        name: "a".into(),
+        depth: 2,
+        cache: None,
+    };
+    assert_tokens(
+        &config,
+        &[
+            Token::Struct {
+                name: "Config",
+                len: 2,
+            },
+            Token::Str("name"),
+            Token::Str("a"),
+            Token::Str("level"),
+            Token::U8(2),
+            Token::StructEnd,
+        ],
+    );
+
+    // Ignored fields are not serialized
+    let cached = Config {
+        cache: Some(1),
+        ..config
+    };
+    assert_ser_tokens(
+        &cached,
+        &[
+            Token::Struct {
+                name: "Config",
+                len: 2,
+            },
+            Token::Str("name"),
+            Token::Str("a"),
+            Token::Str("level"),
+            Token::U8(2),
+            Token::StructEnd,
+        ],
+    );
+}
+
+#[test]
+fn named_de() {
+    assert_de_tokens_error::<Config>(
+        &[
+            Token::Struct {
+                name: "Config",
+                len: 1,
+            },
+            Token::Str("name"),
+            Token::Str("a"),
+            Token::StructEnd,
+        ],
+        "missing field `level`",
+    );
+    // Ignored fields are not keys, thus are skipped as unknown
+    assert_de_tokens(
+        &Config {
+            name: "b".into(),
+            depth: 0,
+            cache: None,
+        },
+        &[
+            Token::Struct {
+                name: "Config",
+                len: 3,
+            },
+            Token::Str("name"),
+            Token::Str("b"),
+            Token::Str("cache"),
+            Token::U32(5),
+            Token::Str("level"),
+            Token::U8(0),
+            Token::StructEnd,
+        ],
+    );
+}
+
+#[autoimpl(Serialize, Deserialize ignore self.1)]
+#[derive(Debug, PartialEq)]
+struct Pair(u16, u8, bool);
+
+#[test]
+fn unnamed() {
+    assert_tokens(
+        &Pair(1, 0, true),
+        &[
+            Token::TupleStruct {
+                name: "Pair",
+                len: 2,
+            },
+            Token::U16(1),
+            Token::Bool(true),
+            Token::TupleStructEnd,
+        ],
+    );
+}
+
+#[autoimpl(Serialize, Deserialize using self.id)]
+#[derive(Debug, PartialEq)]
+struct Id<T> {
+    id: u32,
+    tag: core::marker::PhantomData<T>,
+}
+
+#[autoimpl(Serialize, Deserialize)]
+#[derive(Debug, PartialEq)]
+struct Marker;
+
+#[test]
+fn using_and_unit() {
+    let id: Id<()> = Id {
+        id: 7,
+        tag: Default::default(),
+    };
+    assert_tokens(&id, &[Token::U32(7)]);
+
+    assert_tokens(&Marker, &[Token::UnitStruct { name: "Marker" }]);
+}
@@ -0,0 +1,64 @@
+//! Test #[autoimpl] for zeroize traits
+#![cfg(feature = "zeroize")]
+
+use core::cell::Cell;
+use impl_tools::autoimpl;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+#[autoimpl(Zeroize ignore self.id)]
+struct Secret {
+    id: u32,
+    key: [u8; 4],
+    note: String,
+}
+
+#[test]
+fn zeroize() {
+    let mut s = Secret {
+        id: 1,
+        key: [1, 2, 3, 4],
+        note: "n".into(),
+    };
+    s.zeroize();
+    assert_eq!((s.id, s.key, s.note.as_str()), (1, [0; 4], ""));
+}
+
+/// Records zeroization in a cell, thus is observable after drop
+struct Probe<'a>(&'a Cell<bool>);
+impl<'a> Zeroize for Probe<'a> {
+    fn zeroize(&mut self) {
+        self.0.set(true);
+    }
+}
+
+#[autoimpl(ZeroizeOnDrop ignore self.1)]
+struct Guarded<'a>(Probe<'a>, #[allow(unused)] Probe<'a>);
+
+fn has_zeroize_on_drop(_: &impl ZeroizeOnDrop) {}
+
+#[test]
+fn zeroize_on_drop() {
+    let (a, b) = (Cell::new(false), Cell::new(false));
+    let guarded = Guarded(Probe(&a), Probe(&b));
+    has_zeroize_on_drop(&guarded);
+    drop(guarded);
+    assert!(a.get());
+    assert!(!b.get());
+}
+
+mod facade {
+    pub use ::core;
+    pub use ::zeroize;
+}
+
+#[autoimpl(Zeroize, crate = facade)]
+#[autoimpl(ZeroizeOnDrop, crate = facade ignore self.0)]
+struct Facaded(u8, Vec<u8>);
+
+#[test]
+fn crate_path() {
+    let mut x = Facaded(1, vec![2]);
+    x.zeroize();
+    assert_eq!((x.0, x.1.len()), (0, 0));
+    has_zeroize_on_drop(&x);
+}
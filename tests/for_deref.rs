@@ -1,5 +1,7 @@
 //! Test #[autoimpl] for trait references
 
+// `cfg` attributes on trait items are copied to generated impls
+#![allow(unexpected_cfgs)]
 // Test no_std
 #![no_std]
 extern crate alloc;
@@ -8,6 +10,7 @@ use alloc::boxed::Box;
 use core::fmt::Debug;
 use impl_tools::autoimpl;
 
+#[allow(dead_code)]
 #[autoimpl(for<'a, T: trait> &'a mut T, Box<T>)]
 trait Z {
     const A: i32;
@@ -55,6 +58,7 @@ where
 }
 
 #[test]
+#[allow(clippy::needless_borrows_for_generic_args)]
 fn g() {
     struct S;
     impl G<i32> for S {
@@ -80,6 +84,7 @@ fn g() {
     impls_g(&mut (Box::new(S) as Box<dyn G<i32>>));
 }
 
+#[allow(dead_code)]
 #[autoimpl(for<T: trait + ?Sized> &T)]
 trait H<X, Y: G<X>>
 where
@@ -145,6 +150,7 @@ fn custom_deref_target() {
     assert_eq!(y, 12);
 }
 
+#[allow(dead_code)]
 #[autoimpl(for<T: trait + ?Sized> &T)]
 trait Cfgs {
     #[cfg(test)]
@@ -153,3 +159,142 @@ trait Cfgs {
     #[cfg(feature = "never")]
     fn excluded(&self);
 }
+
+#[autoimpl(for<T: trait + ?Sized> &T, &mut T, Box<T>, alloc::rc::Rc<T>)]
+trait Counter {
+    fn get(&self) -> u32;
+    fn bump(&mut self) {}
+}
+
+#[autoimpl(for<T: trait + ?Sized> &T, &mut T with report)]
+trait Reported {
+    fn value(&self) -> u32;
+    fn reset(&mut self) {}
+    fn doubled(&self) -> u32
+    where
+        Self: Sized,
+    {
+        2 * self.value()
+    }
+}
+
+#[test]
+fn report() {
+    struct V(u32);
+    impl Reported for V {
+        fn value(&self) -> u32 {
+            self.0
+        }
+    }
+
+    let mut v = V(4);
+    Reported::reset(&mut &mut v);
+    assert_eq!(Reported::value(&&v), 4);
+    assert_eq!(Reported::doubled(&&v), 8);
+}
+
+#[allow(dead_code)]
+#[autoimpl(for<T: trait> &T, Box<T>)]
+trait Sizes {
+    fn size(&self) -> usize;
+    fn double(&self) -> usize
+    where
+        Self: Sized;
+}
+
+#[autoimpl(for<T: trait + ?Sized> &T, Box<T>)]
+trait Objects {
+    fn size(&self) -> usize;
+    fn double(&self) -> usize
+    where
+        Self: Sized,
+    {
+        2 * self.size()
+    }
+}
+
+#[autoimpl(for<T: trait> &T, Box<T>)]
+trait Named {
+    fn name(&self) -> &'static str;
+    fn into_name(self) -> &'static str
+    where
+        Self: Sized,
+    {
+        "default"
+    }
+    fn copied(&self) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+}
+
+#[test]
+fn sized_methods() {
+    struct S;
+    impl Sizes for S {
+        fn size(&self) -> usize {
+            1
+        }
+        fn double(&self) -> usize {
+            10
+        }
+    }
+    impl Objects for S {
+        fn size(&self) -> usize {
+            3
+        }
+        fn double(&self) -> usize {
+            30
+        }
+    }
+
+    // Forwarded for sized definitive type
+    assert_eq!(Sizes::double(&&S), 10);
+    assert_eq!(Sizes::double(&Box::new(S)), 10);
+
+    // Default implementation where the method cannot be forwarded
+    impl Named for S {
+        fn name(&self) -> &'static str {
+            "S"
+        }
+        fn into_name(self) -> &'static str {
+            "by value"
+        }
+        fn copied(&self) -> Option<Self> {
+            Some(S)
+        }
+    }
+    assert_eq!(S.into_name(), "by value");
+    assert_eq!((&S).into_name(), "default");
+    assert_eq!(Named::name(&Box::new(S)), "S");
+    assert!(Named::copied(&Box::new(S)).is_none());
+
+    // Default implementation with possibly-unsized definitive type
+    let obj: Box<dyn Objects> = Box::new(S);
+    assert_eq!(Objects::double(&obj), 6);
+    assert_eq!(Objects::double(&&S), 6);
+}
+
+#[test]
+fn read_only_targets() {
+    struct C(u32);
+    impl Counter for C {
+        fn get(&self) -> u32 {
+            self.0
+        }
+        fn bump(&mut self) {
+            self.0 += 1;
+        }
+    }
+
+    let mut c = C(1);
+    let mut r = &mut c;
+    <&mut C as Counter>::bump(&mut r);
+    assert_eq!(<&mut C as Counter>::get(&r), 2);
+
+    let mut rc = alloc::rc::Rc::new(C(5));
+    <alloc::rc::Rc<C> as Counter>::bump(&mut rc); // default impl: does nothing
+    assert_eq!(<alloc::rc::Rc<C> as Counter>::get(&rc), 5);
+}
@@ -56,3 +56,194 @@ fn person() {
     assert_eq!(person.age, 72);
     assert_eq!(person.occupation, "");
 }
+
+impl_scope! {
+    #[impl_default]
+    struct Coord(i32 = 1, i32, String = "z".to_string());
+}
+
+#[test]
+fn coord() {
+    let c = Coord::default();
+    assert_eq!((c.0, c.1, c.2.as_str()), (1, 0, "z"));
+}
+
+impl_scope! {
+    #[impl_try_default(core::num::ParseIntError)]
+    struct Config {
+        port: u16 = "8080".parse()?,
+        name: String = "server".to_string(),
+        retries: u8,
+    }
+}
+
+impl_scope! {
+    #[impl_try_default(core::num::ParseIntError)]
+    struct Broken(u8 = "300".parse()?);
+}
+
+#[test]
+fn try_default() {
+    let config = Config::try_default().unwrap();
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.name, "server");
+    assert_eq!(config.retries, 0);
+
+    assert!(Broken::try_default().is_err());
+}
+
+#[impl_default(Span(3, 5), validate = Span::check)]
+#[derive(Debug)]
+struct Span(u32, u32);
+
+impl Span {
+    fn check(&self) -> Result<(), &'static str> {
+        if self.0 <= self.1 {
+            Ok(())
+        } else {
+            Err("start > end")
+        }
+    }
+}
+
+impl_scope! {
+    #[impl_default(validate = Self::check)]
+    #[impl_try_default(&'static str, validate = Self::check)]
+    #[impl_new(pub(crate), validate = Self::check)]
+    struct Window {
+        start: u32 = 7,
+        end: u32 = 4,
+    }
+
+    impl Self {
+        fn check(&self) -> Result<(), &'static str> {
+            if self.start <= self.end {
+                Ok(())
+            } else {
+                Err("start > end")
+            }
+        }
+    }
+}
+
+impl_scope! {
+    #[impl_new(validate = Self::check)]
+    struct Ordered {
+        low: u8 = 1,
+        high: u8 = 2,
+    }
+
+    impl Self {
+        fn check(&self) -> Result<(), &'static str> {
+            if self.low <= self.high {
+                Ok(())
+            } else {
+                Err("low > high")
+            }
+        }
+    }
+}
+
+#[test]
+fn validate() {
+    let span = Span::default();
+    assert_eq!((span.0, span.1), (3, 5));
+    assert_eq!(Window::try_default().err(), Some("start > end"));
+    let ordered = Ordered::new();
+    assert_eq!((ordered.low, ordered.high), (1, 2));
+}
+
+#[test]
+#[should_panic(expected = "Window::default(): validation failed: \"start > end\"")]
+fn validate_panics() {
+    let _ = Window::default();
+}
+
+#[test]
+#[should_panic(expected = "Window::new(): validation failed: \"start > end\"")]
+fn validate_new_panics() {
+    let _ = Window::new();
+}
+
+impl_scope! {
+    #[impl_default(validate = Handle::check where T: Default)]
+    #[derive(Debug)]
+    struct Handle<T> {
+        #[impl_default(skip)]
+        name: String,
+        value: T,
+        count: u32 = 1,
+    }
+
+    impl Self {
+        fn check(&self) -> Result<(), &'static str> {
+            if self.name.is_empty() {
+                Err("empty name")
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
+
+impl_scope! {
+    #[impl_default]
+    struct Pair(#[impl_default(skip)] u8, u8, #[impl_default(skip)] char);
+}
+
+#[test]
+fn skip() {
+    let h: Handle<i8> = Handle::new_with("h".to_string());
+    assert_eq!(h.name, "h");
+    assert_eq!(h.value, 0);
+    assert_eq!(h.count, 1);
+
+    let p = Pair::new_with(1, 'x');
+    assert_eq!((p.0, p.1, p.2), (1, 0, 'x'));
+}
+
+#[test]
+#[should_panic(expected = "Handle::new_with(): validation failed: \"empty name\"")]
+fn skip_validate_panics() {
+    let _ = Handle::<()>::new_with(String::new());
+}
+
+/// Generated code must not depend on `core`, `Default` or `panic!` in scope
+mod shadowed {
+    use impl_tools::{impl_default, impl_scope};
+
+    mod core {}
+    trait Default {}
+    macro_rules! panic {
+        ($($tt:tt)*) => {
+            compile_error!("shadowed")
+        };
+    }
+
+    fn check(x: &Level) -> Result<(), ()> {
+        if x.0 < 10 {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    #[impl_default(Level(1), validate = check)]
+    pub struct Level(pub u8);
+
+    impl_scope! {
+        #[impl_default]
+        pub struct Limits {
+            pub low: u8 = 1,
+            pub high: u8,
+        }
+    }
+}
+
+#[test]
+fn shadowed() {
+    use core::default::Default;
+    assert_eq!(shadowed::Level::default().0, 1);
+    let limits = shadowed::Limits::default();
+    assert_eq!((limits.low, limits.high), (1, 0));
+}
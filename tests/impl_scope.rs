@@ -0,0 +1,531 @@
+//! Test impl_scope!
+
+// Test no_std
+#![no_std]
+extern crate alloc;
+use alloc::string::{String, ToString};
+
+use impl_tools::{autoimpl, impl_scope};
+
+impl_scope! {
+    #[derive(Debug, PartialEq)]
+    struct Labelled<'l, T: Default> {
+        label: &'l str,
+        value: T,
+    }
+
+    impl<'a> From<&'a str> for Self where 'a: 'l {
+        fn from(label: &'a str) -> Self {
+            Labelled { label, value: T::default() }
+        }
+    }
+
+    impl<U: Into<T>> Extend<U> for Self {
+        fn extend<I: IntoIterator<Item = U>>(&mut self, iter: I) {
+            if let Some(value) = iter.into_iter().last() {
+                self.value = value.into();
+            }
+        }
+    }
+
+    impl<const N: usize> From<[T; N]> for Self where T: Clone {
+        fn from(arr: [T; N]) -> Self {
+            Labelled { label: "array", value: arr[N - 1].clone() }
+        }
+    }
+}
+
+#[test]
+fn labelled() {
+    let mut l = Labelled::<String>::from("x");
+    assert_eq!(l.value, "");
+    l.extend(["y"]);
+    assert_eq!(l.value, "y");
+
+    let l: Labelled<String> = Labelled::from(["a".to_string(), "b".to_string()]);
+    assert_eq!(l.label, "array");
+    assert_eq!(l.value, "b");
+}
+
+impl_scope! {
+    struct Bag<T>(alloc::vec::Vec<T>);
+
+    impl<'a> IntoIterator for &'a Self {
+        type Item = &'a T;
+        type IntoIter = core::slice::Iter<'a, T>;
+        fn into_iter(self) -> Self::IntoIter {
+            self.0.iter()
+        }
+    }
+
+    impl<'a> IntoIterator for &'a mut Self {
+        type Item = &'a mut T;
+        type IntoIter = core::slice::IterMut<'a, T>;
+        fn into_iter(self) -> Self::IntoIter {
+            self.0.iter_mut()
+        }
+    }
+}
+
+#[test]
+fn bag() {
+    let mut bag = Bag(alloc::vec![1, 2, 3]);
+    for x in &mut bag {
+        *x *= 2;
+    }
+    let sum: i32 = (&bag).into_iter().sum();
+    assert_eq!(sum, 12);
+}
+
+impl_scope! {
+    #![impl_default]
+    #![derive(Debug, PartialEq)]
+    #[allow(unused)]
+    struct Settings {
+        verbose: bool = true,
+        level: u8 = 3,
+    }
+}
+
+#[test]
+fn inner_attrs() {
+    assert_eq!(
+        Settings::default(),
+        Settings {
+            verbose: true,
+            level: 3
+        }
+    );
+}
+
+impl_scope! {
+    #[impl_new(const)]
+    struct Limits {
+        min: i32 = -1,
+        max: i32 = i32::MAX,
+        steps: [u8; 2] = [1, 2],
+    }
+}
+
+impl_scope! {
+    #[impl_new(const)]
+    struct Greeting<'a>(&'a str = "hello", usize = "hello".len());
+}
+
+impl_scope! {
+    #[impl_new]
+    struct Buffer {
+        data: alloc::vec::Vec<u8>,
+        label: String = "buffer".to_string(),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Meters(u32);
+
+impl core::ops::Add for Meters {
+    type Output = Meters;
+    fn add(self, rhs: Meters) -> Meters {
+        Meters(self.0 + rhs.0)
+    }
+}
+
+impl_scope! {
+    // Not const: `Add` is not a const trait
+    #[impl_new]
+    struct Route {
+        length: Meters = Meters(1) + Meters(2),
+    }
+}
+
+// Inferred const: initializers are literals and operators over these
+impl_scope! {
+    #[impl_new]
+    struct Flags {
+        mask: u32 = 1 << 4 | 1,
+        enabled: bool = true,
+        pair: (i8, [char; 2]) = (-3, ['a', 'b']),
+    }
+}
+
+impl_scope! {
+    #[impl_new(where T: trait)]
+    struct Slot<T> {
+        id: u32 = 1,
+        value: T,
+    }
+}
+
+static LIMITS: Limits = Limits::new();
+static GREETING: Greeting = Greeting::new();
+static FLAGS: Flags = Flags::new();
+
+#[test]
+fn impl_new() {
+    assert_eq!(
+        (LIMITS.min, LIMITS.max, LIMITS.steps),
+        (-1, i32::MAX, [1, 2])
+    );
+    assert_eq!((GREETING.0, GREETING.1), ("hello", 5));
+    let buffer = Buffer::new();
+    assert!(buffer.data.is_empty());
+    assert_eq!(buffer.label, "buffer");
+    assert_eq!(Route::new().length, Meters(3));
+    assert_eq!(
+        (FLAGS.mask, FLAGS.enabled, FLAGS.pair),
+        (17, true, (-3, ['a', 'b']))
+    );
+    let slot = Slot::<u8>::new();
+    assert_eq!((slot.id, slot.value), (1, 0));
+}
+
+impl_scope! {
+    #[impl_default]
+    #[impl_new]
+    struct Threshold<T> {
+        level: u32 = Self::LEVEL,
+        limit: Option<T> = Self::no_limit(),
+    }
+
+    impl Self {
+        const LEVEL: u32 = 4;
+        const fn no_limit() -> Option<T> {
+            None
+        }
+    }
+}
+
+#[test]
+fn self_in_initializers() {
+    let t = Threshold::<u8>::default();
+    assert_eq!((t.level, t.limit), (4, None));
+
+    let s = impl_tools::singleton! {
+        struct(u32 = Self::BASE * 2);
+        impl Self {
+            const BASE: u32 = 21;
+            fn value(&self) -> u32 {
+                self.0
+            }
+        }
+    };
+    assert_eq!(s.value(), 42);
+}
+
+mod private_ctor {
+    use impl_tools::impl_scope;
+
+    impl_scope! {
+        #[impl_new(pub(crate) const)]
+        pub struct Token(pub u32 = 1);
+    }
+}
+
+#[test]
+fn impl_new_visibility() {
+    assert_eq!(private_ctor::Token::new().0, 1);
+}
+
+pub fn double(x: u32) -> u32 {
+    2 * x
+}
+
+macro_rules! counter {
+    ($vis:vis $name:ident, $ty:ty = $init:expr, $($method:ident => $op:tt),*) => {
+        impl_scope! {
+            #[impl_default]
+            #[derive(Debug)]
+            $vis struct $name {
+                value: $ty = $crate::double($init),
+                #[allow(unused)]
+                step:$ty=1,
+            }
+
+            impl Self {
+                $(
+                    fn $method(&mut self) -> $ty {
+                        self.value $op self.step;
+                        self.value
+                    }
+                )*
+            }
+
+            impl From<$ty> for $name {
+                fn from(value: $ty) -> Self {
+                    $name { value, step: 1 }
+                }
+            }
+        }
+    };
+}
+
+counter!(pub(crate) Counter, u32 = 5, incr => +=, decr => -=);
+
+#[test]
+fn macro_rules_body() {
+    let mut c = Counter::default();
+    assert_eq!(c.value, 10);
+    assert_eq!(c.incr(), 11);
+    assert_eq!(c.decr(), 10);
+    assert_eq!(Counter::from(3).incr(), 4);
+}
+
+macro_rules! wrapper {
+    ($name:ident, $self:ty) => {
+        impl_scope! {
+            struct $name<T>(T);
+
+            impl $self {
+                fn get(&self) -> &T {
+                    &self.0
+                }
+            }
+
+            impl<'a> IntoIterator for &'a $self {
+                type Item = &'a T;
+                type IntoIter = core::iter::Once<&'a T>;
+                fn into_iter(self) -> Self::IntoIter {
+                    core::iter::once(&self.0)
+                }
+            }
+        }
+    };
+}
+
+wrapper!(Wrapper, Self);
+
+#[test]
+fn macro_rules_self_ty() {
+    let w = Wrapper(3);
+    assert_eq!(*w.get(), 3);
+    assert_eq!((&w).into_iter().next(), Some(&3));
+}
+
+impl_scope! {
+    #[impl_default]
+    #[impl_new]
+    #[derive(Debug, PartialEq)]
+    struct EmptyBraced {}
+}
+
+impl_scope! {
+    #[impl_try_default(())]
+    #[impl_new]
+    #[derive(Debug, PartialEq)]
+    struct EmptyTuple();
+}
+
+impl_scope! {
+    #[impl_default]
+    #[impl_new]
+    #[non_exhaustive]
+    #[derive(Debug, PartialEq)]
+    pub struct Options {
+        pub verbose: bool = true,
+    }
+}
+
+#[test]
+fn zero_fields() {
+    assert_eq!(EmptyBraced::new(), EmptyBraced::default());
+    assert_eq!(EmptyTuple::try_default(), Ok(EmptyTuple::new()));
+    assert_eq!(Options::new(), Options::default());
+    assert!(Options::new().verbose);
+}
+
+impl_scope! {
+    #[impl_default]
+    #[impl_tools::autoimpl(Clone, Debug)]
+    pub struct Tracked {
+        pub value: i32 = 5,
+        #[autoimpl(hidden)]
+        pub generation: u32 = 1,
+    }
+}
+
+#[test]
+fn hidden_field() {
+    let t = Tracked::default();
+    assert_eq!(t.generation, 1);
+    let u = t.clone();
+    assert_eq!(u.generation, 1);
+    assert_eq!(alloc::format!("{:?}", u), "Tracked { value: 5, .. }");
+}
+
+impl_scope! {
+    #[impl_debug_display("({x}, {y})" where T: trait)]
+    struct Point<T> {
+        x: T,
+        y: T,
+    }
+}
+
+impl_scope! {
+    #[impl_debug_display("{_0}{}", self.1.unit())]
+    struct Length(f32, Unit);
+}
+
+#[derive(Debug)]
+enum Unit {
+    Metre,
+}
+
+impl Unit {
+    fn unit(&self) -> &'static str {
+        match self {
+            Unit::Metre => "m",
+        }
+    }
+}
+
+impl_scope! {
+    #[impl_debug_display("{_0}" where T: trait)]
+    struct Bounded<T>(T);
+}
+
+#[test]
+fn debug_display() {
+    let p = Point { x: 1, y: 2 };
+    assert_eq!(p.to_string(), "(1, 2)");
+    assert_eq!(alloc::format!("{:?}", p), "Point { x: 1, y: 2 }");
+
+    let l = Length(2.5, Unit::Metre);
+    assert_eq!(l.to_string(), "2.5m");
+    assert_eq!(alloc::format!("{:?}", l), "Length(2.5, Metre)");
+
+    let b = Bounded("x");
+    assert_eq!(b.to_string(), "x");
+    assert_eq!(alloc::format!("{:?}", b), "Bounded(\"x\")");
+
+    let u = User {
+        login_name: "ann",
+        token: 7,
+        cache: 1,
+    };
+    assert_eq!(u.to_string(), "ann");
+    assert_eq!(
+        alloc::format!("{:?}", u),
+        "User { name: \"ann\", token: \"***\", .. }"
+    );
+
+    let m = Masked(1, 2);
+    assert_eq!(alloc::format!("{:?}", m), "Masked(1, _)");
+
+    let p = Password {
+        user: "ann",
+        secret: alloc::string::String::from("hunter2"),
+    };
+    assert_eq!(p.to_string(), "ann");
+    assert_eq!(alloc::format!("{:?}", p), "Password { login: \"ann\", .. }");
+}
+
+// Field attributes of `#[autoimpl]` apply to `Debug`
+impl_scope! {
+    #[impl_debug_display("{login_name}")]
+    #[autoimpl(Clone)]
+    struct User {
+        #[autoimpl(rename = "name")]
+        login_name: &'static str,
+        #[autoimpl(debug = "***")]
+        token: u32,
+        #[autoimpl(hidden)]
+        cache: u32,
+    }
+}
+
+impl_scope! {
+    #[impl_debug_display("{_0}")]
+    #[autoimpl(Clone)]
+    struct Masked(u8, #[autoimpl(hidden)] u8);
+}
+
+// Field attributes are removed without a struct-level `#[autoimpl]`
+impl_scope! {
+    #[impl_debug_display("{user}")]
+    struct Password {
+        #[autoimpl(rename = "login")]
+        user: &'static str,
+        #[autoimpl(hidden)]
+        secret: alloc::string::String,
+    }
+}
+
+// A field named `f` does not shadow the formatter
+impl_scope! {
+    #[impl_debug_display("{f}")]
+    struct Frame {
+        f: u8,
+    }
+}
+
+impl_scope! {
+    #[impl_debug_display("{key}", redact self.secret)]
+    struct Credentials<T> {
+        key: &'static str,
+        secret: u32,
+        _marker: core::marker::PhantomData<T>,
+    }
+}
+
+#[test]
+fn debug_display_as_autoimpl() {
+    let f = Frame { f: 3 };
+    assert_eq!(alloc::format!("{f} {f:?}"), "3 Frame { f: 3 }");
+
+    let c = Credentials::<()> {
+        key: "k",
+        secret: 42,
+        _marker: core::marker::PhantomData,
+    };
+    assert_eq!(c.to_string(), "k");
+    assert_eq!(
+        alloc::format!("{c:?}"),
+        "Credentials { key: \"k\", secret: *** }"
+    );
+}
+
+impl_scope! {
+    #[impl_default]
+    #[impl_new]
+    #[impl_debug_display("{first}")]
+    struct Configured {
+        #[cfg(not(test))]
+        missing: Missing = Missing,
+        first: u8 = 1,
+        #[cfg(test)]
+        second: u8,
+    }
+}
+
+#[test]
+fn field_cfgs() {
+    let a = Configured::default();
+    let b = Configured::new();
+    assert_eq!((a.first, a.second, b.first, b.second), (1, 0, 1, 0));
+    assert_eq!(
+        alloc::format!("{a:?}"),
+        "Configured { first: 1, second: 0 }"
+    );
+    assert_eq!(alloc::format!("{b}"), "1");
+}
+
+impl_scope! {
+    #[impl_default]
+    #[impl_debug_display("{}", self.r#type)]
+    struct Tagged {
+        r#type: u8 = 2,
+    }
+}
+
+#[test]
+fn raw_idents() {
+    let t = Tagged::default();
+    assert_eq!(alloc::format!("{t} {t:?}"), "2 Tagged { type: 2 }");
+
+    let s = impl_tools::singleton! {
+        struct {
+            r#type: u8 = 3,
+        }
+    };
+    assert_eq!(s.r#type, 3);
+}
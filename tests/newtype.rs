@@ -1,5 +1,7 @@
 //! Test implementing traits over newtype wrappers
 
+#![allow(clippy::disallowed_names)]
+
 use std::rc::Rc;
 use std::sync::Arc;
 